@@ -0,0 +1,350 @@
+//! Shared per-agent CLI construction.
+//!
+//! Every executor backend (`executors::local`/`docker`/`bwrap`/`remote`) used to hand-roll
+//! its own claude/codex/gemini flag knowledge, and had drifted apart doing it -- e.g.
+//! `executors::remote`'s one-shot builder was missing the codex reasoning-effort
+//! whitelist and `-m` flag that `docker`/`bwrap`/`local` already had, and `docker`/`bwrap`
+//! interpolated an unescaped `model` value straight into a shell string. This module is
+//! the single place that knowledge now lives, used both by those one-shot executors and
+//! by the interactive tmux/PTY session path (`sessions::manager`,
+//! `executors::remote::execute_remote_pty`).
+
+/// Agent executors every backend knows how to launch.
+pub const ALLOWED_EXECUTORS: [&str; 3] = ["claude", "codex", "gemini"];
+
+pub fn is_allowed_executor(executor: &str) -> bool {
+    ALLOWED_EXECUTORS.contains(&executor)
+}
+
+/// `reasoningEffort`/`reasoningLevel` values codex's `-c reasoning.effort=` accepts.
+const REASONING_EFFORTS: [&str; 4] = ["low", "medium", "high", "xhigh"];
+
+fn reasoning_effort(options: &serde_json::Value) -> Option<&str> {
+    let level = options
+        .get("reasoningEffort")
+        .or_else(|| options.get("reasoningLevel"))
+        .and_then(|v| v.as_str())?;
+    REASONING_EFFORTS.contains(&level).then_some(level)
+}
+
+/// Escape a string for shell use: wrap in single quotes, closing/reopening around any
+/// embedded ones (`'` -> `'\''`).
+pub fn shell_escape(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Join `argv` into a single shell command string: the program name bare, every
+/// following argument shell-escaped. Quoting plain flags like `--model` is harmless (a
+/// shell parses `'--model'` identically to `--model`) and means a value interpolated
+/// next to one is never accidentally unescaped.
+fn join_shell_escaped(argv: &[String]) -> String {
+    let Some((prog, args)) = argv.split_first() else {
+        return String::new();
+    };
+    let mut cmd = prog.clone();
+    for a in args {
+        cmd.push(' ');
+        cmd.push_str(&shell_escape(a));
+    }
+    cmd
+}
+
+/// Build a one-shot agent invocation (captured output, process exits when the agent is
+/// done) as `argv`, for a direct process spawn (`executors::local`). `options` is the
+/// node's free-form per-run JSON: `model`, `reasoningEffort`/`reasoningLevel` (codex),
+/// `thinkingBudget` (claude).
+pub fn one_shot_argv(executor: &str, prompt: &str, options: &Option<serde_json::Value>) -> Vec<String> {
+    match executor {
+        "claude" => {
+            let mut args = vec![
+                "claude".to_string(),
+                "-p".to_string(),
+                prompt.to_string(),
+                "--output-format".to_string(),
+                "text".to_string(),
+                "--no-session-persistence".to_string(),
+                "--permission-mode".to_string(),
+                "dontAsk".to_string(),
+                "--tools".to_string(),
+                String::new(),
+            ];
+
+            if let Some(opts) = options {
+                if let Some(model) = opts.get("model").and_then(|v| v.as_str()) {
+                    args.push("--model".to_string());
+                    args.push(model.to_string());
+                }
+                if let Some(budget) = opts.get("thinkingBudget").and_then(|v| v.as_i64()) {
+                    args.push("--append-system-prompt".to_string());
+                    args.push(format!("Think for at most {} tokens.", budget));
+                }
+            }
+
+            args
+        }
+
+        "codex" => {
+            let mut args = vec!["codex".to_string(), "exec".to_string(), "--skip-git-repo-check".to_string()];
+
+            if let Some(opts) = options {
+                if let Some(level) = reasoning_effort(opts) {
+                    args.push("-c".to_string());
+                    args.push(format!("reasoning.effort={}", level));
+                }
+                if let Some(model) = opts.get("model").and_then(|v| v.as_str()) {
+                    args.push("-m".to_string());
+                    args.push(model.to_string());
+                }
+            }
+
+            args.push(prompt.to_string());
+            args
+        }
+
+        "gemini" => {
+            let model: String = options
+                .as_ref()
+                .and_then(|o| o.get("model"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("gemini-3-pro-preview")
+                .chars()
+                .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '.')
+                .collect();
+
+            vec![
+                "gemini".to_string(),
+                prompt.to_string(),
+                "-m".to_string(),
+                model,
+                "-o".to_string(),
+                "text".to_string(),
+            ]
+        }
+
+        _ => vec![],
+    }
+}
+
+/// `one_shot_argv`, joined into a single shell-escaped command string -- for backends
+/// that hand the whole invocation to a container/remote shell instead of exec'ing it
+/// directly (`executors::docker`/`bwrap`/`remote`'s non-interactive paths).
+pub fn one_shot_shell_command(executor: &str, prompt: &str, options: &Option<serde_json::Value>) -> String {
+    if !is_allowed_executor(executor) {
+        return shell_escape(prompt);
+    }
+    join_shell_escaped(&one_shot_argv(executor, prompt, options))
+}
+
+/// Build an interactive agent invocation (prompt passed positionally, no one-shot-only
+/// flags like `-p`/`exec`/`--output-format`) from the node's free-form JSON `options`,
+/// for backends that don't have a typed model/extra-args to validate up front
+/// (`executors::remote::execute_remote_pty`). See `interactive_argv` for the typed
+/// equivalent used by the local tmux/PTY session path.
+pub fn interactive_argv_from_options(executor: &str, prompt: &str, options: &Option<serde_json::Value>) -> Vec<String> {
+    let model = options.as_ref().and_then(|o| o.get("model")).and_then(|v| v.as_str());
+
+    match executor {
+        "claude" => {
+            let mut argv = vec!["claude".to_string()];
+            if let Some(m) = model {
+                argv.push("--model".to_string());
+                argv.push(m.to_string());
+            }
+            argv.push(prompt.to_string());
+            argv
+        }
+
+        "codex" => {
+            let mut argv = vec!["codex".to_string()];
+            if let Some(opts) = options {
+                if let Some(level) = reasoning_effort(opts) {
+                    argv.push("-c".to_string());
+                    argv.push(format!("reasoning.effort={}", level));
+                }
+            }
+            argv.push(prompt.to_string());
+            argv
+        }
+
+        "gemini" => {
+            vec![
+                "gemini".to_string(),
+                "-m".to_string(),
+                model.unwrap_or("gemini-3-pro-preview").to_string(),
+                "-i".to_string(),
+                prompt.to_string(),
+            ]
+        }
+
+        _ => vec![prompt.to_string()],
+    }
+}
+
+/// `interactive_argv_from_options`, joined into a single shell-escaped command string.
+pub fn interactive_shell_command_from_options(
+    executor: &str,
+    prompt: &str,
+    options: &Option<serde_json::Value>,
+) -> String {
+    if !is_allowed_executor(executor) {
+        return shell_escape(prompt);
+    }
+    join_shell_escaped(&interactive_argv_from_options(executor, prompt, options))
+}
+
+/// Validate a user-supplied model name before it's interpolated into a shell command:
+/// reasonable length, restricted to characters real model names actually use.
+pub fn validate_model(model: &str) -> Result<(), String> {
+    if model.is_empty() {
+        return Err("Model must not be empty".to_string());
+    }
+    if model.len() > 128 {
+        return Err("Model is too long".to_string());
+    }
+    if !model
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | ':' | '/'))
+    {
+        return Err("Model contains invalid characters".to_string());
+    }
+    Ok(())
+}
+
+/// Validate a node's `extraArgs` list before they're interpolated into a shell command.
+pub fn validate_extra_args(extra_args: &[String]) -> Result<(), String> {
+    if extra_args.len() > 64 {
+        return Err("Too many extraArgs (max 64)".to_string());
+    }
+    for a in extra_args {
+        if a.is_empty() {
+            return Err("extraArgs contains an empty argument".to_string());
+        }
+        if a.len() > 1024 {
+            return Err("extraArgs contains an argument that is too long".to_string());
+        }
+        if a.contains('\0') {
+            return Err("extraArgs contains an invalid character".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Build an interactive agent invocation (prompt passed positionally) as `argv` from a
+/// typed model/extra-args, validating both first. Used by the local tmux/PTY session
+/// path (`sessions::manager::build_agent_command`) so a malformed model/extra-arg fails
+/// before a session is even created.
+pub fn interactive_argv(
+    executor: &str,
+    model: Option<&str>,
+    extra_args: &[String],
+    prompt: &str,
+) -> Result<Vec<String>, String> {
+    if let Some(m) = model {
+        validate_model(m)?;
+    }
+    validate_extra_args(extra_args)?;
+
+    let prompt = prompt.trim();
+    let mut argv: Vec<String> = Vec::new();
+    match executor {
+        "claude" => {
+            // One-shot uses `-p`/`--print`, but interactive sessions should start
+            // interactive by default and pass the initial message as a positional
+            // `[prompt]` argument.
+            argv.push("claude".to_string());
+            argv.push("--allowedTools".to_string());
+            argv.push("Bash,Read,Write,Edit,Glob,Grep".to_string());
+            if let Some(m) = model {
+                argv.push("--model".to_string());
+                argv.push(m.to_string());
+            }
+            argv.extend(extra_args.iter().cloned());
+            if !prompt.is_empty() {
+                argv.push(prompt.to_string());
+            }
+        }
+        "codex" => {
+            // One-shot uses `codex exec`, but interactive sessions should omit the
+            // subcommand.
+            argv.push("codex".to_string());
+            if let Some(m) = model {
+                argv.push("--model".to_string());
+                argv.push(m.to_string());
+            }
+            argv.extend(extra_args.iter().cloned());
+            if !prompt.is_empty() {
+                argv.push(prompt.to_string());
+            }
+        }
+        "gemini" => {
+            // Positional prompt defaults to one-shot; for interactive, use
+            // `-i`/`--prompt-interactive`.
+            argv.push("gemini".to_string());
+            if let Some(m) = model {
+                argv.push("-m".to_string());
+                argv.push(m.to_string());
+            }
+            argv.extend(extra_args.iter().cloned());
+            if !prompt.is_empty() {
+                argv.push("-i".to_string());
+                argv.push(prompt.to_string());
+            }
+        }
+        other => return Err(format!("Unsupported agent type: {}", other)),
+    }
+
+    Ok(argv)
+}
+
+/// `interactive_argv`, joined into a single shell-escaped command string.
+pub fn interactive_shell_command(
+    executor: &str,
+    model: Option<&str>,
+    extra_args: &[String],
+    prompt: &str,
+) -> Result<String, String> {
+    Ok(join_shell_escaped(&interactive_argv(executor, model, extra_args, prompt)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_shot_claude_includes_model_and_escapes_it() {
+        let options = serde_json::json!({ "model": "sonnet' ; rm -rf /" });
+        let cmd = one_shot_shell_command("claude", "hello", &Some(options));
+        assert!(cmd.contains("'hello'"), "cmd was: {}", cmd);
+        assert!(cmd.contains("--model"), "cmd was: {}", cmd);
+        assert!(cmd.contains("'sonnet'\\'' ; rm -rf /'"), "model value must be shell-escaped: {}", cmd);
+    }
+
+    #[test]
+    fn one_shot_codex_includes_reasoning_effort_and_model() {
+        let options = serde_json::json!({ "reasoningEffort": "high", "model": "gpt-5" });
+        let cmd = one_shot_shell_command("codex", "do it", &Some(options));
+        assert!(cmd.contains("reasoning.effort=high"), "cmd was: {}", cmd);
+        assert!(cmd.contains("-m"), "cmd was: {}", cmd);
+        assert!(cmd.contains("'do it'"), "cmd was: {}", cmd);
+    }
+
+    #[test]
+    fn one_shot_codex_rejects_unknown_reasoning_effort() {
+        let options = serde_json::json!({ "reasoningEffort": "nonsense" });
+        let cmd = one_shot_shell_command("codex", "do it", &Some(options));
+        assert!(!cmd.contains("reasoning.effort"), "cmd was: {}", cmd);
+    }
+
+    #[test]
+    fn interactive_argv_rejects_invalid_model() {
+        let err = interactive_argv("claude", Some("bad model!"), &[], "hi").unwrap_err();
+        assert!(err.contains("invalid characters"));
+    }
+
+    #[test]
+    fn interactive_argv_codex_omits_exec_subcommand() {
+        let argv = interactive_argv("codex", Some("gpt-5"), &[], "do it").expect("argv");
+        assert!(!argv.contains(&"exec".to_string()), "argv was: {:?}", argv);
+        assert!(argv.contains(&"do it".to_string()), "argv was: {:?}", argv);
+    }
+}
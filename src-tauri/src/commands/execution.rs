@@ -1,8 +1,122 @@
 use serde::{Deserialize, Serialize};
+use std::process::Stdio;
 use tauri::Emitter;
 
-use crate::executors;
 use crate::state::{AppState, RunningProcess};
+use crate::terminal::TerminalEmulator;
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Default PTY window size for `ExecuteNodeInput::use_pty`: same defaults
+/// `executors::local`'s own PTY path uses, generous enough that most CLIs' progress
+/// bars/tables don't wrap.
+const DEFAULT_PTY_ROWS: u16 = 40;
+const DEFAULT_PTY_COLS: u16 = 120;
+
+/// How long `pump_output`/`pump_error` wait for more bytes before flushing a
+/// `line_buffered` session's pending partial line anyway, so a line-oriented consumer
+/// isn't left waiting forever for a final line that never gets a trailing newline (e.g.
+/// the agent is idle mid-line).
+const LINE_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Force a `line_buffered` line out even without a trailing newline once it grows this
+/// long, so a chatty process that never prints one doesn't grow the buffer unbounded.
+const LINE_MAX_BYTES: usize = 64 * 1024;
+
+/// Decodes bytes to UTF-8 incrementally across read boundaries, so a multi-byte
+/// character split across two 4096-byte reads doesn't get corrupted into a stray
+/// replacement character the way a plain `String::from_utf8_lossy` per read would.
+/// Holds back up to 3 trailing bytes that don't yet form a complete codepoint and
+/// prepends them to the next call.
+#[derive(Default)]
+struct IncrementalUtf8Decoder {
+    tail: Vec<u8>,
+}
+
+impl IncrementalUtf8Decoder {
+    /// Decode as much of the buffered tail plus `bytes` as forms complete UTF-8, holding
+    /// back any trailing incomplete sequence (at most 3 bytes -- the longest possible
+    /// prefix of a 4-byte codepoint) for the next call. Bytes that are genuinely
+    /// malformed (not just incomplete) are replaced, same as `String::from_utf8_lossy`.
+    fn decode(&mut self, bytes: &[u8]) -> String {
+        let mut buf = std::mem::take(&mut self.tail);
+        buf.extend_from_slice(bytes);
+
+        match std::str::from_utf8(&buf) {
+            Ok(s) => s.to_string(),
+            Err(e) => {
+                let valid_up_to = e.valid_up_to();
+                let remainder = &buf[valid_up_to..];
+                // `error_len() == None` means the error is "ran out of bytes", i.e. the
+                // tail is a genuine in-progress codepoint rather than malformed input.
+                let incomplete = e.error_len().is_none() && remainder.len() <= 3;
+                let decoded = String::from_utf8_lossy(&buf[..valid_up_to]).into_owned();
+                if incomplete {
+                    self.tail = remainder.to_vec();
+                    decoded
+                } else {
+                    decoded + &String::from_utf8_lossy(remainder)
+                }
+            }
+        }
+    }
+
+    /// Flush any pending incomplete bytes at EOF, lossily decoding them since there's no
+    /// more data coming to complete the sequence.
+    fn flush(&mut self) -> String {
+        if self.tail.is_empty() {
+            return String::new();
+        }
+        String::from_utf8_lossy(&std::mem::take(&mut self.tail)).into_owned()
+    }
+}
+
+/// Coalesces decoded text until a full line (or `LINE_MAX_BYTES`/`LINE_FLUSH_TIMEOUT`) is
+/// available; see `ExecuteNodeInput::line_buffered`.
+#[derive(Default)]
+struct LineBuffer {
+    pending: String,
+}
+
+impl LineBuffer {
+    /// Append `text` and return every complete line it produced (trailing newline
+    /// stripped), keeping any trailing partial line buffered for the next call.
+    fn push(&mut self, text: &str) -> Vec<String> {
+        self.pending.push_str(text);
+        let mut lines = Vec::new();
+        while let Some(pos) = self.pending.find('\n') {
+            lines.push(self.pending[..pos].to_string());
+            self.pending.drain(..=pos);
+        }
+        if self.pending.len() >= LINE_MAX_BYTES {
+            lines.push(std::mem::take(&mut self.pending));
+        }
+        lines
+    }
+
+    /// Take whatever's buffered (a partial line with no newline yet), if any -- used by
+    /// the flush timeout and at EOF so nothing is silently dropped.
+    fn take_partial(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.pending))
+        }
+    }
+}
+
+fn emit_line_chunk(window: &tauri::Window, session_id: &str, stream: &str, line: String) {
+    if let Err(e) = window.emit(
+        "execution://chunk",
+        ExecutionChunkEvent {
+            session_id: session_id.to_string(),
+            stream: stream.to_string(),
+            chunk: line,
+            step: None,
+        },
+    ) {
+        tracing::warn!("Failed to emit line-buffered chunk: {e}");
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,6 +129,29 @@ pub struct ExecuteNodeInput {
     pub extra_args: Option<Vec<String>>,
     pub prompt: String,
     pub cwd: Option<String>,
+    /// Run the agent attached to a pseudo-terminal instead of piped stdout/stderr, so
+    /// CLIs that detect a non-TTY (and disable color/progress/spinners/interactive
+    /// prompts) behave as they do when run by hand.
+    #[serde(default)]
+    pub use_pty: bool,
+    /// A Lua orchestration script (see `orchestration::run_script`) that sequences
+    /// multiple `run()` commands and `step()` markers instead of a single agent
+    /// invocation. Takes precedence over `agent`/`prompt`/`use_pty` when present.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Run the agent on a remote host over SSH instead of locally (see
+    /// `spawn_remote_agent`), reusing `AppState::remote_connections`'s shared
+    /// ControlMaster connections. Takes precedence over `use_pty` (there is no remote PTY
+    /// path here; see `executors::remote::execute_remote_pty` for that) but not over
+    /// `script`.
+    #[serde(default)]
+    pub host: Option<crate::commands::projects::RemoteConfig>,
+    /// Coalesce `pump_output`/`pump_error`'s decoded text until a full line (or
+    /// `LINE_MAX_BYTES`/`LINE_FLUSH_TIMEOUT`) before emitting `execution://chunk`, so a
+    /// structured/NDJSON-emitting agent delivers whole, parseable lines instead of
+    /// fragments split mid-read. Has no effect on `use_pty`/`script` sessions.
+    #[serde(default)]
+    pub line_buffered: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +166,19 @@ pub struct ExecutionChunkEvent {
     pub session_id: String,
     pub stream: String,
     pub chunk: String,
+    /// Which orchestration step (see `orchestration::run_script`) produced this chunk.
+    /// `None` for the single-agent (non-`script`) execution paths.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStepEvent {
+    pub session_id: String,
+    pub step_name: String,
+    pub status: crate::orchestration::StepStatus,
+    pub exit_code: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,26 +196,107 @@ pub struct ExecutionErrorEvent {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteProjectOutput {
+    /// Final `ExecutionResult` for every node that actually ran, keyed by node id. Nodes
+    /// left `blocked` (by an upstream failure, or by their own unresolved human-approval
+    /// check; see `executors::graph::run_project`) never appear.
+    pub results: std::collections::HashMap<String, crate::executors::ExecutionResult>,
+}
+
+/// One row's worth of screen cells changed since the last `execution://screen` emit.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirtyRow {
+    pub index: usize,
+    pub cells: Vec<crate::terminal::Cell>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionScreenEvent {
+    pub session_id: String,
+    pub rows: Vec<DirtyRow>,
+}
+
+/// Feed `bytes` through `emulator` and, if anything changed, emit the dirty rows as an
+/// `execution://screen` event so an attached client can update its rendered terminal
+/// without re-parsing the raw chunk itself.
+async fn emit_screen_update(
+    window: &tauri::Window,
+    session_id: &str,
+    emulator: &AsyncMutex<TerminalEmulator>,
+    bytes: &[u8],
+) {
+    let rows = {
+        let mut emulator = emulator.lock().await;
+        emulator.feed(bytes);
+        emulator.take_dirty_rows()
+    };
+    if rows.is_empty() {
+        return;
+    }
+    if let Err(e) = window.emit(
+        "execution://screen",
+        ExecutionScreenEvent {
+            session_id: session_id.to_string(),
+            rows: rows
+                .into_iter()
+                .map(|(index, cells)| DirtyRow { index, cells })
+                .collect(),
+        },
+    ) {
+        tracing::warn!("Failed to emit screen update: {e}");
+    }
+}
+
 async fn pump_output(
     window: tauri::Window,
     session_id: String,
     stream: String,
+    emulator: std::sync::Arc<AsyncMutex<TerminalEmulator>>,
+    session_logs: crate::session_log::SessionLogStore,
+    line_buffered: bool,
     mut reader: tokio::process::ChildStdout,
 ) {
     use tokio::io::AsyncReadExt;
 
     let mut buf = [0u8; 4096];
+    let mut decoder = IncrementalUtf8Decoder::default();
+    let mut lines = LineBuffer::default();
     loop {
-        match reader.read(&mut buf).await {
+        let read = if line_buffered {
+            match tokio::time::timeout(LINE_FLUSH_TIMEOUT, reader.read(&mut buf)).await {
+                Ok(read) => read,
+                Err(_) => {
+                    if let Some(partial) = lines.take_partial() {
+                        emit_line_chunk(&window, &session_id, &stream, partial);
+                    }
+                    continue;
+                }
+            }
+        } else {
+            reader.read(&mut buf).await
+        };
+
+        match read {
             Ok(0) => break,
             Ok(n) => {
-                let chunk = String::from_utf8_lossy(&buf[..n]).to_string();
-                if let Err(e) = window.emit(
+                emit_screen_update(&window, &session_id, &emulator, &buf[..n]).await;
+                let text = decoder.decode(&buf[..n]);
+                session_logs.append(&session_id, &stream, &text);
+                if line_buffered {
+                    for line in lines.push(&text) {
+                        emit_line_chunk(&window, &session_id, &stream, line);
+                    }
+                } else if let Err(e) = window.emit(
                     "execution://chunk",
                     ExecutionChunkEvent {
                         session_id: session_id.clone(),
                         stream: stream.clone(),
-                        chunk,
+                        chunk: text,
+                        step: None,
                     },
                 ) {
                     tracing::warn!("Failed to emit stdout chunk: {e}");
@@ -74,28 +305,79 @@ async fn pump_output(
             Err(_) => break,
         }
     }
+
+    let tail = decoder.flush();
+    if line_buffered {
+        if !tail.is_empty() {
+            session_logs.append(&session_id, &stream, &tail);
+        }
+        let mut remaining = lines.push(&tail);
+        remaining.extend(lines.take_partial());
+        for line in remaining {
+            emit_line_chunk(&window, &session_id, &stream, line);
+        }
+    } else if !tail.is_empty() {
+        session_logs.append(&session_id, &stream, &tail);
+        if let Err(e) = window.emit(
+            "execution://chunk",
+            ExecutionChunkEvent {
+                session_id: session_id.clone(),
+                stream: stream.clone(),
+                chunk: tail,
+                step: None,
+            },
+        ) {
+            tracing::warn!("Failed to emit stdout chunk: {e}");
+        }
+    }
 }
 
 async fn pump_error(
     window: tauri::Window,
     session_id: String,
     stream: String,
+    emulator: std::sync::Arc<AsyncMutex<TerminalEmulator>>,
+    session_logs: crate::session_log::SessionLogStore,
+    line_buffered: bool,
     mut reader: tokio::process::ChildStderr,
 ) {
     use tokio::io::AsyncReadExt;
 
     let mut buf = [0u8; 4096];
+    let mut decoder = IncrementalUtf8Decoder::default();
+    let mut lines = LineBuffer::default();
     loop {
-        match reader.read(&mut buf).await {
+        let read = if line_buffered {
+            match tokio::time::timeout(LINE_FLUSH_TIMEOUT, reader.read(&mut buf)).await {
+                Ok(read) => read,
+                Err(_) => {
+                    if let Some(partial) = lines.take_partial() {
+                        emit_line_chunk(&window, &session_id, &stream, partial);
+                    }
+                    continue;
+                }
+            }
+        } else {
+            reader.read(&mut buf).await
+        };
+
+        match read {
             Ok(0) => break,
             Ok(n) => {
-                let chunk = String::from_utf8_lossy(&buf[..n]).to_string();
-                if let Err(e) = window.emit(
+                emit_screen_update(&window, &session_id, &emulator, &buf[..n]).await;
+                let text = decoder.decode(&buf[..n]);
+                session_logs.append(&session_id, &stream, &text);
+                if line_buffered {
+                    for line in lines.push(&text) {
+                        emit_line_chunk(&window, &session_id, &stream, line);
+                    }
+                } else if let Err(e) = window.emit(
                     "execution://chunk",
                     ExecutionChunkEvent {
                         session_id: session_id.clone(),
                         stream: stream.clone(),
-                        chunk,
+                        chunk: text,
+                        step: None,
                     },
                 ) {
                     tracing::warn!("Failed to emit stderr chunk: {e}");
@@ -104,6 +386,284 @@ async fn pump_error(
             Err(_) => break,
         }
     }
+
+    let tail = decoder.flush();
+    if line_buffered {
+        if !tail.is_empty() {
+            session_logs.append(&session_id, &stream, &tail);
+        }
+        let mut remaining = lines.push(&tail);
+        remaining.extend(lines.take_partial());
+        for line in remaining {
+            emit_line_chunk(&window, &session_id, &stream, line);
+        }
+    } else if !tail.is_empty() {
+        session_logs.append(&session_id, &stream, &tail);
+        if let Err(e) = window.emit(
+            "execution://chunk",
+            ExecutionChunkEvent {
+                session_id: session_id.clone(),
+                stream: stream.clone(),
+                chunk: tail,
+                step: None,
+            },
+        ) {
+            tracing::warn!("Failed to emit stderr chunk: {e}");
+        }
+    }
+}
+
+/// Resolve the executable and build its argv for `agent`/`model`/`prompt`,
+/// the same one-shot shape `executors::local` uses.
+fn resolve_agent_argv(
+    agent: &str,
+    model: Option<&str>,
+    prompt: &str,
+) -> Result<(std::path::PathBuf, Vec<String>), String> {
+    if !crate::agent_command::is_allowed_executor(agent) {
+        return Err(format!(
+            "Invalid executor: {}. Allowed: {}",
+            agent,
+            crate::agent_command::ALLOWED_EXECUTORS.join(", ")
+        ));
+    }
+
+    let options = model.map(|m| serde_json::json!({ "model": m }));
+    let args = crate::agent_command::one_shot_argv(agent, prompt, &options);
+    let executable = which::which(&args[0])
+        .map_err(|e| format!("Executable '{}' not found: {}", args[0], e))?;
+    Ok((executable, args))
+}
+
+fn spawn_piped_agent(
+    agent: &str,
+    model: Option<&str>,
+    prompt: &str,
+    cwd: Option<&str>,
+) -> Result<tokio::process::Child, String> {
+    let (executable, args) = resolve_agent_argv(agent, model, prompt)?;
+
+    let mut command = tokio::process::Command::new(executable);
+    command
+        .args(&args[1..])
+        .current_dir(cwd.unwrap_or("."))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // Make the child its own process group leader, so `RunningProcess::signal`'s
+    // `send_signal_to_group` (see `state.rs`) can reach any subprocess it spawns of its
+    // own, not just the direct child -- matching `executors::local::execute_local`.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    command
+        .spawn()
+        .map_err(|e| format!("failed to spawn agent: {e}"))
+}
+
+/// Spawn the agent on a remote host over SSH instead of locally. Reuses
+/// `AppState::remote_connections`'s shared ControlMaster connections the same way
+/// `executors::remote::execute_remote_direct` does, so repeated runs against the same
+/// host don't each pay a fresh handshake. Returns the `ssh` child (its piped
+/// stdin/stdout/stderr already carry the remote command's, so `pump_output`/`pump_error`
+/// and `RunningProcess::send_input` work on it unmodified) along with the connection
+/// guard the caller must keep alive for as long as the process is tracked.
+async fn spawn_remote_agent(
+    remote: &crate::commands::projects::RemoteConfig,
+    connections: &crate::executors::remote_connection::RemoteConnectionManager,
+    agent: &str,
+    model: Option<&str>,
+    prompt: &str,
+) -> Result<
+    (
+        tokio::process::Child,
+        crate::executors::remote_connection::ConnectionGuard,
+    ),
+    String,
+> {
+    if !crate::agent_command::is_allowed_executor(agent) {
+        return Err(format!(
+            "Invalid executor: {}. Allowed: {}",
+            agent,
+            crate::agent_command::ALLOWED_EXECUTORS.join(", ")
+        ));
+    }
+
+    let host = &remote.host;
+    let user = remote.user.as_deref().unwrap_or("root");
+    let port = remote.port.unwrap_or(22);
+    let target = format!("{user}@{host}");
+
+    let mut ssh_args = vec![
+        "-o".to_string(),
+        "StrictHostKeyChecking=no".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-p".to_string(),
+        port.to_string(),
+    ];
+    if let Some(key_path) = &remote.key_path {
+        ssh_args.push("-i".to_string());
+        ssh_args.push(key_path.clone());
+    }
+
+    let connection_guard = connections
+        .acquire(user, host, port, &ssh_args)
+        .await
+        .map_err(|e| e.to_string())?;
+    ssh_args.extend(connection_guard.ssh_args());
+
+    let options = model.map(|m| serde_json::json!({ "model": m }));
+    let agent_command = crate::agent_command::one_shot_shell_command(agent, prompt, &options);
+    let remote_command = match &remote.workdir {
+        Some(dir) => format!("cd {} && exec {agent_command}", crate::agent_command::shell_escape(dir)),
+        None => format!("exec {agent_command}"),
+    };
+
+    ssh_args.push(target);
+    ssh_args.push(remote_command);
+
+    let child = tokio::process::Command::new("ssh")
+        .args(&ssh_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start ssh: {e}"))?;
+
+    Ok((child, connection_guard))
+}
+
+/// Spawn the agent attached to a PTY instead. Returns the child (kept alive only by the
+/// caller's background wait task, see `execute_node`), its pid (so `RunningProcess` can
+/// stop it without owning the child), and the PTY master (so output can be streamed and
+/// `resize_pty` can reach it later). Mirrors `executors::local::execute_local_pty`'s
+/// `portable-pty` idioms.
+fn spawn_pty_agent(
+    agent: &str,
+    model: Option<&str>,
+    prompt: &str,
+    cwd: Option<&str>,
+) -> Result<
+    (
+        Box<dyn portable_pty::Child + Send + Sync>,
+        i32,
+        Box<dyn portable_pty::MasterPty + Send>,
+    ),
+    String,
+> {
+    let (executable, args) = resolve_agent_argv(agent, model, prompt)?;
+
+    let pty_system = portable_pty::native_pty_system();
+    let pair = pty_system
+        .openpty(portable_pty::PtySize {
+            rows: DEFAULT_PTY_ROWS,
+            cols: DEFAULT_PTY_COLS,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("failed to open PTY: {e}"))?;
+
+    let mut cmd = portable_pty::CommandBuilder::new(executable);
+    for arg in &args[1..] {
+        cmd.arg(arg);
+    }
+    if let Some(dir) = cwd {
+        cmd.cwd(dir);
+    }
+
+    let child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("failed to spawn in PTY: {e}"))?;
+    // Drop our copy of the slave fd: the child holds the only remaining one, so the
+    // reader thread below sees EOF once the child exits instead of blocking forever.
+    drop(pair.slave);
+
+    let pid = child
+        .process_id()
+        .map(|p| p as i32)
+        .ok_or_else(|| "PTY child has no pid".to_string())?;
+
+    Ok((child, pid, pair.master))
+}
+
+/// Pump a PTY master's merged stdout/stderr stream into `execution://chunk` events on a
+/// blocking thread (the `portable-pty` reader is a plain blocking `Read`, not async).
+/// Also feeds the same bytes through `emulator` and emits `execution://screen` deltas,
+/// using `blocking_lock` since this runs outside the tokio runtime.
+fn pump_pty_output(
+    window: tauri::Window,
+    session_id: String,
+    emulator: std::sync::Arc<AsyncMutex<TerminalEmulator>>,
+    session_logs: crate::session_log::SessionLogStore,
+    mut reader: Box<dyn std::io::Read + Send>,
+) {
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut decoder = IncrementalUtf8Decoder::default();
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let rows = {
+                        let mut emulator = emulator.blocking_lock();
+                        emulator.feed(&buf[..n]);
+                        emulator.take_dirty_rows()
+                    };
+                    if !rows.is_empty() {
+                        if let Err(e) = window.emit(
+                            "execution://screen",
+                            ExecutionScreenEvent {
+                                session_id: session_id.clone(),
+                                rows: rows
+                                    .into_iter()
+                                    .map(|(index, cells)| DirtyRow { index, cells })
+                                    .collect(),
+                            },
+                        ) {
+                            tracing::warn!("Failed to emit screen update: {e}");
+                        }
+                    }
+
+                    let chunk = decoder.decode(&buf[..n]);
+                    session_logs.append(&session_id, "pty", &chunk);
+                    if let Err(e) = window.emit(
+                        "execution://chunk",
+                        ExecutionChunkEvent {
+                            session_id: session_id.clone(),
+                            stream: "pty".to_string(),
+                            chunk,
+                            step: None,
+                        },
+                    ) {
+                        tracing::warn!("Failed to emit pty chunk: {e}");
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        let tail = decoder.flush();
+        if !tail.is_empty() {
+            session_logs.append(&session_id, "pty", &tail);
+            if let Err(e) = window.emit(
+                "execution://chunk",
+                ExecutionChunkEvent {
+                    session_id: session_id.clone(),
+                    stream: "pty".to_string(),
+                    chunk: tail,
+                    step: None,
+                },
+            ) {
+                tracing::warn!("Failed to emit pty chunk: {e}");
+            }
+        }
+    });
 }
 
 #[tauri::command]
@@ -116,15 +676,291 @@ pub async fn execute_node(
         .session_id
         .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
-    let mut child =
-        executors::local::spawn_agent(
+    let emulator = std::sync::Arc::new(AsyncMutex::new(TerminalEmulator::new(
+        DEFAULT_PTY_ROWS as usize,
+        DEFAULT_PTY_COLS as usize,
+    )));
+    state
+        .terminals
+        .lock()
+        .await
+        .insert(session_id.clone(), emulator.clone());
+
+    if let Some(script) = input.script.clone() {
+        let rx = crate::orchestration::spawn_script(script, input.cwd.clone());
+
+        let window_script = window.clone();
+        let state_terminals = state.terminals.clone();
+        let session_logs = state.session_logs.clone();
+        let session_script = session_id.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut any_failed = false;
+            for event in rx {
+                match event {
+                    crate::orchestration::ScriptEvent::Step(step) => {
+                        if step.status == crate::orchestration::StepStatus::Failed {
+                            any_failed = true;
+                        }
+                        if let Err(e) = window_script.emit(
+                            "execution://step",
+                            ExecutionStepEvent {
+                                session_id: session_script.clone(),
+                                step_name: step.step_name,
+                                status: step.status,
+                                exit_code: step.exit_code,
+                            },
+                        ) {
+                            tracing::warn!("Failed to emit execution step event: {e}");
+                        }
+                    }
+                    crate::orchestration::ScriptEvent::Output {
+                        step,
+                        stream,
+                        chunk,
+                    } => {
+                        let rows = {
+                            let mut emulator = emulator.blocking_lock();
+                            emulator.feed(chunk.as_bytes());
+                            emulator.take_dirty_rows()
+                        };
+                        if !rows.is_empty() {
+                            if let Err(e) = window_script.emit(
+                                "execution://screen",
+                                ExecutionScreenEvent {
+                                    session_id: session_script.clone(),
+                                    rows: rows
+                                        .into_iter()
+                                        .map(|(index, cells)| DirtyRow { index, cells })
+                                        .collect(),
+                                },
+                            ) {
+                                tracing::warn!("Failed to emit screen update: {e}");
+                            }
+                        }
+                        session_logs.append(&session_script, &stream, &chunk);
+                        if let Err(e) = window_script.emit(
+                            "execution://chunk",
+                            ExecutionChunkEvent {
+                                session_id: session_script.clone(),
+                                stream,
+                                chunk,
+                                step: Some(step),
+                            },
+                        ) {
+                            tracing::warn!("Failed to emit execution chunk event: {e}");
+                        }
+                    }
+                }
+            }
+
+            state_terminals
+                .blocking_lock()
+                .remove(&session_script);
+            session_logs.record_status(&session_script, !any_failed, None);
+            if let Err(e) = window_script.emit(
+                "execution://done",
+                ExecutionDoneEvent {
+                    session_id: session_script,
+                    success: !any_failed,
+                    exit_code: None,
+                },
+            ) {
+                tracing::warn!("Failed to emit execution done event: {e}");
+            }
+        });
+
+        return Ok(ExecuteNodeOutput { session_id });
+    }
+
+    if let Some(remote) = input.host.clone() {
+        let (mut child, connection_guard) = spawn_remote_agent(
+            &remote,
+            &state.remote_connections,
             &input.agent,
             input.model.as_deref(),
-            input.extra_args.as_deref(),
             &input.prompt,
-            &input.cwd,
+        )
+        .await?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "failed to capture remote stdout".to_string())?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| "failed to capture remote stderr".to_string())?;
+
+        let running = RunningProcess::new_remote(child, connection_guard);
+        state
+            .processes
+            .lock()
+            .await
+            .insert(session_id.clone(), running.clone());
+
+        let window_stdout = window.clone();
+        let window_stderr = window.clone();
+        let session_stdout = session_id.clone();
+        let session_stderr = session_id.clone();
+        let emulator_stdout = emulator.clone();
+        let emulator_stderr = emulator.clone();
+        let session_logs_stdout = state.session_logs.clone();
+        let session_logs_stderr = state.session_logs.clone();
+        let line_buffered = input.line_buffered;
+        tokio::spawn(async move {
+            pump_output(
+                window_stdout,
+                session_stdout,
+                "stdout".to_string(),
+                emulator_stdout,
+                session_logs_stdout,
+                line_buffered,
+                stdout,
+            )
+            .await
+        });
+        tokio::spawn(async move {
+            pump_error(
+                window_stderr,
+                session_stderr,
+                "stderr".to_string(),
+                emulator_stderr,
+                session_logs_stderr,
+                line_buffered,
+                stderr,
+            )
+            .await
+        });
+
+        let window_done = window.clone();
+        let state_processes = state.processes.clone();
+        let state_terminals = state.terminals.clone();
+        let session_logs_done = state.session_logs.clone();
+        let session_done = session_id.clone();
+        tokio::spawn(async move {
+            let status = running.wait().await;
+            state_processes.lock().await.remove(&session_done);
+            state_terminals.lock().await.remove(&session_done);
+            match status {
+                Ok(status) => {
+                    session_logs_done.record_status(&session_done, status.success(), status.code());
+                    if let Err(e) = window_done.emit(
+                        "execution://done",
+                        ExecutionDoneEvent {
+                            session_id: session_done,
+                            success: status.success(),
+                            exit_code: status.code(),
+                        },
+                    ) {
+                        tracing::warn!("Failed to emit execution done event: {e}");
+                    }
+                }
+                Err(e) => {
+                    if let Err(emit_err) = window_done.emit(
+                        "execution://error",
+                        ExecutionErrorEvent {
+                            session_id: session_done,
+                            message: format!("ssh wait error: {e}"),
+                        },
+                    ) {
+                        tracing::warn!("Failed to emit execution error event: {emit_err}");
+                    }
+                }
+            }
+        });
+
+        return Ok(ExecuteNodeOutput { session_id });
+    }
+
+    if input.use_pty {
+        let (child, pid, master) = spawn_pty_agent(
+            &input.agent,
+            input.model.as_deref(),
+            &input.prompt,
+            input.cwd.as_deref(),
         )?;
 
+        let reader = master
+            .try_clone_reader()
+            .map_err(|e| format!("failed to clone PTY reader: {e}"))?;
+        pump_pty_output(
+            window.clone(),
+            session_id.clone(),
+            emulator,
+            state.session_logs.clone(),
+            reader,
+        );
+
+        let running = RunningProcess::new_pty(pid, master)?;
+        state
+            .processes
+            .lock()
+            .await
+            .insert(session_id.clone(), running);
+
+        let window_done = window.clone();
+        let state_processes = state.processes.clone();
+        let state_terminals = state.terminals.clone();
+        let session_logs_done = state.session_logs.clone();
+        let session_done = session_id.clone();
+        let mut child = child;
+        tokio::spawn(async move {
+            let status = tokio::task::spawn_blocking(move || child.wait()).await;
+            state_processes.lock().await.remove(&session_done);
+            state_terminals.lock().await.remove(&session_done);
+            match status {
+                Ok(Ok(status)) => {
+                    session_logs_done.record_status(
+                        &session_done,
+                        status.success(),
+                        Some(status.exit_code() as i32),
+                    );
+                    if let Err(e) = window_done.emit(
+                        "execution://done",
+                        ExecutionDoneEvent {
+                            session_id: session_done,
+                            success: status.success(),
+                            exit_code: Some(status.exit_code() as i32),
+                        },
+                    ) {
+                        tracing::warn!("Failed to emit execution done event: {e}");
+                    }
+                }
+                Ok(Err(e)) => {
+                    if let Err(emit_err) = window_done.emit(
+                        "execution://error",
+                        ExecutionErrorEvent {
+                            session_id: session_done,
+                            message: format!("pty wait error: {e}"),
+                        },
+                    ) {
+                        tracing::warn!("Failed to emit execution error event: {emit_err}");
+                    }
+                }
+                Err(e) => {
+                    if let Err(emit_err) = window_done.emit(
+                        "execution://error",
+                        ExecutionErrorEvent {
+                            session_id: session_done,
+                            message: format!("pty wait task failed: {e}"),
+                        },
+                    ) {
+                        tracing::warn!("Failed to emit execution error event: {emit_err}");
+                    }
+                }
+            }
+        });
+
+        return Ok(ExecuteNodeOutput { session_id });
+    }
+
+    let mut child = spawn_piped_agent(
+        &input.agent,
+        input.model.as_deref(),
+        &input.prompt,
+        input.cwd.as_deref(),
+    )?;
+
     let stdout = child
         .stdout
         .take()
@@ -145,21 +981,48 @@ pub async fn execute_node(
     let window_stderr = window.clone();
     let session_stdout = session_id.clone();
     let session_stderr = session_id.clone();
+    let emulator_stdout = emulator.clone();
+    let emulator_stderr = emulator.clone();
+    let session_logs_stdout = state.session_logs.clone();
+    let session_logs_stderr = state.session_logs.clone();
+    let line_buffered = input.line_buffered;
     tokio::spawn(async move {
-        pump_output(window_stdout, session_stdout, "stdout".to_string(), stdout).await
+        pump_output(
+            window_stdout,
+            session_stdout,
+            "stdout".to_string(),
+            emulator_stdout,
+            session_logs_stdout,
+            line_buffered,
+            stdout,
+        )
+        .await
     });
     tokio::spawn(async move {
-        pump_error(window_stderr, session_stderr, "stderr".to_string(), stderr).await
+        pump_error(
+            window_stderr,
+            session_stderr,
+            "stderr".to_string(),
+            emulator_stderr,
+            session_logs_stderr,
+            line_buffered,
+            stderr,
+        )
+        .await
     });
 
     let window_done = window.clone();
     let state_processes = state.processes.clone();
+    let state_terminals = state.terminals.clone();
+    let session_logs_done = state.session_logs.clone();
     let session_done = session_id.clone();
     tokio::spawn(async move {
         let status = running.wait().await;
         state_processes.lock().await.remove(&session_done);
+        state_terminals.lock().await.remove(&session_done);
         match status {
             Ok(status) => {
+                session_logs_done.record_status(&session_done, status.success(), status.code());
                 if let Err(e) = window_done.emit(
                     "execution://done",
                     ExecutionDoneEvent {
@@ -188,30 +1051,247 @@ pub async fn execute_node(
     Ok(ExecuteNodeOutput { session_id })
 }
 
+/// Run every node of a project's DAG in dependency order, respecting `edges` and each
+/// node's own `ExecutionConfig` backend (local/Docker/bwrap/remote/Modal -- see
+/// `executors::execute`), rather than the single hand-rolled local/remote/pty path
+/// `execute_node` drives for one node at a time. Blocks until the whole graph settles;
+/// the UI should expect this to run for as long as the project's longest dependency
+/// chain takes.
+#[tauri::command]
+pub async fn execute_project(
+    state: tauri::State<'_, AppState>,
+    project_id: String,
+) -> Result<ExecuteProjectOutput, String> {
+    let results = crate::executors::graph::run_project(
+        &state,
+        &state.execution_registry,
+        &project_id,
+        crate::executors::graph::DEFAULT_PARALLELISM,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(ExecuteProjectOutput { results })
+}
+
+#[tauri::command]
+pub async fn send_remote_pty_input(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+    input: String,
+) -> Result<(), String> {
+    state.remote_pty_inputs.send(&session_id, &input)
+}
+
+/// Default grace period between each stage of `stop_execution`'s escalation sequence
+/// (SIGINT, then SIGTERM, only SIGKILL as a last resort), when the caller doesn't pass
+/// `grace_ms`.
+const DEFAULT_STOP_GRACE_MS: u64 = 3000;
+
+/// Stop a running session. By default this is graceful: SIGINT, wait up to `grace_ms`
+/// (default `DEFAULT_STOP_GRACE_MS`) for the agent to exit on its own, then SIGTERM with
+/// the same grace, and only SIGKILL if it's still alive after both -- so an agent mid
+/// write gets a chance to flush and clean up instead of being hard-killed outright. Pass
+/// `force: true` to skip straight to SIGKILL (the old behavior).
 #[tauri::command]
 pub async fn stop_execution(
     window: tauri::Window,
     state: tauri::State<'_, AppState>,
     session_id: String,
+    grace_ms: Option<u64>,
+    force: bool,
 ) -> Result<(), String> {
     let running = { state.processes.lock().await.get(&session_id).cloned() };
     let Some(running) = running else {
         return Ok(());
     };
 
-    match running.kill().await {
-        Ok(()) => {
-            if let Err(e) = window.emit(
-                "execution://error",
-                ExecutionErrorEvent {
-                    session_id,
-                    message: "Execution stopped".to_string(),
-                },
-            ) {
-                tracing::warn!("Failed to emit stop execution event: {e}");
+    if force {
+        return match running.kill().await {
+            Ok(()) => {
+                emit_stop_notice(&window, &session_id);
+                Ok(())
             }
-            Ok(())
+            Err(e) => Err(format!("failed to kill process: {e}")),
+        };
+    }
+
+    let grace = std::time::Duration::from_millis(grace_ms.unwrap_or(DEFAULT_STOP_GRACE_MS));
+    let processes = state.processes.clone();
+    tokio::spawn(escalate_stop(window, processes, session_id, running, grace));
+    Ok(())
+}
+
+/// Deliver `stop_execution`'s graceful shutdown sequence. Exit is detected by `session_id`
+/// disappearing from `processes`, since the session's own wait task spawned by
+/// `execute_node` (not this one) owns the actual `wait()` and removes it
+/// right after -- this lets the escalation race each grace period against that without a
+/// second, competing waiter on the same process.
+async fn escalate_stop(
+    window: tauri::Window,
+    processes: std::sync::Arc<AsyncMutex<std::collections::HashMap<String, std::sync::Arc<RunningProcess>>>>,
+    session_id: String,
+    running: std::sync::Arc<RunningProcess>,
+    grace: std::time::Duration,
+) {
+    use crate::commands::projects::StopSignal;
+
+    for signal in [StopSignal::Sigint, StopSignal::Sigterm] {
+        if let Err(e) = running.signal(signal).await {
+            tracing::warn!("Failed to send {signal:?} to session {session_id}: {e}");
+            break;
+        }
+        if wait_for_exit(&processes, &session_id, grace).await {
+            emit_stop_notice(&window, &session_id);
+            return;
         }
-        Err(e) => Err(format!("failed to kill process: {e}")),
     }
+
+    if let Err(e) = running.kill().await {
+        tracing::warn!("Failed to force-kill session {session_id} after graceful escalation: {e}");
+    }
+    emit_stop_notice(&window, &session_id);
+}
+
+/// Poll `processes` for up to `grace`, returning `true` as soon as `session_id` is no
+/// longer registered.
+async fn wait_for_exit(
+    processes: &AsyncMutex<std::collections::HashMap<String, std::sync::Arc<RunningProcess>>>,
+    session_id: &str,
+    grace: std::time::Duration,
+) -> bool {
+    let deadline = tokio::time::Instant::now() + grace;
+    while tokio::time::Instant::now() < deadline {
+        if !processes.lock().await.contains_key(session_id) {
+            return true;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    !processes.lock().await.contains_key(session_id)
+}
+
+fn emit_stop_notice(window: &tauri::Window, session_id: &str) {
+    if let Err(e) = window.emit(
+        "execution://error",
+        ExecutionErrorEvent {
+            session_id: session_id.to_string(),
+            message: "Execution stopped".to_string(),
+        },
+    ) {
+        tracing::warn!("Failed to emit stop execution event: {e}");
+    }
+}
+
+/// Forward a window-size change to a PTY-backed session's master. Errors if `session_id`
+/// isn't running or wasn't started with `usePty`.
+#[tauri::command]
+pub async fn resize_pty(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+    rows: u16,
+    cols: u16,
+) -> Result<(), String> {
+    let running = { state.processes.lock().await.get(&session_id).cloned() };
+    let Some(running) = running else {
+        return Err(format!("no running session for {session_id}"));
+    };
+
+    running.resize_pty(rows, cols).await?;
+
+    if let Some(emulator) = state.terminals.lock().await.get(&session_id) {
+        emulator
+            .lock()
+            .await
+            .resize(rows as usize, cols as usize);
+    }
+
+    Ok(())
+}
+
+/// Full current screen grid for `session_id`, so a client that just (re)attached can
+/// render the terminal immediately instead of waiting for the next dirty-row delta.
+#[tauri::command]
+pub async fn get_screen(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+) -> Result<crate::terminal::ScreenSnapshot, String> {
+    let emulator = { state.terminals.lock().await.get(&session_id).cloned() };
+    let Some(emulator) = emulator else {
+        return Err(format!("no running session for {session_id}"));
+    };
+
+    Ok(emulator.lock().await.snapshot())
+}
+
+/// Feed input to a running session, as if typed at its terminal. Set `newline` to send
+/// a trailing `\n`, matching how a user would press Enter after answering a prompt.
+#[tauri::command]
+pub async fn send_input(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+    data: String,
+    newline: bool,
+) -> Result<(), String> {
+    let running = { state.processes.lock().await.get(&session_id).cloned() };
+    let Some(running) = running else {
+        return Err(format!("no running session for {session_id}"));
+    };
+
+    running.send_input(&data, newline).await
+}
+
+/// Close a running session's stdin, signalling EOF to the agent process.
+#[tauri::command]
+pub async fn close_input(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+) -> Result<(), String> {
+    let running = { state.processes.lock().await.get(&session_id).cloned() };
+    let Some(running) = running else {
+        return Err(format!("no running session for {session_id}"));
+    };
+
+    running.close_input().await
+}
+
+/// One slice of `session_id`'s captured log, with `next_offset` to pass back in as
+/// `offset` on the next call to resume from where this one left off.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLogChunk {
+    pub content: String,
+    pub next_offset: u64,
+}
+
+/// Captured log bytes for `session_id` from `offset` (default 0) onward, so a reopened
+/// window can reconstruct scrollback for a session whether or not its process is still
+/// alive. See `session_log::SessionLogStore`.
+#[tauri::command]
+pub async fn get_session_log(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+    offset: Option<u64>,
+) -> Result<SessionLogChunk, String> {
+    let offset = offset.unwrap_or(0);
+    let bytes = state
+        .session_logs
+        .read_from(&session_id, offset)
+        .map_err(|e| format!("failed to read session log: {e}"))?;
+    let next_offset = offset + bytes.len() as u64;
+    Ok(SessionLogChunk {
+        content: String::from_utf8_lossy(&bytes).into_owned(),
+        next_offset,
+    })
+}
+
+/// Every session with a captured log, each with its final `success`/`exit_code` if the
+/// run has finished (`None` for both if it's still running or never recorded a status).
+#[tauri::command]
+pub async fn list_session_logs(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<crate::session_log::SessionLogStatus>, String> {
+    state
+        .session_logs
+        .list()
+        .map_err(|e| format!("failed to list session logs: {e}"))
 }
@@ -0,0 +1,70 @@
+//! Tauri commands exposing the run-history audit trail (see `sessions::history`).
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tauri::State;
+
+use crate::sessions::history::{HistoryStore, RunRecord};
+use crate::sessions::manager::{Session, SessionManager};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeIdInput {
+    pub node_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunIdInput {
+    pub run_id: String,
+}
+
+/// List recorded runs for a node, most recent first.
+#[tauri::command]
+pub async fn list_runs(
+    history: State<'_, Arc<HistoryStore>>,
+    input: NodeIdInput,
+) -> Result<Vec<RunRecord>, String> {
+    history.list_runs(&input.node_id).map_err(|e| e.to_string())
+}
+
+/// Fetch a single recorded run, including its check results.
+#[tauri::command]
+pub async fn get_run(
+    history: State<'_, Arc<HistoryStore>>,
+    input: RunIdInput,
+) -> Result<RunRecord, String> {
+    history
+        .get_run(&input.run_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Run {} not found", input.run_id))
+}
+
+/// Relaunch the session that produced a past run, using its recorded inputs.
+#[tauri::command]
+pub async fn replay_run(
+    history: State<'_, Arc<HistoryStore>>,
+    session_manager: State<'_, SessionManager>,
+    input: RunIdInput,
+) -> Result<Session, String> {
+    let run = history
+        .get_run(&input.run_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Run {} not found", input.run_id))?;
+
+    let replay = run
+        .replay
+        .ok_or_else(|| format!("Run {} has no recorded inputs to replay", input.run_id))?;
+
+    session_manager
+        .create_session(
+            &replay.node_id,
+            &replay.agent,
+            replay.model.as_deref(),
+            replay.extra_args.as_deref(),
+            &replay.prompt,
+            replay.cwd.as_deref(),
+        )
+        .await
+}
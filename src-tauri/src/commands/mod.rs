@@ -4,12 +4,16 @@
 //! Commands are organized by domain: projects, nodes, execution, sessions.
 
 pub mod execution;
+pub mod history;
 pub mod nodes;
+pub mod notifications;
 pub mod projects;
 pub mod sessions;
 
 // Re-export common types for convenience
 pub use execution::*;
+pub use history::*;
 pub use nodes::*;
+pub use notifications::*;
 pub use projects::*;
 pub use sessions::*;
@@ -1,47 +1,117 @@
 //! Node management commands
+//!
+//! `AppState.projects` (persisted to `projects.json`) is the store `get_project`/
+//! `list_projects`/`execute_node` actually read, so every command here mutates that
+//! first and mirrors into `db::Database` afterwards -- same direction as
+//! `commands::projects::{create,save,delete}_project` -- rather than writing only to the
+//! CRDT-backed `db::Database` mirror and leaving the node invisible to the rest of the
+//! app.
+
+use std::sync::Arc;
 
 use crate::db::Database;
-use super::projects::{Node, NodeStatus};
+use crate::sessions::agent_state::AgentState;
+use crate::state::AppState;
+use super::projects::Node;
 use tauri::State;
 
 /// Add a new node to a project
 #[tauri::command]
 pub async fn add_node(
-    db: State<'_, Database>,
+    state: State<'_, AppState>,
+    db: State<'_, Arc<Database>>,
     project_id: String,
     node: Node,
 ) -> Result<Node, String> {
+    {
+        let mut projects = state.projects.write().await;
+        let project = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| format!("project {project_id} not found"))?;
+        project.nodes.push(node.clone());
+        project.updated_at = chrono::Utc::now().timestamp_millis();
+    }
+    state.persist_projects().await?;
+
     db.add_node(&project_id, &node).map_err(|e| e.to_string())
 }
 
 /// Update an existing node
 #[tauri::command]
 pub async fn update_node(
-    db: State<'_, Database>,
+    state: State<'_, AppState>,
+    db: State<'_, Arc<Database>>,
     project_id: String,
     node: Node,
 ) -> Result<Node, String> {
+    {
+        let mut projects = state.projects.write().await;
+        let project = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| format!("project {project_id} not found"))?;
+        let existing = project
+            .nodes
+            .iter_mut()
+            .find(|n| n.id == node.id)
+            .ok_or_else(|| format!("node {} not found", node.id))?;
+        *existing = node.clone();
+        project.updated_at = chrono::Utc::now().timestamp_millis();
+    }
+    state.persist_projects().await?;
+
     db.update_node(&project_id, &node).map_err(|e| e.to_string())
 }
 
 /// Delete a node from a project
 #[tauri::command]
 pub async fn delete_node(
-    db: State<'_, Database>,
+    state: State<'_, AppState>,
+    db: State<'_, Arc<Database>>,
     project_id: String,
     node_id: String,
 ) -> Result<(), String> {
+    {
+        let mut projects = state.projects.write().await;
+        let project = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| format!("project {project_id} not found"))?;
+        project.nodes.retain(|n| n.id != node_id);
+        // Mirrors `db::Database::delete_node`'s edge-pruning, so a node's dangling edges
+        // don't survive it in `AppState.projects` either.
+        project
+            .edges
+            .retain(|e| e.source_id != node_id && e.target_id != node_id);
+        project.updated_at = chrono::Utc::now().timestamp_millis();
+    }
+    state.persist_projects().await?;
+
     db.delete_node(&project_id, &node_id).map_err(|e| e.to_string())
 }
 
 /// Set node status
 #[tauri::command]
 pub async fn set_node_status(
-    db: State<'_, Database>,
+    state: State<'_, AppState>,
+    db: State<'_, Arc<Database>>,
     project_id: String,
     node_id: String,
-    status: NodeStatus,
+    status: AgentState,
 ) -> Result<(), String> {
+    {
+        let mut projects = state.projects.write().await;
+        let project = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| format!("project {project_id} not found"))?;
+        let node = project
+            .nodes
+            .iter_mut()
+            .find(|n| n.id == node_id)
+            .ok_or_else(|| format!("node {node_id} not found"))?;
+        node.status = status;
+        project.updated_at = chrono::Utc::now().timestamp_millis();
+    }
+    state.persist_projects().await?;
+
     db.set_node_status(&project_id, &node_id, &status)
         .map_err(|e| e.to_string())
 }
@@ -0,0 +1,41 @@
+//! Tauri commands exposing the in-app notification center (see `sessions::notifier` and
+//! `db::Database`'s notification operations).
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tauri::State;
+
+use crate::db::{Database, NotificationEvent};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectIdInput {
+    pub project_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationIdInput {
+    pub notification_id: String,
+}
+
+/// List a project's notifications, most recent first.
+#[tauri::command]
+pub async fn list_notifications(
+    db: State<'_, Arc<Database>>,
+    input: ProjectIdInput,
+) -> Result<Vec<NotificationEvent>, String> {
+    db.list_notifications(&input.project_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Mark a notification as read/handled.
+#[tauri::command]
+pub async fn acknowledge_notification(
+    db: State<'_, Arc<Database>>,
+    input: NotificationIdInput,
+) -> Result<(), String> {
+    db.acknowledge_notification(&input.notification_id)
+        .map_err(|e| e.to_string())
+}
@@ -1,7 +1,11 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use uuid::Uuid;
 
+use crate::db::Database;
+use crate::sessions::agent_state::AgentState;
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,7 +37,7 @@ pub struct Node {
     pub context: Vec<serde_json::Value>,
     pub deliverables: Vec<serde_json::Value>,
     pub checks: Vec<serde_json::Value>,
-    pub status: String,
+    pub status: AgentState,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +49,30 @@ pub struct Edge {
     pub source_deliverable: Option<String>,
 }
 
+/// Free-form project-level context: reference material plus a `variables` bag that
+/// features can read user-supplied configuration from at runtime (e.g.
+/// `sessions::input_detection`'s custom `DetectorProfile`s) without a schema migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectContext {
+    #[serde(default)]
+    pub resources: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub variables: serde_json::Value,
+}
+
+impl Default for ProjectContext {
+    fn default() -> Self {
+        Self {
+            resources: vec![],
+            notes: String::new(),
+            variables: serde_json::Value::Object(serde_json::Map::new()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Project {
@@ -52,10 +80,203 @@ pub struct Project {
     pub name: String,
     pub description: String,
     pub location: Option<String>,
+    #[serde(default)]
+    pub context: ProjectContext,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    /// Execution config applied to a node run that doesn't set its own. Stored
+    /// separately from the `nodes` JSON blob in `db::Database`, hence `Option` here
+    /// rather than defaulting per-node.
+    #[serde(default)]
+    pub default_execution_config: Option<ExecutionConfig>,
     pub created_at: i64,
     pub updated_at: i64,
+    /// External sinks to fan `session://completed`/`session://awaiting_input` events out
+    /// to, in addition to the Tauri webview. See `sessions::notifier`.
+    #[serde(default)]
+    pub notify: Vec<NotifyRule>,
+}
+
+/// Which session lifecycle event a `NotifyRule` fires on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotifyOn {
+    Completed,
+    AwaitingInput,
+}
+
+/// An external sink a `NotifyRule` dispatches to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NotifySink {
+    /// POSTs the event as JSON to `url`, with retry/backoff on failure.
+    Webhook { url: String },
+    /// Shows a desktop OS notification via `tauri_plugin_notification`.
+    Desktop,
+    /// Runs `cmd` through `sh -c`, with the event JSON in the `ORCHESTRA_EVENT` env var.
+    Command { cmd: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotifyRule {
+    pub on: NotifyOn,
+    #[serde(flatten)]
+    pub sink: NotifySink,
+}
+
+/// Signal delivered to a running agent process during a graceful stop, before the
+/// grace period expires and we escalate to SIGKILL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StopSignal {
+    Sigterm,
+    Sigint,
+    Sighup,
+    Sigquit,
+}
+
+impl Default for StopSignal {
+    fn default() -> Self {
+        Self::Sigterm
+    }
+}
+
+/// Policy for what happens when a node is re-run while its previous execution is
+/// still live. Mirrors watchexec's `on-busy-update`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OnBusyUpdate {
+    /// Reject the re-run; the existing execution keeps going untouched.
+    #[default]
+    DoNothing,
+    /// Hold the new run until the existing one finishes, then start it.
+    Queue,
+    /// Gracefully stop the existing execution (via `stop_signal`/`stop_timeout`), then
+    /// start the new one.
+    Restart,
+    /// Forward `stop_signal` to the existing execution and leave it running; the new
+    /// run is dropped.
+    Signal,
+}
+
+/// Which backend runs a node's agent process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExecutionBackend {
+    #[default]
+    Local,
+    Docker,
+    DockerInteractive,
+    /// Rootless bubblewrap jail; faster startup than Docker, Linux only. Falls back to
+    /// `Docker` if `bwrap` isn't installed, unless `SandboxConfig::disable_docker_fallback`.
+    Bwrap,
+    /// Runs the agent inside a Docker container on a remote VM over SSH; blocks for the
+    /// whole run and returns `Done`/`Error`. See `RemoteInteractive` to stream output from
+    /// a detached remote session instead.
+    Remote,
+    /// Runs the agent directly on a remote host (no Docker) inside a detached remote tmux
+    /// session over SSH, so output streams back live and the session survives a UI restart.
+    RemoteInteractive,
+    /// Runs the agent directly on a remote host (no Docker, no tmux) via a lightweight
+    /// client/server protocol over SSH: blocks for the whole run like `Remote`, but frames
+    /// stdout/stderr and the exit status instead of shelling a single Docker command, and
+    /// can reconnect and recover the outcome if the SSH connection drops mid-run.
+    RemoteDirect,
+    /// Runs the agent inside `docker run -it` on a remote VM over `ssh -tt`, keeping a real
+    /// pseudo-terminal open end to end so a caller can forward keystrokes through
+    /// `ExecuteRequest::remote_pty_inputs` -- unlike `RemoteInteractive`'s tmux polling,
+    /// this is bidirectional, at the cost of not surviving a UI restart.
+    RemotePty,
+    Modal,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    pub memory: Option<String>,
+    pub cpus: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DockerConfig {
+    pub image: Option<String>,
+    pub resources: Option<ResourceLimits>,
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxConfig {
+    /// Mirrors `DockerConfig::network`: pass `"none"` to run the jail with `--unshare-net`.
+    pub network: Option<String>,
+    /// If `bwrap` isn't installed, the `Bwrap` backend falls back to `Docker` by default.
+    /// Set `true` to fail fast instead.
+    #[serde(default)]
+    pub disable_docker_fallback: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModalConfig {
+    pub function_name: Option<String>,
+    pub gpu: Option<String>,
+    pub memory: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConfig {
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub key_path: Option<String>,
+    /// Directory to `cd` into on the remote host before running the agent command.
+    /// Defaults to whatever directory the SSH session lands in (usually `$HOME`).
+    pub workdir: Option<String>,
+}
+
+/// Execution backend selection and per-backend tuning for a node run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionConfig {
+    #[serde(default)]
+    pub backend: ExecutionBackend,
+    pub docker: Option<DockerConfig>,
+    pub sandbox: Option<SandboxConfig>,
+    pub modal: Option<ModalConfig>,
+    pub remote: Option<RemoteConfig>,
+    /// Signal to deliver first when gracefully stopping this node's process.
+    /// Defaults to `Sigterm`.
+    #[serde(default)]
+    pub stop_signal: Option<StopSignal>,
+    /// Seconds to wait after `stop_signal` before escalating to SIGKILL.
+    /// Defaults to `executors::DEFAULT_STOP_TIMEOUT` when unset.
+    pub stop_timeout: Option<u64>,
+    /// Seconds of zero output activity (neither stdout nor stderr) before a streaming
+    /// executor treats the process as stalled and stops it early. Disabled (`None`) by
+    /// default so long silent computations aren't killed.
+    pub stall_timeout: Option<u64>,
+    /// What to do when this node is re-run while its previous execution is still live.
+    /// Defaults to `OnBusyUpdate::DoNothing`.
+    #[serde(default)]
+    pub on_busy_update: Option<OnBusyUpdate>,
+    /// Retry a transient failure (`ExecutorError::Process`/`Io`/`Timeout`) instead of
+    /// settling straight into `Failed`. Disabled (`None`) by default -- a node only gets
+    /// retried if it opts in.
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
+}
+
+/// Exponential-backoff retry policy for a node's execution. See `executors::execute`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryConfig {
+    /// Total attempts, including the first. `1` means "no retry" despite being configured.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles after each subsequent attempt.
+    pub base_delay_ms: u64,
 }
 
 fn now_ms() -> i64 {
@@ -75,13 +296,23 @@ pub async fn list_projects(state: tauri::State<'_, AppState>) -> Result<Vec<Proj
 
     // Reset transient statuses on startup/restart so the UI doesn't get stuck in "Starting...".
     // (We don't attempt to reconcile old tmux sessions here; this is a safe default.)
+    // `AgentState::can_transition` always allows a reset back to `Pending`, so this is a
+    // legal transition even though nothing ran it through `AppState::state_tx` -- there's
+    // no live execution to report it to a channel consumer yet at this point in startup.
     let mut changed = false;
     {
         let mut guard = state.projects.write().await;
         for project in guard.values_mut() {
             for node in project.nodes.iter_mut() {
-                if node.status == "running" || node.status == "awaiting_input" {
-                    node.status = "pending".to_string();
+                if matches!(
+                    node.status,
+                    AgentState::Starting
+                        | AgentState::Running
+                        | AgentState::AwaitingInput
+                        | AgentState::Checking
+                        | AgentState::Retrying
+                ) {
+                    node.status = AgentState::Pending;
                     changed = true;
                 }
             }
@@ -104,9 +335,15 @@ pub async fn get_project(
     Ok(guard.get(&id).cloned())
 }
 
+/// Creates in `AppState.projects` (the store the rest of the app reads from) and
+/// mirrors into `db::Database` with the same id, so `db::Database`'s CRDT change log
+/// (see `db::crdt`) has a row to attach node/edge changes to from the start instead of
+/// only ever seeing projects created through `db::Database::create_project` directly,
+/// which nothing else calls.
 #[tauri::command]
 pub async fn create_project(
     state: tauri::State<'_, AppState>,
+    db: tauri::State<'_, Arc<Database>>,
     name: String,
     description: String,
 ) -> Result<Project, String> {
@@ -114,23 +351,32 @@ pub async fn create_project(
     let ts = now_ms();
     let project = Project {
         id: id.clone(),
-        name,
-        description,
+        name: name.clone(),
+        description: description.clone(),
         location: None,
+        context: ProjectContext::default(),
         nodes: vec![],
         edges: vec![],
+        default_execution_config: None,
         created_at: ts,
         updated_at: ts,
+        notify: vec![],
     };
 
-    state.projects.write().await.insert(id, project.clone());
+    state.projects.write().await.insert(id.clone(), project.clone());
     state.persist_projects().await?;
+    db.create_project(&id, &name, Some(&description), None)
+        .map_err(|e| e.to_string())?;
     Ok(project)
 }
 
+/// See `create_project`'s doc comment -- also mirrors into `db::Database` so a bulk
+/// project save (as opposed to a single `commands::nodes` edit) keeps its `nodes`/`edges`
+/// CRDT rows up to date too.
 #[tauri::command]
 pub async fn save_project(
     state: tauri::State<'_, AppState>,
+    db: tauri::State<'_, Arc<Database>>,
     project: Project,
 ) -> Result<Project, String> {
     let mut project = project;
@@ -141,13 +387,19 @@ pub async fn save_project(
         .await
         .insert(project.id.clone(), project.clone());
     state.persist_projects().await?;
+    db.update_project(&project).map_err(|e| e.to_string())?;
     Ok(project)
 }
 
 #[tauri::command]
-pub async fn delete_project(state: tauri::State<'_, AppState>, id: String) -> Result<(), String> {
+pub async fn delete_project(
+    state: tauri::State<'_, AppState>,
+    db: tauri::State<'_, Arc<Database>>,
+    id: String,
+) -> Result<(), String> {
     state.projects.write().await.remove(&id);
     state.persist_projects().await?;
+    db.delete_project(&id).map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -206,7 +458,7 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
         context: vec![],
         deliverables: vec![],
         checks: vec![],
-        status: "pending".into(),
+        status: AgentState::Pending,
     };
 
     let node_one_shot_stream = Node {
@@ -223,7 +475,7 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
         context: vec![],
         deliverables: vec![],
         checks: vec![],
-        status: "pending".into(),
+        status: AgentState::Pending,
     };
 
     let node_one_shot_approval = Node {
@@ -240,7 +492,7 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
         context: vec![],
         deliverables: vec![],
         checks: vec![json!({ "id": Uuid::new_v4().to_string(), "type": "human_approval" })],
-        status: "pending".into(),
+        status: AgentState::Pending,
     };
 
     let check_file_exists_id = Uuid::new_v4().to_string();
@@ -267,7 +519,7 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
             json!({ "id": check_contains_id, "type": "contains", "path": "result.txt", "pattern": "hello from Orchestra" }),
             json!({ "id": check_command_id, "type": "command", "cmd": "ls -la result.txt" }),
         ],
-        status: "pending".into(),
+        status: AgentState::Pending,
     };
 
     let node_codex_one_shot = Node {
@@ -284,7 +536,7 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
         context: vec![],
         deliverables: vec![],
         checks: vec![],
-        status: "pending".into(),
+        status: AgentState::Pending,
     };
 
     let node_gemini_interactive = Node {
@@ -301,7 +553,7 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
         context: vec![],
         deliverables: vec![],
         checks: vec![],
-        status: "pending".into(),
+        status: AgentState::Pending,
     };
 
     // A couple of edges, just to have something on the canvas (execution is still single-node).
@@ -326,6 +578,7 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
         description: "Template project for exercising Orchestra features (interactive tmux sessions, one-shot runs, checks, inbox)."
             .into(),
         location: Some(workspace_str),
+        context: ProjectContext::default(),
         nodes: vec![
             node_interactive,
             node_one_shot_stream,
@@ -335,8 +588,10 @@ You can delete it at any time; Orchestra will recreate it when you re-create the
             node_gemini_interactive,
         ],
         edges,
+        default_execution_config: None,
         created_at,
         updated_at: ts,
+        notify: vec![],
     };
 
     guard.insert(id, project.clone());
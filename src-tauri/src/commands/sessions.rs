@@ -1,8 +1,14 @@
-use crate::sessions::manager::{Session, SessionManager};
-use crate::sessions::tmux;
-use serde::Deserialize;
+use crate::commands::projects::{ExecutionConfig, OnBusyUpdate};
+use crate::executors;
+use crate::sessions::backend::InteractiveBackend;
+use crate::sessions::checks::{self, Check, CheckContext, Deliverable};
+use crate::sessions::events::{DeliverableDetectedEvent, SessionChecksUpdatedEvent};
+use crate::sessions::manager::{Session, SessionManager, SessionStatus};
+use crate::sessions::supervisor::{Supervisor, WorkerStatus};
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use tauri::State;
+use tauri::{Emitter, State};
 
 /// Validate session ID to prevent operations on arbitrary tmux sessions.
 /// Only allows orchestra-prefixed session IDs with alphanumeric/dash/underscore chars.
@@ -58,6 +64,69 @@ pub struct CreateInteractiveSessionInput {
     pub extra_args: Option<Vec<String>>,
     pub prompt: String,
     pub cwd: Option<String>,
+    /// Governs what happens if `node_id` already has a live session (see `OnBusyUpdate`).
+    #[serde(default)]
+    pub execution_config: Option<ExecutionConfig>,
+}
+
+/// Find a still-live session (Running or AwaitingInput) for `node_id`, if any.
+async fn find_live_session_for_node(state: &SessionManager, node_id: &str) -> Option<Session> {
+    state
+        .list_sessions()
+        .await
+        .into_iter()
+        .find(|s| s.node_id == node_id && matches!(s.status, SessionStatus::Running | SessionStatus::AwaitingInput))
+}
+
+/// Apply the `OnBusyUpdate` policy for a new run of `node_id` against any session
+/// already live for it. Returns `Ok(None)` when it's clear to proceed with a normal
+/// `create_session` call, `Ok(Some(session))` to short-circuit and hand back the
+/// existing session (the `Signal` case), or `Err` to reject the re-run.
+async fn apply_on_busy_update(
+    state: &SessionManager,
+    node_id: &str,
+    execution_config: Option<&ExecutionConfig>,
+) -> Result<Option<Session>, String> {
+    let Some(existing) = find_live_session_for_node(state, node_id).await else {
+        return Ok(None);
+    };
+
+    let policy = execution_config
+        .and_then(|c| c.on_busy_update)
+        .unwrap_or_default();
+
+    match policy {
+        OnBusyUpdate::DoNothing => Err(format!(
+            "Node {node_id} already has a running session ({}); on-busy-update policy is doNothing",
+            existing.id
+        )),
+        OnBusyUpdate::Signal => {
+            let (signal, _grace) = executors::stop_policy(execution_config);
+            if let Some(kind) = state.backend_kind(&existing.id).await {
+                let pid = state.backend(kind).pane_pid(&existing.id).map_err(|e| e.0)?;
+                executors::send_signal_to_group(pid, signal);
+            }
+            Ok(Some(existing))
+        }
+        OnBusyUpdate::Restart => {
+            let (signal, grace) = executors::stop_policy(execution_config);
+            if let Some(kind) = state.backend_kind(&existing.id).await {
+                if let Ok(pid) = state.backend(kind).pane_pid(&existing.id) {
+                    executors::stop_pid(pid, signal, grace)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            state.kill_session(&existing.id).await?;
+            Ok(None)
+        }
+        OnBusyUpdate::Queue => {
+            while find_live_session_for_node(state, node_id).await.is_some() {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            }
+            Ok(None)
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -80,12 +149,124 @@ pub struct CaptureSessionOutputInput {
     pub lines: Option<usize>,
 }
 
+/// Look up `node_id`'s checks and deliverables, parsed from their free-form JSON the same
+/// way `sessions::monitor::get_node_checks_and_label` does for the completion-time run.
+async fn get_node_checks_and_deliverables(
+    app_state: &AppState,
+    node_id: &str,
+) -> (Vec<Check>, Vec<Deliverable>) {
+    let projects = app_state.projects.read().await;
+    for project in projects.values() {
+        if let Some(node) = project.nodes.iter().find(|n| n.id == node_id) {
+            let checks = node
+                .checks
+                .iter()
+                .filter_map(|v| serde_json::from_value(v.clone()).ok())
+                .collect();
+            let deliverables = node
+                .deliverables
+                .iter()
+                .filter_map(|v| serde_json::from_value(v.clone()).ok())
+                .collect();
+            return (checks, deliverables);
+        }
+    }
+    (Vec::new(), Vec::new())
+}
+
+/// Start watching `session`'s node for its `deliverables` (see `checks::run_checks_watch`)
+/// so the UI hears about a deliverable file as soon as it appears instead of waiting for
+/// the session to finish, registering the resulting `WatchHandle` with `session_manager` so
+/// `kill_interactive_session` tears it down. A no-op when the node has no deliverables to
+/// watch for. Best-effort: a watcher that fails to start (e.g. an unwatchable `cwd`) just
+/// means this session falls back to the existing completion-time check run.
+async fn start_deliverable_watch(
+    window: &tauri::Window,
+    session_manager: &SessionManager,
+    app_state: &AppState,
+    session: &Session,
+) {
+    let (checks, deliverables) = get_node_checks_and_deliverables(app_state, &session.node_id).await;
+    if deliverables.is_empty() {
+        return;
+    }
+
+    let ctx = CheckContext {
+        cwd: session.cwd.clone(),
+        output: String::new(),
+        exit_code: 0,
+        session_id: session.id.clone(),
+        node_id: session.node_id.clone(),
+    };
+
+    let results_window = window.clone();
+    let results_session_id = session.id.clone();
+    let results_node_id = session.node_id.clone();
+    let deliverable_window = window.clone();
+    let deliverable_session_id = session.id.clone();
+    let deliverable_node_id = session.node_id.clone();
+
+    let handle = checks::run_checks_watch(
+        checks,
+        ctx,
+        deliverables,
+        Vec::new(),
+        move |check_results| {
+            if let Err(e) = results_window.emit(
+                "session://checks_updated",
+                SessionChecksUpdatedEvent {
+                    session_id: results_session_id.clone(),
+                    node_id: results_node_id.clone(),
+                    check_results,
+                },
+            ) {
+                tracing::warn!("Failed to emit checks updated event: {}", e);
+            }
+        },
+        move |deliverable| {
+            if let Err(e) = deliverable_window.emit(
+                "session://deliverable_detected",
+                DeliverableDetectedEvent {
+                    session_id: deliverable_session_id.clone(),
+                    node_id: deliverable_node_id.clone(),
+                    deliverable_id: deliverable.id.clone(),
+                    path: deliverable.path.clone(),
+                    timestamp: chrono::Utc::now().timestamp_millis(),
+                },
+            ) {
+                tracing::warn!("Failed to emit deliverable detected event: {}", e);
+            }
+        },
+    );
+
+    match handle {
+        Ok(handle) => session_manager.set_deliverable_watch(&session.id, handle).await,
+        Err(e) => tracing::warn!(
+            "Failed to start deliverable watch for session {}: {}",
+            session.id,
+            e
+        ),
+    }
+}
+
 #[tauri::command]
 pub async fn create_interactive_session(
+    window: tauri::Window,
     state: State<'_, SessionManager>,
+    app_state: State<'_, AppState>,
     input: CreateInteractiveSessionInput,
 ) -> Result<Session, String> {
-    state
+    if let Some(existing) = apply_on_busy_update(
+        &state,
+        &input.node_id,
+        input.execution_config.as_ref(),
+    )
+    .await?
+    {
+        return Ok(existing);
+    }
+
+    let session = state
         .create_session(
             &input.node_id,
             &input.agent,
@@ -94,13 +275,44 @@ pub async fn create_interactive_session(
             &input.prompt,
             input.cwd.as_deref(),
         )
-        .await
+        .await?;
+
+    start_deliverable_watch(&window, &state, &app_state, &session).await;
+
+    Ok(session)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachSessionInput {
+    pub session_id: String,
+    /// Kick any other client already attached to this session off instead of sharing
+    /// the view (tmux's `attach -d`).
+    #[serde(default)]
+    pub detach: bool,
 }
 
 #[tauri::command]
-pub async fn attach_session(input: SessionIdInput) -> Result<(), String> {
+pub async fn attach_session(
+    state: State<'_, SessionManager>,
+    input: AttachSessionInput,
+) -> Result<(), String> {
     validate_session_id(&input.session_id)?;
 
+    let attach_command = state
+        .backend_kind(&input.session_id)
+        .await
+        .and_then(|kind| state.backend(kind).attach_command(&input.session_id, input.detach))
+        .ok_or_else(|| {
+            format!(
+                "Session {} has no external attach command (native PTY sessions can only be \
+                 viewed from within Orchestra)",
+                input.session_id
+            )
+        })?;
+
+    state.mark_attached(&input.session_id).await;
+
     let terminal = std::env::var("ORCHESTRA_TERMINAL").unwrap_or_else(|_| "Ghostty".to_string());
     let session_id = input.session_id;
 
@@ -120,8 +332,7 @@ pub async fn attach_session(input: SessionIdInput) -> Result<(), String> {
             "Terminal" => {
                 // macOS Terminal.app uses osascript for command execution
                 // Escape the command to prevent AppleScript injection
-                let attach_cmd = tmux::get_attach_command(&session_id);
-                let escaped_cmd = escape_applescript(&attach_cmd);
+                let escaped_cmd = escape_applescript(&attach_command);
                 std::process::Command::new("osascript")
                     .args([
                         "-e",
@@ -143,8 +354,7 @@ pub async fn attach_session(input: SessionIdInput) -> Result<(), String> {
 
         if result.is_err() {
             // Fallback to Terminal.app with escaped command
-            let attach_cmd = tmux::get_attach_command(&session_id);
-            let escaped_cmd = escape_applescript(&attach_cmd);
+            let escaped_cmd = escape_applescript(&attach_command);
             std::process::Command::new("osascript")
                 .args([
                     "-e",
@@ -160,12 +370,11 @@ pub async fn attach_session(input: SessionIdInput) -> Result<(), String> {
 
     #[cfg(target_os = "linux")]
     {
-        let attach_cmd = tmux::get_attach_command(&session_id);
         let terminals = ["ghostty", "alacritty", "kitty", "gnome-terminal", "xterm"];
         for term in terminals {
             if which::which(term).is_ok() {
                 std::process::Command::new(term)
-                    .args(["-e", "sh", "-c", &attach_cmd])
+                    .args(["-e", "sh", "-c", &attach_command])
                     .spawn()
                     .map_err(|e| format!("Failed to open terminal: {}", e))?;
                 return Ok(());
@@ -178,16 +387,138 @@ pub async fn attach_session(input: SessionIdInput) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn send_session_input(input: SendSessionInput) -> Result<(), String> {
+pub async fn send_session_input(
+    state: State<'_, SessionManager>,
+    input: SendSessionInput,
+) -> Result<(), String> {
     validate_session_id(&input.session_id)?;
-    tmux::send_keys(&input.session_id, &input.input).map_err(|e| e.0)
+    let kind = state
+        .backend_kind(&input.session_id)
+        .await
+        .ok_or_else(|| format!("Unknown session {}", input.session_id))?;
+    state
+        .backend(kind)
+        .send_input(&input.session_id, &input.input)
+        .map_err(|e| e.0)
 }
 
 #[tauri::command]
-pub async fn capture_session_output(input: CaptureSessionOutputInput) -> Result<String, String> {
+pub async fn capture_session_output(
+    state: State<'_, SessionManager>,
+    input: CaptureSessionOutputInput,
+) -> Result<String, String> {
     validate_session_id(&input.session_id)?;
     let lines = input.lines.unwrap_or(50).clamp(1, 5000);
-    tmux::capture_pane(&input.session_id, lines).map_err(|e| e.0)
+    let kind = state
+        .backend_kind(&input.session_id)
+        .await
+        .ok_or_else(|| format!("Unknown session {}", input.session_id))?;
+    state
+        .backend(kind)
+        .capture_output(&input.session_id, lines)
+        .map_err(|e| e.0)
+}
+
+/// Start forwarding a native PTY session's live output to the frontend as
+/// `session://pty_output` events, instead of the UI having to poll
+/// `capture_session_output`. Only supported on the PTY backend -- a tmux session is meant
+/// to be attached from an external terminal instead. Safe to call more than once for the
+/// same session; each call starts its own tailing cursor, so the UI doesn't need to track
+/// whether a stream is already running.
+#[tauri::command]
+pub async fn stream_session_output(
+    window: tauri::Window,
+    state: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<(), String> {
+    use crate::sessions::backend::InteractiveBackendKind;
+    use crate::sessions::events::PtyOutputEvent;
+
+    validate_session_id(&session_id)?;
+    let kind = state
+        .backend_kind(&session_id)
+        .await
+        .ok_or_else(|| format!("Unknown session {}", session_id))?;
+
+    if kind != InteractiveBackendKind::Pty {
+        return Err(
+            "stream_session_output is only supported for native PTY sessions; tmux sessions \
+             are viewed by attaching an external terminal instead"
+                .to_string(),
+        );
+    }
+
+    let pty = state.pty_backend();
+
+    tokio::spawn(async move {
+        let mut cursor = 0u64;
+        let mut ticker = tokio::time::interval(std::time::Duration::from_millis(80));
+
+        loop {
+            ticker.tick().await;
+
+            let poll_pty = pty.clone();
+            let poll_session_id = session_id.clone();
+            let read = tokio::task::spawn_blocking(move || {
+                if !poll_pty.session_exists(&poll_session_id) {
+                    return None;
+                }
+                poll_pty.read_new_output(&poll_session_id, cursor).ok()
+            })
+            .await
+            .ok()
+            .flatten();
+
+            let Some((chunk, new_cursor)) = read else {
+                break;
+            };
+            cursor = new_cursor;
+
+            if chunk.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = window.emit(
+                "session://pty_output",
+                PtyOutputEvent {
+                    session_id: session_id.clone(),
+                    chunk,
+                },
+            ) {
+                tracing::warn!("Failed to emit PTY output event: {}", e);
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResizeSessionInput {
+    pub session_id: String,
+    pub rows: u16,
+    pub cols: u16,
+}
+
+/// Propagate a terminal window-size change to a session's backend. Only the native PTY
+/// backend can actually honor this (see `InteractiveBackend::resize`); tmux panes size
+/// themselves off whichever client is attached.
+#[tauri::command]
+pub async fn resize_session(
+    state: State<'_, SessionManager>,
+    input: ResizeSessionInput,
+) -> Result<(), String> {
+    validate_session_id(&input.session_id)?;
+    let kind = state
+        .backend_kind(&input.session_id)
+        .await
+        .ok_or_else(|| format!("Unknown session {}", input.session_id))?;
+    state
+        .backend(kind)
+        .resize(&input.session_id, input.rows, input.cols)
+        .map_err(|e| e.0)
 }
 
 #[tauri::command]
@@ -199,22 +530,114 @@ pub async fn kill_interactive_session(
     state.kill_session(&input.session_id).await
 }
 
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ListInteractiveSessionsInput {
+    /// Only include sessions whose id, node id, or agent contains this substring.
+    #[serde(default)]
+    pub search: Option<String>,
+    /// Return only session ids instead of full `Session` records.
+    #[serde(default)]
+    pub quiet: bool,
+}
+
+/// A listed session annotated with whether it's the one `switch_session` (called with
+/// no argument) would jump back to, so the UI can render a "previous" indicator.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionListEntry {
+    #[serde(flatten)]
+    pub session: Session,
+    pub is_previous: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", untagged)]
+pub enum SessionListResult {
+    Quiet(Vec<String>),
+    Full(Vec<SessionListEntry>),
+}
+
 #[tauri::command]
 pub async fn list_interactive_sessions(
     state: State<'_, SessionManager>,
-) -> Result<Vec<Session>, String> {
-    Ok(state.list_sessions().await)
+    input: Option<ListInteractiveSessionsInput>,
+) -> Result<SessionListResult, String> {
+    let input = input.unwrap_or_default();
+
+    let sessions = state
+        .list_sessions()
+        .await
+        .into_iter()
+        .filter(|s| match &input.search {
+            Some(q) => s.id.contains(q.as_str()) || s.node_id.contains(q.as_str()) || s.agent.contains(q.as_str()),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+
+    if input.quiet {
+        return Ok(SessionListResult::Quiet(
+            sessions.into_iter().map(|s| s.id).collect(),
+        ));
+    }
+
+    let previous = state.previous_attached().await;
+    Ok(SessionListResult::Full(
+        sessions
+            .into_iter()
+            .map(|session| {
+                let is_previous = previous.as_deref() == Some(session.id.as_str());
+                SessionListEntry { session, is_previous }
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchSessionInput {
+    /// Session to switch to; defaults to the previously-attached session when omitted.
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
+/// Remux-style navigation: switch attachment to `session_id`, or back to the
+/// previously-attached session when none is given.
 #[tauri::command]
-pub fn get_attach_command(input: SessionIdInput) -> Result<String, String> {
+pub async fn switch_session(
+    state: State<'_, SessionManager>,
+    input: SwitchSessionInput,
+) -> Result<Session, String> {
+    if let Some(id) = &input.session_id {
+        validate_session_id(id)?;
+    }
+    state.switch_session(input.session_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn get_attach_command(
+    state: State<'_, SessionManager>,
+    input: SessionIdInput,
+) -> Result<String, String> {
     validate_session_id(&input.session_id)?;
-    Ok(tmux::get_attach_command(&input.session_id))
+    state
+        .backend_kind(&input.session_id)
+        .await
+        .and_then(|kind| state.backend(kind).attach_command(&input.session_id, false))
+        .ok_or_else(|| format!("Session {} has no external attach command", input.session_id))
 }
 
 #[tauri::command]
-pub fn open_in_ghostty(input: SessionIdInput) -> Result<(), String> {
+pub async fn open_in_ghostty(
+    state: State<'_, SessionManager>,
+    input: SessionIdInput,
+) -> Result<(), String> {
     validate_session_id(&input.session_id)?;
+    let attach_command = state
+        .backend_kind(&input.session_id)
+        .await
+        .and_then(|kind| state.backend(kind).attach_command(&input.session_id, false))
+        .ok_or_else(|| format!("Session {} has no external attach command", input.session_id))?;
     let session_id = input.session_id;
 
     #[cfg(target_os = "macos")]
@@ -230,12 +653,45 @@ pub fn open_in_ghostty(input: SessionIdInput) -> Result<(), String> {
 
     #[cfg(target_os = "linux")]
     {
-        let attach_cmd = tmux::get_attach_command(&session_id);
         std::process::Command::new("ghostty")
-            .args(["-e", "sh", "-c", &attach_cmd])
+            .args(["-e", "sh", "-c", &attach_command])
             .spawn()
             .map_err(|e| format!("Failed to open Ghostty: {}", e))?;
     }
 
     Ok(())
 }
+
+#[tauri::command]
+pub async fn list_workers(state: State<'_, Supervisor>) -> Result<Vec<WorkerStatus>, String> {
+    Ok(state.list_workers().await)
+}
+
+#[tauri::command]
+pub async fn pause_worker(
+    supervisor: State<'_, Supervisor>,
+    session_manager: State<'_, SessionManager>,
+    input: SessionIdInput,
+) -> Result<(), String> {
+    validate_session_id(&input.session_id)?;
+    supervisor.pause(&session_manager, &input.session_id).await
+}
+
+#[tauri::command]
+pub async fn resume_worker(
+    supervisor: State<'_, Supervisor>,
+    session_manager: State<'_, SessionManager>,
+    input: SessionIdInput,
+) -> Result<(), String> {
+    validate_session_id(&input.session_id)?;
+    supervisor.resume(&session_manager, &input.session_id).await
+}
+
+#[tauri::command]
+pub async fn cancel_worker(
+    state: State<'_, Supervisor>,
+    input: SessionIdInput,
+) -> Result<(), String> {
+    validate_session_id(&input.session_id)?;
+    state.cancel(&input.session_id).await
+}
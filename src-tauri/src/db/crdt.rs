@@ -0,0 +1,339 @@
+//! Change-data-capture + last-writer-wins CRDT layer for multi-device sync.
+//!
+//! `projects.nodes`/`projects.edges` are stored as JSON blobs for simplicity, which makes
+//! field-level conflict resolution impossible: two devices editing the same node at once
+//! just clobber each other on whichever write lands last. This module adds a parallel
+//! change log instead -- every write to a node also records one row per changed top-level
+//! field into `__crdt_changes`, tagged with this device's `site_id` and a monotonic
+//! `col_version`/`db_version`. Two devices converge by exchanging
+//! `Database::export_changes`/`Database::apply_changes` over whatever transport the
+//! caller wires up (a relay server, CloudKit, a direct connection -- this module doesn't
+//! care) and resolving conflicts with last-writer-wins: for each `(table, pk, col)` the
+//! change with the greater `col_version` wins, ties broken by comparing `site_id`
+//! lexically so every peer resolves a tie the same way without coordinating.
+//!
+//! Deletes are modeled as tombstone rows (a change on [`TOMBSTONE_COL`]) rather than
+//! removed rows, so they propagate through the log exactly like any other field edit.
+
+use super::DbResult;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Sentinel column name for a tombstone: a delete is recorded as a change on this column
+/// rather than by removing history, so the delete itself propagates and can still lose to
+/// a genuinely later edit from another site.
+pub const TOMBSTONE_COL: &str = "__deleted__";
+
+pub type SiteId = String;
+
+/// One field-level change, as exchanged between peers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    pub table_name: String,
+    pub pk: String,
+    pub col_name: String,
+    pub value: Option<String>,
+    pub col_version: i64,
+    pub db_version: i64,
+    pub site_id: SiteId,
+}
+
+/// Ensure this device has a persistent `site_id`, creating one the first time the
+/// database is opened. Called once from `Database::new`/`Database::new_in_memory`.
+pub fn ensure_site_id(conn: &Connection) -> DbResult<SiteId> {
+    let existing: Option<String> = conn
+        .query_row("SELECT site_id FROM __crdt_meta LIMIT 1", [], |row| row.get(0))
+        .optional()?;
+
+    if let Some(site_id) = existing {
+        return Ok(site_id);
+    }
+
+    let site_id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO __crdt_meta (site_id, db_version) VALUES (?, 0)",
+        params![&site_id],
+    )?;
+    Ok(site_id)
+}
+
+/// Bump and return this device's logical clock. Call once per write transaction, before
+/// recording that write's changes, so every field changed by the same write shares a
+/// `db_version`.
+pub fn next_db_version(conn: &Connection, site_id: &str) -> DbResult<i64> {
+    conn.execute(
+        "UPDATE __crdt_meta SET db_version = db_version + 1 WHERE site_id = ?",
+        params![site_id],
+    )?;
+    let version = conn.query_row(
+        "SELECT db_version FROM __crdt_meta WHERE site_id = ?",
+        params![site_id],
+        |row| row.get(0),
+    )?;
+    Ok(version)
+}
+
+/// Highest `col_version` already recorded for `(table, pk, col)`; `0` if nothing has
+/// touched this field yet.
+fn current_col_version(conn: &Connection, table_name: &str, pk: &str, col_name: &str) -> DbResult<i64> {
+    let version: Option<i64> = conn.query_row(
+        "SELECT MAX(col_version) FROM __crdt_changes WHERE table_name = ? AND pk = ? AND col_name = ?",
+        params![table_name, pk, col_name],
+        |row| row.get(0),
+    )?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Record one local field-level change within `tx`, claiming the next `col_version` for
+/// `(table, pk, col)`. Call once per changed field in the same transaction as the
+/// underlying write, so the change log and the materialized row never drift apart.
+pub fn record_change(
+    conn: &Connection,
+    site_id: &str,
+    db_version: i64,
+    table_name: &str,
+    pk: &str,
+    col_name: &str,
+    value: Option<&str>,
+) -> DbResult<()> {
+    let col_version = current_col_version(conn, table_name, pk, col_name)? + 1;
+    conn.execute(
+        "INSERT INTO __crdt_changes (table_name, pk, col_name, value, col_version, db_version, site_id)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        params![table_name, pk, col_name, value, col_version, db_version, site_id],
+    )?;
+    Ok(())
+}
+
+/// `true` if `incoming` should win over whatever is already recorded for its
+/// `(table, pk, col)`: a strictly greater `col_version`, or an equal one broken by
+/// comparing `site_id` lexically.
+fn wins_over(incoming: &Change, existing_version: i64, existing_site_id: Option<&str>) -> bool {
+    match incoming.col_version.cmp(&existing_version) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => match existing_site_id {
+            Some(site) => incoming.site_id.as_str() > site,
+            None => true,
+        },
+    }
+}
+
+/// All changes this device has ever recorded, whose `db_version` exceeds
+/// `since[site_id]` for that change's own originating site -- i.e. what a peer would need
+/// to catch up. Omitting a site from `since` requests its full history.
+pub fn export_changes(conn: &Connection, since: &HashMap<SiteId, u64>) -> DbResult<Vec<Change>> {
+    let mut stmt = conn.prepare(
+        "SELECT table_name, pk, col_name, value, col_version, db_version, site_id
+         FROM __crdt_changes ORDER BY seq ASC",
+    )?;
+    let changes = stmt
+        .query_map([], |row| {
+            Ok(Change {
+                table_name: row.get(0)?,
+                pk: row.get(1)?,
+                col_name: row.get(2)?,
+                value: row.get(3)?,
+                col_version: row.get(4)?,
+                db_version: row.get(5)?,
+                site_id: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(changes
+        .into_iter()
+        .filter(|c| c.db_version > since.get(&c.site_id).copied().unwrap_or(0) as i64)
+        .collect())
+}
+
+/// Merged watermark: for every remote site this device has ever recorded a change from,
+/// the highest `db_version` seen. A peer can hand this back as the `since` map for its
+/// next `export_changes` call.
+pub fn watermarks(conn: &Connection, local_site_id: &str) -> DbResult<HashMap<SiteId, u64>> {
+    let mut stmt = conn.prepare(
+        "SELECT site_id, MAX(db_version) FROM __crdt_changes WHERE site_id != ? GROUP BY site_id",
+    )?;
+    let rows = stmt
+        .query_map(params![local_site_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows.into_iter().map(|(site, version)| (site, version as u64)).collect())
+}
+
+/// Merge a batch of remote changes into the local log, returning only the ones that won
+/// their `(table, pk, col)` slot -- the ones the caller needs to materialize into its own
+/// tables. Every incoming change is recorded regardless of outcome (skipping exact
+/// duplicates, so re-applying the same batch twice is harmless), so a later, correctly
+/// ordered change can still compare against it.
+pub fn apply_changes(conn: &Connection, changes: &[Change]) -> DbResult<Vec<Change>> {
+    let mut applied = Vec::new();
+
+    for change in changes {
+        let already_recorded: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM __crdt_changes
+              WHERE table_name = ? AND pk = ? AND col_name = ? AND col_version = ? AND site_id = ?)",
+            params![
+                &change.table_name,
+                &change.pk,
+                &change.col_name,
+                change.col_version,
+                &change.site_id
+            ],
+            |row| row.get(0),
+        )?;
+        if already_recorded {
+            continue;
+        }
+
+        let existing_version = current_col_version(conn, &change.table_name, &change.pk, &change.col_name)?;
+        let existing_site_id: Option<String> = if existing_version == 0 {
+            None
+        } else {
+            conn.query_row(
+                "SELECT site_id FROM __crdt_changes
+                  WHERE table_name = ? AND pk = ? AND col_name = ? AND col_version = ?
+                  ORDER BY seq DESC LIMIT 1",
+                params![&change.table_name, &change.pk, &change.col_name, existing_version],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+
+        let won = wins_over(change, existing_version, existing_site_id.as_deref());
+
+        conn.execute(
+            "INSERT INTO __crdt_changes (table_name, pk, col_name, value, col_version, db_version, site_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                &change.table_name,
+                &change.pk,
+                &change.col_name,
+                &change.value,
+                change.col_version,
+                change.db_version,
+                &change.site_id,
+            ],
+        )?;
+
+        if won {
+            applied.push(change.clone());
+        }
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn conn_with_schema() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::initialize(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn ensure_site_id_is_stable_across_calls() {
+        let conn = conn_with_schema();
+        let first = ensure_site_id(&conn).unwrap();
+        let second = ensure_site_id(&conn).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn apply_changes_prefers_greater_col_version() {
+        let conn = conn_with_schema();
+        let older = Change {
+            table_name: "nodes".into(),
+            pk: "proj:node-1".into(),
+            col_name: "status".into(),
+            value: Some("\"running\"".into()),
+            col_version: 1,
+            db_version: 1,
+            site_id: "site-a".into(),
+        };
+        let newer = Change {
+            col_version: 2,
+            value: Some("\"done\"".into()),
+            db_version: 2,
+            site_id: "site-b".into(),
+            ..older.clone()
+        };
+
+        let applied_first = apply_changes(&conn, std::slice::from_ref(&older)).unwrap();
+        assert_eq!(applied_first, vec![older.clone()]);
+
+        let applied_second = apply_changes(&conn, std::slice::from_ref(&newer)).unwrap();
+        assert_eq!(applied_second, vec![newer]);
+    }
+
+    #[test]
+    fn apply_changes_breaks_ties_by_site_id() {
+        let conn = conn_with_schema();
+        let from_a = Change {
+            table_name: "nodes".into(),
+            pk: "proj:node-1".into(),
+            col_name: "status".into(),
+            value: Some("\"from-a\"".into()),
+            col_version: 1,
+            db_version: 1,
+            site_id: "aaaa".into(),
+        };
+        let from_z = Change {
+            site_id: "zzzz".into(),
+            value: Some("\"from-z\"".into()),
+            ..from_a.clone()
+        };
+
+        apply_changes(&conn, std::slice::from_ref(&from_a)).unwrap();
+        let applied = apply_changes(&conn, std::slice::from_ref(&from_z)).unwrap();
+        assert_eq!(applied, vec![from_z], "zzzz > aaaa lexically, so it should win the tie");
+    }
+
+    #[test]
+    fn apply_changes_is_idempotent() {
+        let conn = conn_with_schema();
+        let change = Change {
+            table_name: "nodes".into(),
+            pk: "proj:node-1".into(),
+            col_name: "status".into(),
+            value: Some("\"done\"".into()),
+            col_version: 1,
+            db_version: 1,
+            site_id: "site-a".into(),
+        };
+
+        apply_changes(&conn, std::slice::from_ref(&change)).unwrap();
+        let applied_again = apply_changes(&conn, std::slice::from_ref(&change)).unwrap();
+        assert!(applied_again.is_empty(), "re-applying the same change should be a no-op");
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM __crdt_changes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn export_changes_respects_watermark() {
+        let conn = conn_with_schema();
+        let site_id = ensure_site_id(&conn).unwrap();
+        let v1 = next_db_version(&conn, &site_id).unwrap();
+        record_change(&conn, &site_id, v1, "nodes", "proj:node-1", "status", Some("\"running\"")).unwrap();
+        let v2 = next_db_version(&conn, &site_id).unwrap();
+        record_change(&conn, &site_id, v2, "nodes", "proj:node-1", "status", Some("\"done\"")).unwrap();
+
+        let mut since = HashMap::new();
+        since.insert(site_id.clone(), v1 as u64);
+
+        let changes = export_changes(&conn, &since).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].db_version, v2);
+    }
+}
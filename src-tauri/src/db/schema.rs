@@ -20,7 +20,10 @@ pub fn initialize(conn: &Connection) -> Result<(), rusqlite::Error> {
             updated_at INTEGER NOT NULL
         );
 
-        -- Sessions table
+        -- Sessions table. `job_status`/`last_heartbeat` back the job-queue model in
+        -- `Database::claim_next_session`/`heartbeat_session` (see db::mod) -- separate
+        -- from `status`, which is a free-form execution status set by whichever backend
+        -- is running the session.
         CREATE TABLE IF NOT EXISTS sessions (
             id TEXT PRIMARY KEY,
             node_id TEXT NOT NULL,
@@ -32,7 +35,10 @@ pub fn initialize(conn: &Connection) -> Result<(), rusqlite::Error> {
             attach_command TEXT,
             container_id TEXT,
             started_at INTEGER NOT NULL,
-            completed_at INTEGER
+            completed_at INTEGER,
+            exit_code INTEGER,
+            job_status TEXT NOT NULL DEFAULT 'queued',
+            last_heartbeat INTEGER
         );
 
         -- Agent library table
@@ -100,9 +106,46 @@ pub fn initialize(conn: &Connection) -> Result<(), rusqlite::Error> {
             created_at INTEGER NOT NULL
         );
 
+        -- CRDT sync: per-device identity and logical clock (see db::crdt)
+        CREATE TABLE IF NOT EXISTS __crdt_meta (
+            site_id TEXT PRIMARY KEY,
+            db_version INTEGER NOT NULL DEFAULT 0
+        );
+
+        -- One row per observed field-level change, local or remote. `seq` orders changes
+        -- as this device saw them; `db_version`/`col_version` are the CRDT clocks --
+        -- `db_version` is the writer's logical clock at the time of the write,
+        -- `col_version` increments per (table_name, pk, col_name) and is what
+        -- last-writer-wins compares.
+        CREATE TABLE IF NOT EXISTS __crdt_changes (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            table_name TEXT NOT NULL,
+            pk TEXT NOT NULL,
+            col_name TEXT NOT NULL,
+            value TEXT,
+            col_version INTEGER NOT NULL,
+            db_version INTEGER NOT NULL,
+            site_id TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_crdt_changes_col ON __crdt_changes(table_name, pk, col_name);
+
+        -- Append-only log written alongside `sessions.output` by `append_session_output`,
+        -- meant to back a tailable/paginated reader so long-running sessions wouldn't need
+        -- re-reading the whole `sessions.output` blob. Nothing reads this table back out
+        -- today -- see the note after `append_session_output` in db/mod.rs.
+        CREATE TABLE IF NOT EXISTS session_output_chunks (
+            session_id TEXT NOT NULL,
+            seq INTEGER NOT NULL,
+            byte_offset INTEGER NOT NULL,
+            chunk TEXT NOT NULL,
+            PRIMARY KEY (session_id, seq)
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_output_chunks_offset ON session_output_chunks(session_id, byte_offset);
+
         -- Indexes for common queries
         CREATE INDEX IF NOT EXISTS idx_sessions_node_id ON sessions(node_id);
         CREATE INDEX IF NOT EXISTS idx_sessions_status ON sessions(status);
+        CREATE INDEX IF NOT EXISTS idx_sessions_job_status ON sessions(job_status);
         CREATE INDEX IF NOT EXISTS idx_node_runs_project ON node_runs(project_id);
         CREATE INDEX IF NOT EXISTS idx_node_runs_node ON node_runs(node_id);
         CREATE INDEX IF NOT EXISTS idx_code_todos_project ON code_todos(project_id);
@@ -0,0 +1,151 @@
+//! Bubblewrap executor - runs agents in a lightweight, rootless sandbox (Linux only)
+
+use super::{ExecuteRequest, ExecutionRegistry, ExecutionResult, ExecutorError, ExecutorResult};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::time::{timeout, Duration};
+
+/// Execution timeout (10 minutes, same as Docker)
+const EXECUTION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Execute an agent command inside a bubblewrap jail
+pub async fn execute_bwrap<F>(
+    request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
+where
+    F: Fn(String) + Send + 'static,
+{
+    let sandbox_config = request
+        .execution_config
+        .as_ref()
+        .and_then(|c| c.sandbox.as_ref());
+
+    let project_path = request.project_path.as_deref().ok_or_else(|| {
+        ExecutorError::Process("bwrap executor requires a project_path".to_string())
+    })?;
+
+    let agent_command =
+        crate::agent_command::one_shot_shell_command(&request.executor, &request.prompt, &request.options);
+
+    let mut args = vec![
+        "--ro-bind".to_string(),
+        "/usr".to_string(),
+        "/usr".to_string(),
+        "--ro-bind".to_string(),
+        "/lib".to_string(),
+        "/lib".to_string(),
+        "--ro-bind".to_string(),
+        "/bin".to_string(),
+        "/bin".to_string(),
+        "--proc".to_string(),
+        "/proc".to_string(),
+        "--dev".to_string(),
+        "/dev".to_string(),
+        "--bind".to_string(),
+        project_path.to_string(),
+        "/workspace".to_string(),
+        "--chdir".to_string(),
+        "/workspace".to_string(),
+        "--die-with-parent".to_string(),
+    ];
+
+    // Mirrors Docker's `--network none`.
+    if sandbox_config.and_then(|c| c.network.as_deref()) == Some("none") {
+        args.push("--unshare-net".to_string());
+    }
+
+    // Pass through environment variables
+    for var in [
+        "ANTHROPIC_API_KEY",
+        "OPENAI_API_KEY",
+        "GOOGLE_API_KEY",
+        "CLAUDE_CODE_OAUTH_TOKEN",
+    ] {
+        if let Ok(value) = std::env::var(var) {
+            args.push("--setenv".to_string());
+            args.push(var.to_string());
+            args.push(value);
+        }
+    }
+
+    args.push("sh".to_string());
+    args.push("-c".to_string());
+    args.push(agent_command);
+
+    tracing::info!("Executing in bwrap sandbox: bwrap {}", args.join(" "));
+
+    // Spawn bwrap process
+    let mut child = Command::new("bwrap")
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ExecutorError::Process(format!("Failed to start bwrap: {}", e)))?;
+
+    let _registry_guard = super::track_child(registry, &request.node_id, &child);
+
+    // Stream output
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+
+    let mut stdout_reader = BufReader::new(stdout).lines();
+    let mut stderr_reader = BufReader::new(stderr).lines();
+
+    let mut output = String::new();
+
+    let result = timeout(EXECUTION_TIMEOUT, async {
+        loop {
+            tokio::select! {
+                line = stdout_reader.next_line() => {
+                    match line {
+                        Ok(Some(line)) => {
+                            output.push_str(&line);
+                            output.push('\n');
+                            on_output(format!("{}\n", line));
+                        }
+                        Ok(None) => break,
+                        Err(_) => break,
+                    }
+                }
+                line = stderr_reader.next_line() => {
+                    match line {
+                        Ok(Some(line)) => {
+                            output.push_str(&line);
+                            output.push('\n');
+                            on_output(format!("{}\n", line));
+                        }
+                        Ok(None) => {}
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+
+        child.wait().await
+    })
+    .await;
+
+    match result {
+        Ok(Ok(status)) => {
+            if status.success() {
+                Ok(ExecutionResult::Done { output })
+            } else {
+                Ok(ExecutionResult::Error {
+                    message: format!(
+                        "bwrap sandbox exited with code {}",
+                        status.code().unwrap_or(-1)
+                    ),
+                })
+            }
+        }
+        Ok(Err(e)) => Err(ExecutorError::Process(e.to_string())),
+        Err(_) => {
+            let _ = child.kill().await;
+            Err(ExecutorError::Timeout)
+        }
+    }
+}
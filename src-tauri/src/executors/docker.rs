@@ -1,6 +1,6 @@
 //! Docker executor - runs agents in isolated containers
 
-use super::{ExecuteRequest, ExecutionResult, ExecutorError, ExecutorResult};
+use super::{ExecuteRequest, ExecutionRegistry, ExecutionResult, ExecutorError, ExecutorResult};
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
@@ -15,6 +15,7 @@ const EXECUTION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
 /// Execute an agent command inside a Docker container
 pub async fn execute_docker<F>(
     request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
     on_output: F,
 ) -> ExecutorResult<ExecutionResult>
 where
@@ -31,7 +32,8 @@ where
         .unwrap_or(DEFAULT_IMAGE);
 
     // Build the agent command
-    let agent_command = build_agent_command(&request.executor, &request.prompt, &request.options);
+    let agent_command =
+        crate::agent_command::one_shot_shell_command(&request.executor, &request.prompt, &request.options);
 
     // Build Docker run arguments
     let mut args = vec!["run".to_string(), "--rm".to_string()];
@@ -57,10 +59,7 @@ where
 
     // Mount project directory
     if let Some(project_path) = &request.project_path {
-        args.push("-v".to_string());
-        args.push(format!("{}:/workspace", project_path));
-        args.push("-w".to_string());
-        args.push("/workspace".to_string());
+        args.extend(volume_mount_args(project_path)?);
     }
 
     // Pass through environment variables
@@ -88,6 +87,8 @@ where
         .spawn()
         .map_err(|e| ExecutorError::Docker(format!("Failed to start Docker: {}", e)))?;
 
+    let _registry_guard = super::track_child(registry, &request.node_id, &child);
+
     // Stream output
     let stdout = child.stdout.take().unwrap();
     let stderr = child.stderr.take().unwrap();
@@ -169,7 +170,8 @@ where
     let container_name = format!("orchestra-{}", uuid::Uuid::new_v4());
 
     // Build the agent command with tmux wrapper
-    let agent_command = build_agent_command(&request.executor, &request.prompt, &request.options);
+    let agent_command =
+        crate::agent_command::one_shot_shell_command(&request.executor, &request.prompt, &request.options);
     let tmux_command = format!(
         "tmux new-session -d -s agent '{}' && tmux wait-for agent-done",
         agent_command.replace("'", "'\\''")
@@ -185,10 +187,7 @@ where
 
     // Mount project directory
     if let Some(project_path) = &request.project_path {
-        args.push("-v".to_string());
-        args.push(format!("{}:/workspace", project_path));
-        args.push("-w".to_string());
-        args.push("/workspace".to_string());
+        args.extend(volume_mount_args(project_path)?);
     }
 
     // Pass through environment variables
@@ -261,6 +260,7 @@ where
     Ok(ExecutionResult::Running {
         session_id: container_name,
         attach_command: Some(attach_command),
+        call_id: None,
     })
 }
 
@@ -282,70 +282,75 @@ pub async fn stop_container(container_id: &str) -> ExecutorResult<()> {
     Ok(())
 }
 
-/// Build the agent command string
-fn build_agent_command(
-    executor: &str,
-    prompt: &str,
-    options: &Option<serde_json::Value>,
-) -> String {
-    // Escape the prompt for shell
-    let escaped_prompt = shell_escape(prompt);
-
-    match executor {
-        "claude" => {
-            let mut cmd = format!(
-                "claude -p {} --output-format text --no-session-persistence --permission-mode dontAsk --tools ''",
-                escaped_prompt
-            );
-
-            if let Some(opts) = options {
-                if let Some(model) = opts.get("model").and_then(|v| v.as_str()) {
-                    cmd.push_str(&format!(" --model {}", model));
-                }
+/// Whether this Orchestra process is itself running inside a Docker/containerd
+/// container, cached after the first check. When true, a `-v host_path:/workspace`
+/// mount below would resolve `host_path` against the *outer host's* filesystem (the
+/// daemon we're talking to), not anything Orchestra's own container can see.
+fn inside_docker() -> bool {
+    static INSIDE_DOCKER: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *INSIDE_DOCKER.get_or_init(|| {
+        if std::path::Path::new("/.dockerenv").exists() {
+            return true;
+        }
+        if let Ok(cgroup) = std::fs::read_to_string("/proc/1/cgroup") {
+            if ["docker", "containerd", "kubepods"]
+                .iter()
+                .any(|marker| cgroup.contains(marker))
+            {
+                return true;
             }
-
-            cmd
         }
-
-        "codex" => {
-            let mut cmd = "codex exec --skip-git-repo-check".to_string();
-
-            if let Some(opts) = options {
-                let reasoning = opts
-                    .get("reasoningEffort")
-                    .or_else(|| opts.get("reasoningLevel"))
-                    .and_then(|v| v.as_str());
-
-                if let Some(level) = reasoning {
-                    if ["low", "medium", "high", "xhigh"].contains(&level) {
-                        cmd.push_str(&format!(" -c reasoning.effort={}", level));
-                    }
-                }
-
-                if let Some(model) = opts.get("model").and_then(|v| v.as_str()) {
-                    cmd.push_str(&format!(" -m {}", model));
-                }
+        if let Ok(mountinfo) = std::fs::read_to_string("/proc/self/mountinfo") {
+            if mountinfo.contains("/docker/containers/") || mountinfo.contains("containerd") {
+                return true;
             }
-
-            cmd.push_str(&format!(" {}", escaped_prompt));
-            cmd
         }
+        false
+    })
+}
 
-        "gemini" => {
-            let model = options
-                .as_ref()
-                .and_then(|o| o.get("model"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("gemini-3-pro-preview");
+/// Build the `-v .../-w` (or `--volumes-from`) arguments that mount `project_path`
+/// into the agent container, accounting for Orchestra running inside Docker itself.
+fn volume_mount_args(project_path: &str) -> ExecutorResult<Vec<String>> {
+    if !inside_docker() {
+        return Ok(vec![
+            "-v".to_string(),
+            format!("{}:/workspace", project_path),
+            "-w".to_string(),
+            "/workspace".to_string(),
+        ]);
+    }
 
-            format!("gemini {} -m {} -o text", escaped_prompt, model)
-        }
+    // Docker-in-Docker: prefer an explicit host-path mapping for `project_path` if one
+    // is configured (the same directory, named as the *host* daemon sees it).
+    if let Ok(host_workspace) = std::env::var("ORCHESTRA_HOST_WORKSPACE") {
+        return Ok(vec![
+            "-v".to_string(),
+            format!("{}:/workspace", host_workspace),
+            "-w".to_string(),
+            "/workspace".to_string(),
+        ]);
+    }
 
-        _ => escaped_prompt,
+    // No mapping configured: share our own container's mounts with the agent container
+    // instead, so `project_path` (as Orchestra sees it) is visible there too.
+    if let Ok(container_id) = std::fs::read_to_string("/etc/hostname") {
+        let container_id = container_id.trim();
+        if !container_id.is_empty() {
+            return Ok(vec![
+                "--volumes-from".to_string(),
+                container_id.to_string(),
+                "-w".to_string(),
+                project_path.to_string(),
+            ]);
+        }
     }
-}
 
-/// Escape a string for shell use
-fn shell_escape(s: &str) -> String {
-    format!("'{}'", s.replace("'", "'\\''"))
+    Err(ExecutorError::Docker(
+        "Orchestra is running inside Docker (docker-in-docker) but no host-path mapping is \
+         configured: set ORCHESTRA_HOST_WORKSPACE to the project path as seen by the host \
+         Docker daemon, or ensure /etc/hostname exposes this container's id for \
+         --volumes-from. Refusing to run the agent against an empty /workspace."
+            .to_string(),
+    ))
 }
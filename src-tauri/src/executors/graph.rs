@@ -0,0 +1,364 @@
+//! Graph-aware multi-node execution: runs every node of a `Project`'s `Node`/`Edge` DAG,
+//! in dependency order, concurrently up to a configurable limit -- instead of the
+//! single-node execution the rest of this module otherwise only supports.
+//!
+//! Nodes are scheduled with a classic topological-sort ready-queue: an in-degree map
+//! built from `edges`, seeded with in-degree-0 nodes, decremented as each upstream
+//! finishes. Each edge's `source_deliverable` is threaded into the downstream node's
+//! `context` before it runs, so a node can see what its dependencies produced. A node
+//! that errors, or that comes back with an unresolved `Check::HumanApproval`, blocks
+//! every node reachable from it instead of letting them run against an incomplete
+//! upstream.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use tokio::task::JoinHandle;
+
+use crate::commands::projects::{Edge, Node, Project};
+use crate::sessions::agent_state::{self, AgentState};
+use crate::sessions::checks::{self, Check, CheckContext};
+use crate::state::AppState;
+
+use super::{
+    execute, ExecuteRequest, ExecutionRegistry, ExecutionResult, ExecutorError, ExecutorResult,
+};
+
+/// How many nodes `run_project` will have in flight at once.
+pub const DEFAULT_PARALLELISM: usize = 4;
+
+/// Run every node of the project `project_id`, respecting `edges` dependencies, up to
+/// `parallelism` nodes running at once. Returns the final `ExecutionResult` for every
+/// node that actually ran, except a node left `blocked` by its own unresolved
+/// human-approval check (nodes left `blocked` by an upstream failure never appear either).
+pub async fn run_project(
+    app_state: &AppState,
+    registry: &ExecutionRegistry,
+    project_id: &str,
+    parallelism: usize,
+) -> ExecutorResult<HashMap<String, ExecutionResult>> {
+    let parallelism = parallelism.max(1);
+
+    let (nodes, edges, project_path) = {
+        let projects = app_state.projects.read().await;
+        let project: &Project = projects.get(project_id).ok_or_else(|| {
+            ExecutorError::Process(format!("project {project_id} not found"))
+        })?;
+        (project.nodes.clone(), project.edges.clone(), project.location.clone())
+    };
+
+    let node_by_id: HashMap<String, Node> =
+        nodes.into_iter().map(|n| (n.id.clone(), n)).collect();
+
+    let mut in_degree: HashMap<String, usize> =
+        node_by_id.keys().map(|id| (id.clone(), 0)).collect();
+    let mut successors: HashMap<String, Vec<Edge>> = HashMap::new();
+    for edge in &edges {
+        *in_degree.entry(edge.target_id.clone()).or_insert(0) += 1;
+        successors
+            .entry(edge.source_id.clone())
+            .or_default()
+            .push(edge.clone());
+    }
+
+    let mut ready: VecDeque<String> = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| id.clone())
+        .collect();
+
+    let mut outputs: HashMap<String, String> = HashMap::new();
+    let mut results: HashMap<String, ExecutionResult> = HashMap::new();
+    let mut blocked: HashSet<String> = HashSet::new();
+    let mut running: HashMap<String, JoinHandle<ExecutorResult<ExecutionResult>>> = HashMap::new();
+
+    loop {
+        while running.len() < parallelism {
+            let Some(node_id) = next_ready(&mut ready, &blocked) else {
+                break;
+            };
+            let Some(node) = node_by_id.get(&node_id) else {
+                continue;
+            };
+
+            agent_state::report(&app_state.state_tx, project_id, &node_id, AgentState::Starting, None);
+            agent_state::report(&app_state.state_tx, project_id, &node_id, AgentState::Running, None);
+
+            let incoming = successors
+                .iter()
+                .flat_map(|(_, edges)| edges.iter())
+                .filter(|e| e.target_id == node_id);
+            let context = node_context_with_deliverables(node, incoming, &outputs);
+            let request =
+                build_execute_request(node, &project_path, context, project_id, app_state);
+
+            let registry = registry.clone();
+            let handle =
+                tokio::spawn(async move { execute(request, &registry, |_chunk| {}).await });
+            running.insert(node_id, handle);
+        }
+
+        if running.is_empty() {
+            break;
+        }
+
+        let Some((node_id, outcome)) = pop_completed(&mut running).await else {
+            break;
+        };
+
+        match outcome {
+            Ok(ExecutionResult::Done { output }) => {
+                let unresolved = match node_by_id.get(&node_id) {
+                    Some(node) => {
+                        has_unresolved_human_approval(node, &output, &project_path).await
+                    }
+                    None => false,
+                };
+                if unresolved {
+                    agent_state::report(&app_state.state_tx, project_id, &node_id, AgentState::Blocked, None);
+                    block_downstream(&node_id, &successors, &mut blocked, &mut ready, app_state, project_id)
+                        .await;
+                } else {
+                    outputs.insert(node_id.clone(), output.clone());
+                    agent_state::report(&app_state.state_tx, project_id, &node_id, AgentState::Done, None);
+                    results.insert(node_id.clone(), ExecutionResult::Done { output });
+                    advance_successors(&node_id, &successors, &mut in_degree, &blocked, &mut ready);
+                }
+            }
+            Ok(ExecutionResult::Error { message }) => {
+                agent_state::report(&app_state.state_tx, project_id, &node_id, AgentState::Failed, None);
+                results.insert(node_id.clone(), ExecutionResult::Error { message });
+                block_downstream(&node_id, &successors, &mut blocked, &mut ready, app_state, project_id)
+                    .await;
+            }
+            Ok(running_result @ ExecutionResult::Running { .. }) => {
+                // A detached/interactive session has no deliverable yet to hand
+                // downstream nodes; leave this node's own status as `running` and
+                // block everything past it rather than guessing at an output.
+                tracing::warn!(
+                    "Node {} returned a detached session from the graph scheduler; blocking its successors",
+                    node_id
+                );
+                results.insert(node_id.clone(), running_result);
+                block_downstream(&node_id, &successors, &mut blocked, &mut ready, app_state, project_id)
+                    .await;
+            }
+            Err(e) => {
+                agent_state::report(&app_state.state_tx, project_id, &node_id, AgentState::Failed, None);
+                results.insert(
+                    node_id.clone(),
+                    ExecutionResult::Error {
+                        message: e.to_string(),
+                    },
+                );
+                block_downstream(&node_id, &successors, &mut blocked, &mut ready, app_state, project_id)
+                    .await;
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Pop the next ready node id that hasn't since been `blocked` by an upstream failure.
+fn next_ready(ready: &mut VecDeque<String>, blocked: &HashSet<String>) -> Option<String> {
+    while let Some(node_id) = ready.pop_front() {
+        if !blocked.contains(&node_id) {
+            return Some(node_id);
+        }
+    }
+    None
+}
+
+/// Wait for any in-flight node to finish without blocking on the others, so the
+/// scheduler can top the ready queue back up to `parallelism` as soon as a slot frees.
+/// Returns `None` once `running` is empty.
+async fn pop_completed(
+    running: &mut HashMap<String, JoinHandle<ExecutorResult<ExecutionResult>>>,
+) -> Option<(String, ExecutorResult<ExecutionResult>)> {
+    loop {
+        if running.is_empty() {
+            return None;
+        }
+        let finished = running
+            .iter()
+            .find(|(_, handle)| handle.is_finished())
+            .map(|(node_id, _)| node_id.clone());
+
+        let Some(node_id) = finished else {
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            continue;
+        };
+
+        let handle = running.remove(&node_id).expect("just found in map");
+        let outcome = handle
+            .await
+            .unwrap_or_else(|e| Err(ExecutorError::Process(format!("join error: {e}"))));
+        return Some((node_id, outcome));
+    }
+}
+
+/// Build the `context` a node runs with: its own stored `context` plus one entry per
+/// finished incoming edge, carrying that edge's `source_deliverable` label and the
+/// upstream node's output.
+fn node_context_with_deliverables<'a>(
+    node: &Node,
+    incoming: impl Iterator<Item = &'a Edge>,
+    outputs: &HashMap<String, String>,
+) -> Vec<serde_json::Value> {
+    let mut context = node.context.clone();
+    for edge in incoming {
+        let Some(output) = outputs.get(&edge.source_id) else {
+            continue;
+        };
+        context.push(serde_json::json!({
+            "fromNode": edge.source_id,
+            "deliverable": edge.source_deliverable,
+            "content": output,
+        }));
+    }
+    context
+}
+
+/// Render a node's prompt plus any deliverables passed in through `context`.
+fn build_prompt(node: &Node, context: &[serde_json::Value]) -> String {
+    if context.is_empty() {
+        return node.prompt.clone();
+    }
+
+    let sections: Vec<String> = context
+        .iter()
+        .map(|value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => serde_json::to_string_pretty(other).unwrap_or_default(),
+        })
+        .collect();
+
+    format!("{}\n\n{}", sections.join("\n\n"), node.prompt)
+}
+
+fn build_execute_request(
+    node: &Node,
+    project_path: &Option<String>,
+    context: Vec<serde_json::Value>,
+    project_id: &str,
+    app_state: &AppState,
+) -> ExecuteRequest {
+    let options = node
+        .agent
+        .model
+        .as_ref()
+        .map(|model| serde_json::json!({ "model": model }));
+
+    ExecuteRequest {
+        node_id: node.id.clone(),
+        executor: node.agent.r#type.clone(),
+        prompt: build_prompt(node, &context),
+        options,
+        project_path: project_path.clone(),
+        execution_config: None,
+        pty: false,
+        pty_size: None,
+        cancellation: Default::default(),
+        project_id: Some(project_id.to_string()),
+        state_tx: Some(app_state.state_tx.clone()),
+        remote_connections: Some(app_state.remote_connections.clone()),
+        remote_pty_inputs: Some(app_state.remote_pty_inputs.clone()),
+    }
+}
+
+/// Whether `node` has at least one `Check::HumanApproval`, and running it comes back
+/// un-passed -- i.e. nobody has approved it yet. `Check::HumanApproval` can't be
+/// auto-passed (see `sessions::checks::run_single_check_once`), so today this is
+/// equivalent to "does this node have a human-approval check at all", but it's written
+/// against the actual check result so it keeps working once approvals can be resolved.
+/// Runs on a blocking-task pool, same as `sessions::monitor`'s post-completion checks,
+/// since checks shell out and otherwise block the async runtime.
+async fn has_unresolved_human_approval(
+    node: &Node,
+    output: &str,
+    cwd: &Option<String>,
+) -> bool {
+    let checks: Vec<Check> = node
+        .checks
+        .iter()
+        .filter_map(|v| serde_json::from_value(v.clone()).ok())
+        .collect();
+
+    if checks.is_empty() {
+        return false;
+    }
+
+    let ctx = CheckContext {
+        cwd: cwd.clone(),
+        output: output.to_string(),
+        exit_code: 0,
+        session_id: node.id.clone(),
+        node_id: node.id.clone(),
+    };
+
+    tokio::task::spawn_blocking(move || {
+        checks::run_checks(&checks, &ctx)
+            .into_iter()
+            .any(|r| r.check_type == "human_approval" && !r.passed)
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Decrement in-degree for `node_id`'s successors and enqueue any that just reached
+/// zero incoming edges (and aren't already `blocked`).
+fn advance_successors(
+    node_id: &str,
+    successors: &HashMap<String, Vec<Edge>>,
+    in_degree: &mut HashMap<String, usize>,
+    blocked: &HashSet<String>,
+    ready: &mut VecDeque<String>,
+) {
+    let Some(edges) = successors.get(node_id) else {
+        return;
+    };
+    for edge in edges {
+        if blocked.contains(&edge.target_id) {
+            continue;
+        }
+        if let Some(degree) = in_degree.get_mut(&edge.target_id) {
+            *degree = degree.saturating_sub(1);
+            if *degree == 0 {
+                ready.push_back(edge.target_id.clone());
+            }
+        }
+    }
+}
+
+/// Mark every node reachable from `node_id` (its direct and transitive successors) as
+/// `blocked`, reporting the transition through `AppState::state_tx` and dropping any of
+/// them already sitting in the ready queue.
+async fn block_downstream(
+    node_id: &str,
+    successors: &HashMap<String, Vec<Edge>>,
+    blocked: &mut HashSet<String>,
+    ready: &mut VecDeque<String>,
+    app_state: &AppState,
+    project_id: &str,
+) {
+    let mut stack = vec![node_id.to_string()];
+    let mut newly_blocked = Vec::new();
+
+    while let Some(id) = stack.pop() {
+        let Some(edges) = successors.get(&id) else {
+            continue;
+        };
+        for edge in edges {
+            if blocked.insert(edge.target_id.clone()) {
+                newly_blocked.push(edge.target_id.clone());
+                stack.push(edge.target_id.clone());
+            }
+        }
+    }
+
+    ready.retain(|id| !blocked.contains(id));
+
+    for id in newly_blocked {
+        agent_state::report(&app_state.state_tx, project_id, &id, AgentState::Blocked, None);
+    }
+}
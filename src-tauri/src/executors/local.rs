@@ -1,6 +1,9 @@
 //! Local executor - runs agents directly via process spawning
 
-use super::{ExecuteRequest, ExecutionResult, ExecutorError, ExecutorResult};
+use super::{ExecuteRequest, ExecutionRegistry, ExecutionResult, ExecutorError, ExecutorResult};
+use crate::commands::projects::StopSignal;
+use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use std::io::Read;
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
@@ -9,25 +12,35 @@ use tokio::time::{timeout, Duration};
 /// Execution timeout (5 minutes)
 const EXECUTION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
-/// Allowed executor types
-const ALLOWED_EXECUTORS: [&str; 3] = ["claude", "codex", "gemini"];
+/// Grace period between SIGTERM and SIGKILL when a run is cancelled or times out, long
+/// enough for the agent to flush output and clean up temp state.
+const STOP_GRACE: Duration = Duration::from_secs(2);
+
+/// Default PTY window size for `ExecuteRequest::pty` mode: generous enough that most
+/// CLIs' progress bars/tables don't wrap, but small enough to keep captured output sane.
+const DEFAULT_PTY_ROWS: u16 = 40;
+const DEFAULT_PTY_COLS: u16 = 120;
 
 /// Execute an agent command locally
-pub async fn execute_local<F>(request: &ExecuteRequest, on_output: F) -> ExecutorResult<ExecutionResult>
+pub async fn execute_local<F>(
+    request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
 where
     F: Fn(String) + Send + 'static,
 {
     // Validate executor
-    if !ALLOWED_EXECUTORS.contains(&request.executor.as_str()) {
+    if !crate::agent_command::is_allowed_executor(&request.executor) {
         return Err(ExecutorError::InvalidExecutor(format!(
             "Invalid executor: {}. Allowed: {}",
             request.executor,
-            ALLOWED_EXECUTORS.join(", ")
+            crate::agent_command::ALLOWED_EXECUTORS.join(", ")
         )));
     }
 
     // Build command arguments
-    let args = build_command_args(&request.executor, &request.prompt, &request.options);
+    let args = crate::agent_command::one_shot_argv(&request.executor, &request.prompt, &request.options);
 
     tracing::info!(
         "Executing locally: {} {}",
@@ -40,15 +53,30 @@ where
         ExecutorError::Process(format!("Executable '{}' not found: {}", args[0], e))
     })?;
 
+    if request.pty {
+        return execute_local_pty(request, registry, &executable, &args, on_output).await;
+    }
+
     // Spawn the process
-    let mut child = Command::new(executable)
+    let mut command = Command::new(executable);
+    command
         .args(&args[1..])
         .current_dir(request.project_path.as_deref().unwrap_or("."))
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| ExecutorError::Io(e))?;
+        .stderr(Stdio::piped());
+
+    // Make the child its own process group leader, so a signal sent to `-pid` reaches
+    // any subprocesses it spawns of its own, not just the direct child.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command.spawn().map_err(ExecutorError::Io)?;
+
+    let _registry_guard = super::track_child(registry, &request.node_id, &child);
 
     // Stream output
     let stdout = child.stdout.take().unwrap();
@@ -58,8 +86,9 @@ where
     let mut stderr_reader = BufReader::new(stderr).lines();
 
     let mut output = String::new();
+    let mut cancelled = false;
 
-    // Read output with timeout
+    // Read output with timeout, folding in cooperative cancellation
     let result = timeout(EXECUTION_TIMEOUT, async {
         loop {
             tokio::select! {
@@ -90,15 +119,28 @@ where
                         }
                     }
                 }
+                _ = request.cancellation.cancelled() => {
+                    cancelled = true;
+                    break;
+                }
             }
         }
 
-        child.wait().await
+        if cancelled {
+            None
+        } else {
+            Some(child.wait().await)
+        }
     })
     .await;
 
+    if cancelled {
+        let _ = super::stop_child(&mut child, StopSignal::Sigterm, STOP_GRACE).await;
+        return Err(ExecutorError::Cancelled);
+    }
+
     match result {
-        Ok(Ok(status)) => {
+        Ok(Some(Ok(status))) => {
             if status.success() {
                 Ok(ExecutionResult::Done { output })
             } else {
@@ -107,103 +149,151 @@ where
                 })
             }
         }
-        Ok(Err(e)) => Err(ExecutorError::Io(e)),
+        Ok(Some(Err(e))) => Err(ExecutorError::Io(e)),
+        Ok(None) => unreachable!("cancelled is false here, so the loop always resolves to Some"),
         Err(_) => {
-            // Timeout - kill the process
-            let _ = child.kill().await;
+            // Timeout - escalate from SIGTERM to SIGKILL rather than killing outright, so
+            // the agent gets the same chance to flush output as a user-initiated cancel.
+            let _ = super::stop_child(&mut child, StopSignal::Sigterm, STOP_GRACE).await;
             Err(ExecutorError::Timeout)
         }
     }
 }
 
-/// Build command arguments for the specified executor
-fn build_command_args(
-    executor: &str,
-    prompt: &str,
-    options: &Option<serde_json::Value>,
-) -> Vec<String> {
-    match executor {
-        "claude" => {
-            let mut args = vec![
-                "claude".to_string(),
-                "-p".to_string(),
-                prompt.to_string(),
-                "--output-format".to_string(),
-                "text".to_string(),
-                "--no-session-persistence".to_string(),
-                "--permission-mode".to_string(),
-                "dontAsk".to_string(),
-                "--tools".to_string(),
-                "".to_string(),
-            ];
-
-            if let Some(opts) = options {
-                if let Some(model) = opts.get("model").and_then(|v| v.as_str()) {
-                    args.push("--model".to_string());
-                    args.push(model.to_string());
-                }
-                if let Some(budget) = opts.get("thinkingBudget").and_then(|v| v.as_i64()) {
-                    args.push("--append-system-prompt".to_string());
-                    args.push(format!("Think for at most {} tokens.", budget));
+/// Execute an agent command locally inside a PTY, so CLIs that detect a non-interactive
+/// stdout (and disable color/progress/spinners) behave as they do when run by hand.
+/// Mirrors `execute_local`'s timeout/kill/Done-or-Error contract; see `sessions::pty` for
+/// the same `portable-pty` idioms used for interactive sessions.
+async fn execute_local_pty<F>(
+    request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
+    executable: &std::path::Path,
+    args: &[String],
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
+where
+    F: Fn(String) + Send + 'static,
+{
+    let (rows, cols) = request
+        .pty_size
+        .map(|s| (s.rows, s.cols))
+        .unwrap_or((DEFAULT_PTY_ROWS, DEFAULT_PTY_COLS));
+
+    let pty_system = native_pty_system();
+    let pair = pty_system
+        .openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| ExecutorError::Process(format!("Failed to open PTY: {e}")))?;
+
+    let mut cmd = CommandBuilder::new(executable);
+    for arg in &args[1..] {
+        cmd.arg(arg);
+    }
+    if let Some(dir) = request.project_path.as_deref() {
+        cmd.cwd(dir);
+    }
+
+    let mut child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| ExecutorError::Process(format!("Failed to spawn in PTY: {e}")))?;
+    // Drop our copy of the slave fd: the child holds the only remaining one, so the
+    // reader thread below sees EOF once the child exits instead of blocking forever.
+    drop(pair.slave);
+
+    let pid = child.process_id().map(|p| p as i32);
+    let _registry_guard = pid.map(|p| super::track_pid(registry, &request.node_id, p));
+
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| ExecutorError::Process(format!("Failed to clone PTY reader: {e}")))?;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => {
+                    buf.extend_from_slice(&chunk[..n]);
+                    while let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+                        let line: Vec<u8> = buf.drain(..=pos).collect();
+                        let line = String::from_utf8_lossy(&line)
+                            .trim_end_matches(['\n', '\r'])
+                            .to_string();
+                        if tx.send(line).is_err() {
+                            return;
+                        }
+                    }
                 }
+                Err(_) => break,
             }
-
-            args
         }
+        if !buf.is_empty() {
+            let _ = tx.send(String::from_utf8_lossy(&buf).to_string());
+        }
+    });
 
-        "codex" => {
-            let mut args = vec![
-                "codex".to_string(),
-                "exec".to_string(),
-                "--skip-git-repo-check".to_string(),
-            ];
-
-            if let Some(opts) = options {
-                let reasoning = opts
-                    .get("reasoningEffort")
-                    .or_else(|| opts.get("reasoningLevel"))
-                    .and_then(|v| v.as_str());
-
-                if let Some(level) = reasoning {
-                    if ["low", "medium", "high", "xhigh"].contains(&level) {
-                        args.push("-c".to_string());
-                        args.push(format!("reasoning.effort={}", level));
+    let mut output = String::new();
+    let mut poll = tokio::time::interval(Duration::from_millis(100));
+
+    let result = timeout(EXECUTION_TIMEOUT, async {
+        loop {
+            tokio::select! {
+                line = rx.recv() => {
+                    match line {
+                        Some(line) => {
+                            output.push_str(&line);
+                            output.push('\n');
+                            on_output(format!("{}\n", line));
+                        }
+                        None => break,
                     }
                 }
-
-                if let Some(model) = opts.get("model").and_then(|v| v.as_str()) {
-                    args.push("-m".to_string());
-                    args.push(model.to_string());
+                _ = poll.tick() => {
+                    if matches!(child.try_wait(), Ok(Some(_))) {
+                        break;
+                    }
                 }
             }
-
-            args.push(prompt.to_string());
-            args
         }
 
-        "gemini" => {
-            let model = options
-                .as_ref()
-                .and_then(|o| o.get("model"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("gemini-3-pro-preview");
-
-            // Sanitize model name
-            let model: String = model
-                .chars()
-                .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '.')
-                .collect();
-
-            vec![
-                "gemini".to_string(),
-                prompt.to_string(),
-                "-m".to_string(),
-                model,
-                "-o".to_string(),
-                "text".to_string(),
-            ]
+        // Drain whatever the reader thread already queued before the child exited.
+        while let Ok(line) = rx.try_recv() {
+            output.push_str(&line);
+            output.push('\n');
+            on_output(format!("{}\n", line));
         }
 
-        _ => vec![],
+        tokio::task::spawn_blocking(move || child.wait()).await
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Ok(status))) => {
+            if status.success() {
+                Ok(ExecutionResult::Done { output })
+            } else {
+                Ok(ExecutionResult::Error {
+                    message: format!("Process exited with code {}", status.exit_code()),
+                })
+            }
+        }
+        Ok(Ok(Err(e))) => Err(ExecutorError::Process(format!("PTY wait failed: {e}"))),
+        Ok(Err(e)) => Err(ExecutorError::Process(format!("PTY wait task failed: {e}"))),
+        Err(_) => {
+            // Timeout - kill the process (and its group, in case the agent spawned
+            // children of its own).
+            if let Some(pid) = pid {
+                super::send_signal_to_group(pid, crate::commands::projects::StopSignal::Sigterm);
+            }
+            Err(ExecutorError::Timeout)
+        }
     }
 }
@@ -4,18 +4,30 @@
 //! - local: Direct process spawning
 //! - docker: Isolated container execution
 //! - docker-interactive: Container with tmux for attach/detach
-//! - remote: SSH + Docker on remote VM
+//! - bwrap: Rootless bubblewrap jail (lighter/faster than Docker, Linux only)
+//! - remote: SSH + Docker on remote VM, or a detached remote tmux session (interactive)
 //! - modal: Modal serverless execution
 
+mod bwrap;
 mod docker;
+pub mod graph;
 mod local;
 mod modal;
 mod remote;
+pub mod remote_connection;
 
-use crate::commands::projects::ExecutionConfig;
+use crate::commands::projects::{ExecutionConfig, OnBusyUpdate, StopSignal};
 use crate::db::Session;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
+use tokio::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Grace period before a graceful stop escalates to SIGKILL, when the node's
+/// `ExecutionConfig` doesn't specify `stop_timeout`.
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Error, Debug)]
 pub enum ExecutorError {
@@ -33,19 +45,136 @@ pub enum ExecutorError {
     Modal(String),
     #[error("Timeout")]
     Timeout,
+    #[error("Stalled: no output for {idle_secs}s")]
+    Stalled { idle_secs: u64 },
+    #[error("Node {0} is already running (on-busy-update policy is doNothing)")]
+    Busy(String),
+    #[error("Execution cancelled")]
+    Cancelled,
 }
 
 pub type ExecutorResult<T> = Result<T, ExecutorError>;
 
+/// Shared contract for execution backends: given a validated request, run the agent and
+/// stream its output through `on_output`. `local::execute_local` and
+/// `remote::execute_remote_direct` already share this exact shape as free functions;
+/// `LocalExecutor`/`RemoteExecutor` just let `execute()` depend on the contract itself
+/// rather than on a specific backend's function.
+pub trait Executor {
+    async fn execute<F>(
+        &self,
+        request: &ExecuteRequest,
+        registry: &ExecutionRegistry,
+        on_output: F,
+    ) -> ExecutorResult<ExecutionResult>
+    where
+        F: Fn(String) + Send + 'static;
+}
+
+pub struct LocalExecutor;
+
+impl Executor for LocalExecutor {
+    async fn execute<F>(
+        &self,
+        request: &ExecuteRequest,
+        registry: &ExecutionRegistry,
+        on_output: F,
+    ) -> ExecutorResult<ExecutionResult>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        local::execute_local(request, registry, on_output).await
+    }
+}
+
+pub struct RemoteExecutor;
+
+impl Executor for RemoteExecutor {
+    async fn execute<F>(
+        &self,
+        request: &ExecuteRequest,
+        registry: &ExecutionRegistry,
+        on_output: F,
+    ) -> ExecutorResult<ExecutionResult>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        remote::execute_remote_direct(request, registry, on_output).await
+    }
+}
+
+pub struct DockerExecutor;
+
+impl Executor for DockerExecutor {
+    async fn execute<F>(
+        &self,
+        request: &ExecuteRequest,
+        registry: &ExecutionRegistry,
+        on_output: F,
+    ) -> ExecutorResult<ExecutionResult>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        docker::execute_docker(request, registry, on_output).await
+    }
+}
+
 /// Request to execute an agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecuteRequest {
+    /// Identifies the node being run, for `ExecutionRegistry`/on-busy-update bookkeeping.
+    pub node_id: String,
     pub executor: String,
     pub prompt: String,
     pub options: Option<serde_json::Value>,
     pub project_path: Option<String>,
     pub execution_config: Option<ExecutionConfig>,
+    /// Run the local executor's agent process inside a pseudo-terminal instead of with
+    /// piped stdio, so CLIs that detect non-interactive stdout (and disable
+    /// color/progress/spinners) behave the way they do when run by hand. Only consulted
+    /// by `local::execute_local`; other backends already run interactively via tmux/PTY.
+    #[serde(default)]
+    pub pty: bool,
+    /// PTY window size when `pty` is set; defaults to 40 rows x 120 cols.
+    #[serde(default)]
+    pub pty_size: Option<PtySizeConfig>,
+    /// Lets a caller cancel a run already in flight (user-initiated stop, as opposed to
+    /// `EXECUTION_TIMEOUT` firing). Never comes from the wire -- a fresh, uncancelled
+    /// token deserializes in, and the caller clones the one it kept before calling
+    /// `execute`/`execute_local` in order to have something to cancel later.
+    #[serde(skip, default)]
+    pub cancellation: CancellationToken,
+    /// Project this node belongs to, only needed to report `ExecutionConfig::retry`
+    /// attempts through `state_tx`; `None` skips reporting instead of erroring, since not
+    /// every caller of `execute()` runs as part of a project graph.
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Where to report `AgentState::Retrying` transitions as `execute()`'s retry loop
+    /// runs. Never comes from the wire, same as `cancellation`.
+    #[serde(skip, default)]
+    pub state_tx: Option<crate::sessions::agent_state::StateReporter>,
+    /// Shared registry of persistent SSH ControlMaster connections, consulted by
+    /// `remote::execute_remote`/`execute_remote_direct`/`execute_remote_interactive` so
+    /// repeated runs against the same host reuse one multiplexed connection instead of
+    /// paying a fresh handshake each time. `None` (e.g. a caller outside `AppState`'s
+    /// reach) falls back to a plain per-call SSH connection, same as before this existed.
+    #[serde(skip, default)]
+    pub remote_connections: Option<remote_connection::RemoteConnectionManager>,
+    /// Registry `remote::execute_remote_pty` registers its session's stdin input channel
+    /// into, keyed by the session id it generates; lets `send_remote_pty_input` forward
+    /// keystrokes after the execution itself already returned `Running`. `None` (e.g. a
+    /// caller outside `AppState`'s reach) means that backend can't accept input.
+    #[serde(skip, default)]
+    pub remote_pty_inputs: Option<RemotePtyInputRegistry>,
+}
+
+/// Requested PTY window size for `ExecuteRequest::pty` mode.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtySizeConfig {
+    pub rows: u16,
+    pub cols: u16,
 }
 
 /// Result of an execution
@@ -58,13 +187,222 @@ pub enum ExecutionResult {
     Running {
         session_id: String,
         attach_command: Option<String>,
+        /// Backend-specific job id for execution that outlives this call (e.g. a detached
+        /// Modal app id), so a later call can follow/reattach to it. `None` for backends
+        /// where `session_id` already serves that purpose (tmux, Docker interactive).
+        #[serde(default)]
+        call_id: Option<String>,
     },
     #[serde(rename = "error")]
     Error { message: String },
 }
 
-/// Execute an agent command using the appropriate backend
-pub async fn execute<F>(request: ExecuteRequest, on_output: F) -> ExecutorResult<ExecutionResult>
+/// Tracks, per node id, the pid of the backend process currently running that node's
+/// agent (if any). `execute()` consults this to apply `OnBusyUpdate` before spawning,
+/// and each backend registers/deregisters its child's pid around its own run.
+#[derive(Clone, Default)]
+pub struct ExecutionRegistry {
+    running: Arc<Mutex<HashMap<String, i32>>>,
+}
+
+impl ExecutionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pid of the process currently registered for `node_id`, if any.
+    pub fn running_pid(&self, node_id: &str) -> Option<i32> {
+        self.running.lock().unwrap().get(node_id).copied()
+    }
+
+    /// Register `pid` as the running process for `node_id`. The returned guard
+    /// deregisters it on drop, so backends can hold it for the lifetime of the spawn.
+    fn register(&self, node_id: &str, pid: i32) -> RegistryGuard {
+        self.running.lock().unwrap().insert(node_id.to_string(), pid);
+        RegistryGuard {
+            registry: self.clone(),
+            node_id: node_id.to_string(),
+        }
+    }
+
+    /// Poll until no process is registered for `node_id`, for `OnBusyUpdate::Queue`.
+    async fn wait_until_free(&self, node_id: &str) {
+        while self.running_pid(node_id).is_some() {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+}
+
+/// Registry of live remote-PTY session input channels, keyed by the session id
+/// `remote::execute_remote_pty` hands back as `ExecutionResult::Running::session_id`. Lets
+/// a caller outside the execution itself (the `send_remote_pty_input` Tauri command)
+/// forward keystrokes into a session's stdin after `execute()` has already returned.
+#[derive(Clone, Default)]
+pub struct RemotePtyInputRegistry {
+    senders: Arc<Mutex<HashMap<String, tokio::sync::mpsc::UnboundedSender<String>>>>,
+}
+
+impl RemotePtyInputRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&self, session_id: &str, sender: tokio::sync::mpsc::UnboundedSender<String>) {
+        self.senders.lock().unwrap().insert(session_id.to_string(), sender);
+    }
+
+    fn unregister(&self, session_id: &str) {
+        self.senders.lock().unwrap().remove(session_id);
+    }
+
+    /// Forward `input` into `session_id`'s PTY stdin, if it's still registered (i.e. the
+    /// session hasn't already exited).
+    pub fn send(&self, session_id: &str, input: &str) -> Result<(), String> {
+        self.senders
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .ok_or_else(|| format!("no remote PTY session {session_id}"))?
+            .send(input.to_string())
+            .map_err(|_| format!("remote PTY session {session_id} is no longer accepting input"))
+    }
+}
+
+/// Deregisters its node's pid from the `ExecutionRegistry` when a backend's spawn
+/// function returns, on any code path.
+struct RegistryGuard {
+    registry: ExecutionRegistry,
+    node_id: String,
+}
+
+impl Drop for RegistryGuard {
+    fn drop(&mut self) {
+        self.registry.running.lock().unwrap().remove(&self.node_id);
+    }
+}
+
+/// Register `child`'s pid for `node_id` if it's still alive, for the duration of the
+/// returned guard. Called by each backend right after spawning.
+fn track_child(registry: &ExecutionRegistry, node_id: &str, child: &tokio::process::Child) -> Option<RegistryGuard> {
+    child.id().map(|pid| registry.register(node_id, pid as i32))
+}
+
+/// Like `track_child`, for backends whose child only exposes a raw pid (e.g. a
+/// PTY-spawned `portable_pty::Child`, which doesn't hand back a `tokio::process::Child`).
+fn track_pid(registry: &ExecutionRegistry, node_id: &str, pid: i32) -> RegistryGuard {
+    registry.register(node_id, pid)
+}
+
+/// Whether `error` is worth retrying under `ExecutionConfig::retry`: a process that
+/// misbehaved, an I/O hiccup talking to it, or it simply taking too long. Everything
+/// else (a bad config, the node already busy, a user-requested cancellation) is retrying
+/// the exact same mistake, so it settles into `Failed` on the first attempt instead.
+fn is_transient(error: &ExecutorError) -> bool {
+    matches!(
+        error,
+        ExecutorError::Process(_) | ExecutorError::Io(_) | ExecutorError::Timeout
+    )
+}
+
+/// Execute an agent command using the appropriate backend, first applying the node's
+/// `OnBusyUpdate` policy if a previous run for the same node is still registered.
+pub async fn execute<F>(
+    request: ExecuteRequest,
+    registry: &ExecutionRegistry,
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
+where
+    F: Fn(String) + Send + Clone + 'static,
+{
+    if let Some(existing_pid) = registry.running_pid(&request.node_id) {
+        let policy = request
+            .execution_config
+            .as_ref()
+            .and_then(|c| c.on_busy_update)
+            .unwrap_or_default();
+
+        match policy {
+            OnBusyUpdate::DoNothing => return Err(ExecutorError::Busy(request.node_id.clone())),
+            OnBusyUpdate::Signal => {
+                let (signal, _grace) = stop_policy(request.execution_config.as_ref());
+                send_signal_to_group(existing_pid, signal);
+                return Ok(ExecutionResult::Running {
+                    session_id: request.node_id.clone(),
+                    attach_command: None,
+                    call_id: None,
+                });
+            }
+            OnBusyUpdate::Restart => {
+                let (signal, grace) = stop_policy(request.execution_config.as_ref());
+                stop_pid(existing_pid, signal, grace).await?;
+            }
+            OnBusyUpdate::Queue => {
+                registry.wait_until_free(&request.node_id).await;
+            }
+        }
+    }
+
+    let retry = request.execution_config.as_ref().and_then(|c| c.retry);
+    let max_attempts = retry.map(|r| r.max_attempts.max(1)).unwrap_or(1);
+    let base_delay_ms = retry.map(|r| r.base_delay_ms).unwrap_or(0);
+
+    let mut attempt = 1;
+    loop {
+        let result = dispatch_backend(&request, registry, on_output.clone()).await;
+
+        let error = match result {
+            Ok(outcome) => return Ok(outcome),
+            Err(e) => e,
+        };
+
+        if attempt >= max_attempts || !is_transient(&error) {
+            return Err(error);
+        }
+
+        let delay_ms = base_delay_ms.saturating_mul(1u64 << (attempt - 1));
+        tracing::warn!(
+            "Node {} execution attempt {}/{} failed transiently, retrying in {}ms: {}",
+            request.node_id,
+            attempt,
+            max_attempts,
+            delay_ms,
+            error
+        );
+        if let (Some(state_tx), Some(project_id)) = (&request.state_tx, &request.project_id) {
+            crate::sessions::agent_state::report(
+                state_tx,
+                project_id,
+                &request.node_id,
+                crate::sessions::agent_state::AgentState::Retrying,
+                Some(crate::sessions::agent_state::RetryInfo { attempt, delay_ms }),
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        attempt += 1;
+        if let (Some(state_tx), Some(project_id)) = (&request.state_tx, &request.project_id) {
+            // Back to `Running` for the new attempt -- `Retrying` was only ever meant to
+            // cover the backoff wait itself.
+            crate::sessions::agent_state::report(
+                state_tx,
+                project_id,
+                &request.node_id,
+                crate::sessions::agent_state::AgentState::Running,
+                None,
+            );
+        }
+    }
+}
+
+/// Run `request` once against whichever backend its `ExecutionConfig` selects (local, by
+/// default). Split out from `execute()` so the retry loop there can call this again on a
+/// transient failure without duplicating the backend dispatch. This is the backend
+/// registry: since `Executor::execute` is generic over `F` it isn't object-safe, so
+/// selecting an implementation is a match rather than a `Box<dyn Executor>` lookup.
+async fn dispatch_backend<F>(
+    request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
 where
     F: Fn(String) + Send + 'static,
 {
@@ -76,22 +414,166 @@ where
 
     match backend {
         Some(crate::commands::projects::ExecutionBackend::Docker) => {
-            docker::execute_docker(&request, on_output).await
+            DockerExecutor.execute(request, registry, on_output).await
         }
         Some(crate::commands::projects::ExecutionBackend::DockerInteractive) => {
-            docker::execute_docker_interactive(&request, on_output).await
+            docker::execute_docker_interactive(request, on_output).await
+        }
+        Some(crate::commands::projects::ExecutionBackend::Bwrap) => {
+            if which::which("bwrap").is_ok() {
+                bwrap::execute_bwrap(request, registry, on_output).await
+            } else {
+                let disable_fallback = request
+                    .execution_config
+                    .as_ref()
+                    .and_then(|c| c.sandbox.as_ref())
+                    .map(|s| s.disable_docker_fallback)
+                    .unwrap_or(false);
+
+                if disable_fallback {
+                    Err(ExecutorError::Process(
+                        "bwrap not found and Docker fallback is disabled".to_string(),
+                    ))
+                } else {
+                    tracing::warn!("bwrap not found on PATH; falling back to Docker backend");
+                    DockerExecutor.execute(request, registry, on_output).await
+                }
+            }
         }
         Some(crate::commands::projects::ExecutionBackend::Remote) => {
-            remote::execute_remote(&request, on_output).await
+            remote::execute_remote(request, registry, on_output).await
+        }
+        Some(crate::commands::projects::ExecutionBackend::RemoteInteractive) => {
+            remote::execute_remote_interactive(request, on_output).await
+        }
+        Some(crate::commands::projects::ExecutionBackend::RemoteDirect) => {
+            RemoteExecutor.execute(request, registry, on_output).await
+        }
+        Some(crate::commands::projects::ExecutionBackend::RemotePty) => {
+            remote::execute_remote_pty(request, registry, on_output).await
         }
         Some(crate::commands::projects::ExecutionBackend::Modal) => {
-            modal::execute_modal(&request, on_output).await
+            modal::execute_modal(request, registry, on_output).await
         }
         // Default to local execution
-        _ => local::execute_local(&request, on_output).await,
+        _ => LocalExecutor.execute(request, registry, on_output).await,
     }
 }
 
+/// Resolve the `(signal, grace)` pair a node's execution config asks for, falling back
+/// to `Sigterm` and `DEFAULT_STOP_TIMEOUT` when unset.
+pub fn stop_policy(execution_config: Option<&ExecutionConfig>) -> (StopSignal, Duration) {
+    let signal = execution_config
+        .and_then(|c| c.stop_signal)
+        .unwrap_or_default();
+    let grace = execution_config
+        .and_then(|c| c.stop_timeout)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_STOP_TIMEOUT);
+    (signal, grace)
+}
+
+/// Gracefully stop `child`: deliver `signal` to its process group (these are typically
+/// shell/tmux-launched agents with children of their own), wait up to `grace` for it to
+/// exit on its own, and only escalate to SIGKILL if it is still alive when the timer fires.
+pub async fn stop_child(
+    child: &mut tokio::process::Child,
+    signal: StopSignal,
+    grace: Duration,
+) -> std::io::Result<()> {
+    let Some(pid) = child.id() else {
+        // Already reaped; nothing left to signal.
+        return Ok(());
+    };
+
+    send_signal_to_group(pid as i32, signal);
+
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    if matches!(child.try_wait(), Ok(Some(_))) {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        "process {} ignored {:?}; escalating to SIGKILL after {:?} grace period",
+        pid,
+        signal,
+        grace
+    );
+    child.kill().await
+}
+
+/// Gracefully stop a process we only know the pid of (e.g. a tmux pane's process, for
+/// the interactive-session `OnBusyUpdate::Restart` path): deliver `signal`, wait up to
+/// `grace` for it to exit on its own, and escalate to SIGKILL if it's still alive.
+pub async fn stop_pid(pid: i32, signal: StopSignal, grace: Duration) -> std::io::Result<()> {
+    if !pid_alive(pid) {
+        return Ok(());
+    }
+
+    send_signal_to_group(pid, signal);
+
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        if !pid_alive(pid) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    if !pid_alive(pid) {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        "pid {} ignored {:?}; escalating to SIGKILL after {:?} grace period",
+        pid,
+        signal,
+        grace
+    );
+    #[cfg(unix)]
+    {
+        if unsafe { libc::kill(-pid, libc::SIGKILL) } != 0 {
+            unsafe { libc::kill(pid, libc::SIGKILL) };
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn pid_alive(pid: i32) -> bool {
+    unsafe { libc::kill(pid, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn pid_alive(_pid: i32) -> bool {
+    false
+}
+
+#[cfg(unix)]
+pub(crate) fn send_signal_to_group(pid: i32, signal: StopSignal) {
+    let raw = match signal {
+        StopSignal::Sigterm => libc::SIGTERM,
+        StopSignal::Sigint => libc::SIGINT,
+        StopSignal::Sighup => libc::SIGHUP,
+        StopSignal::Sigquit => libc::SIGQUIT,
+    };
+    // Negative pid targets the whole process group; fall back to the lone process if
+    // the child was never made its own group leader.
+    if unsafe { libc::kill(-pid, raw) } != 0 {
+        unsafe { libc::kill(pid, raw) };
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn send_signal_to_group(_pid: i32, _signal: StopSignal) {}
+
 /// Stop an execution based on session info
 pub async fn stop_execution(session: &Session) -> ExecutorResult<()> {
     match session.backend.as_deref() {
@@ -103,8 +585,11 @@ pub async fn stop_execution(session: &Session) -> ExecutorResult<()> {
             }
         }
         Some("remote") => {
-            // TODO: Implement remote stop
-            Ok(())
+            if let Some(container_id) = &session.container_id {
+                remote::stop_remote_session(container_id).await
+            } else {
+                Ok(())
+            }
         }
         Some("modal") => {
             // Modal jobs auto-cleanup
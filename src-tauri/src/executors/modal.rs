@@ -1,17 +1,116 @@
 //! Modal executor - runs agents on Modal serverless infrastructure
 
-use super::{ExecuteRequest, ExecutionResult, ExecutorError, ExecutorResult};
+use super::{ExecuteRequest, ExecutionRegistry, ExecutionResult, ExecutorError, ExecutorResult};
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
-use tokio::time::{timeout, Duration};
+use tokio::time::{sleep, timeout, Duration, Instant};
 
 /// Execution timeout (30 minutes for Modal - can be long-running)
 const EXECUTION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
 
+/// How long to actively follow a detached job's real status once the local `modal run
+/// --detach` CLI has exited, before giving up and handing back `call_id` (plus a
+/// `modal app logs --follow` attach command) in `ExecutionResult::Running`.
+const FOLLOW_BUDGET: Duration = Duration::from_secs(5 * 60);
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+enum ModalOutcome {
+    Done(std::process::ExitStatus),
+    Stalled { idle_secs: u64 },
+}
+
+/// Pull the `ap-...` app id out of a line of Modal CLI output. `modal run --detach`
+/// prints a "View run at https://modal.com/apps/<workspace>/main/ap-XXXXXXXX" line on
+/// submission; that id is what `modal app logs`/`modal app list` key on.
+fn extract_call_id(line: &str) -> Option<String> {
+    line.split(|c: char| c.is_whitespace() || c == '/')
+        .find(|tok| tok.starts_with("ap-") && tok.len() > "ap-".len())
+        .map(|tok| {
+            tok.trim_end_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+                .to_string()
+        })
+}
+
+enum ModalAppState {
+    Running,
+    Stopped,
+    Failed,
+}
+
+/// `modal app list --json`'s state for `call_id`, if it appears in the listing.
+fn parse_app_state(json: &str, call_id: &str) -> Option<ModalAppState> {
+    let apps: serde_json::Value = serde_json::from_str(json).ok()?;
+    let app = apps
+        .as_array()?
+        .iter()
+        .find(|a| a.get("App ID").and_then(|v| v.as_str()) == Some(call_id))?;
+
+    match app.get("State").and_then(|v| v.as_str())? {
+        "stopped" | "deployed" => Some(ModalAppState::Stopped),
+        "failed" | "error" => Some(ModalAppState::Failed),
+        _ => Some(ModalAppState::Running),
+    }
+}
+
+/// Resolve a detached Modal job's real terminal state. `modal run --detach` exiting only
+/// confirms the job was *submitted*; whether it actually finished (and how) has to come
+/// from asking Modal about the app itself. If `budget` runs out first the job is still
+/// live, so we hand back `call_id`/`attach_command` in `ExecutionResult::Running`.
+/// `execute_modal` takes no call id of its own: re-running the node submits a new job
+/// rather than resuming this follow.
+async fn follow_call<F>(
+    call_id: &str,
+    on_output: &F,
+    mut output: String,
+    budget: Duration,
+) -> ExecutorResult<ExecutionResult>
+where
+    F: Fn(String),
+{
+    let deadline = Instant::now() + budget;
+
+    while Instant::now() < deadline {
+        let logs = Command::new("modal")
+            .args(["app", "logs", call_id])
+            .output()
+            .await
+            .map_err(|e| ExecutorError::Modal(format!("Failed to fetch logs for {call_id}: {e}")))?;
+
+        let chunk = String::from_utf8_lossy(&logs.stdout);
+        if !chunk.is_empty() {
+            output.push_str(&chunk);
+            on_output(chunk.to_string());
+        }
+
+        let list = Command::new("modal")
+            .args(["app", "list", "--json"])
+            .output()
+            .await
+            .map_err(|e| ExecutorError::Modal(format!("Failed to check status of {call_id}: {e}")))?;
+
+        match parse_app_state(&String::from_utf8_lossy(&list.stdout), call_id) {
+            Some(ModalAppState::Stopped) => return Ok(ExecutionResult::Done { output }),
+            Some(ModalAppState::Failed) => {
+                return Ok(ExecutionResult::Error {
+                    message: format!("Modal app {call_id} failed"),
+                })
+            }
+            Some(ModalAppState::Running) | None => sleep(FOLLOW_POLL_INTERVAL).await,
+        }
+    }
+
+    Ok(ExecutionResult::Running {
+        session_id: call_id.to_string(),
+        attach_command: Some(format!("modal app logs --follow {call_id}")),
+        call_id: Some(call_id.to_string()),
+    })
+}
+
 /// Execute an agent on Modal
 pub async fn execute_modal<F>(
     request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
     on_output: F,
 ) -> ExecutorResult<ExecutionResult>
 where
@@ -75,6 +174,8 @@ where
         .spawn()
         .map_err(|e| ExecutorError::Modal(format!("Failed to start Modal: {}", e)))?;
 
+    let _registry_guard = super::track_child(registry, &request.node_id, &child);
+
     // Stream output
     let stdout = child.stdout.take().unwrap();
     let stderr = child.stderr.take().unwrap();
@@ -83,16 +184,34 @@ where
     let mut stderr_reader = BufReader::new(stderr).lines();
 
     let mut output = String::new();
+    // App id parsed from the CLI's own "View run at .../ap-..." line, if seen.
+    let mut call_id: Option<String> = None;
+
+    // Disabled (the common case) unless the node's execution config opts in.
+    let stall_timeout = request
+        .execution_config
+        .as_ref()
+        .and_then(|c| c.stall_timeout)
+        .map(Duration::from_secs);
 
     let result = timeout(EXECUTION_TIMEOUT, async {
+        let stall_sleep = sleep(stall_timeout.unwrap_or(Duration::from_secs(365 * 24 * 3600)));
+        tokio::pin!(stall_sleep);
+
         loop {
             tokio::select! {
                 line = stdout_reader.next_line() => {
                     match line {
                         Ok(Some(line)) => {
+                            if call_id.is_none() {
+                                call_id = extract_call_id(&line);
+                            }
                             output.push_str(&line);
                             output.push('\n');
                             on_output(format!("{}\n", line));
+                            if let Some(d) = stall_timeout {
+                                stall_sleep.as_mut().reset(Instant::now() + d);
+                            }
                         }
                         Ok(None) => break,
                         Err(_) => break,
@@ -101,34 +220,64 @@ where
                 line = stderr_reader.next_line() => {
                     match line {
                         Ok(Some(line)) => {
+                            if call_id.is_none() {
+                                call_id = extract_call_id(&line);
+                            }
                             output.push_str(&line);
                             output.push('\n');
                             on_output(format!("{}\n", line));
+                            if let Some(d) = stall_timeout {
+                                stall_sleep.as_mut().reset(Instant::now() + d);
+                            }
                         }
                         Ok(None) => {}
                         Err(_) => {}
                     }
                 }
+                // Only armed when `stall_timeout` is set; re-armed on *every* line above
+                // (stdout or stderr), so stderr-only progress keeps the process alive.
+                _ = &mut stall_sleep, if stall_timeout.is_some() => {
+                    return Ok(ModalOutcome::Stalled {
+                        idle_secs: stall_timeout.unwrap().as_secs(),
+                    });
+                }
             }
         }
 
-        child.wait().await
+        Ok(ModalOutcome::Done(child.wait().await?))
     })
     .await;
 
     match result {
-        Ok(Ok(status)) => {
-            if status.success() {
-                Ok(ExecutionResult::Done { output })
-            } else {
-                Ok(ExecutionResult::Error {
+        Ok(Ok(ModalOutcome::Done(status))) => {
+            if !status.success() {
+                return Ok(ExecutionResult::Error {
                     message: format!("Modal execution failed with code {}", status.code().unwrap_or(-1)),
-                })
+                });
             }
+
+            // `--detach` means this exit only confirms submission, not completion: follow
+            // the app itself for its real terminal state rather than trusting that.
+            match &call_id {
+                Some(id) => follow_call(id, &on_output, output, FOLLOW_BUDGET).await,
+                None => Ok(ExecutionResult::Done { output }),
+            }
+        }
+        Ok(Ok(ModalOutcome::Stalled { idle_secs })) => {
+            let (signal, grace) = super::stop_policy(request.execution_config.as_ref());
+            if let Err(e) = super::stop_child(&mut child, signal, grace).await {
+                tracing::warn!("Failed to stop stalled Modal process: {e}");
+            }
+            Err(ExecutorError::Stalled { idle_secs })
         }
         Ok(Err(e)) => Err(ExecutorError::Modal(e.to_string())),
         Err(_) => {
-            let _ = child.kill().await;
+            // Give the (detached) `modal run` CLI a chance to exit cleanly before SIGKILL;
+            // the server-side Modal function itself isn't affected either way.
+            let (signal, grace) = super::stop_policy(request.execution_config.as_ref());
+            if let Err(e) = super::stop_child(&mut child, signal, grace).await {
+                tracing::warn!("Failed to stop timed-out Modal process: {e}");
+            }
             Err(ExecutorError::Timeout)
         }
     }
@@ -1,17 +1,69 @@
 //! Remote executor - runs agents on remote VMs via SSH
 
-use super::{ExecuteRequest, ExecutionResult, ExecutorError, ExecutorResult};
+use super::{remote_connection, ExecuteRequest, ExecutionRegistry, ExecutionResult, ExecutorError, ExecutorResult};
+use std::path::PathBuf;
 use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
 /// Execution timeout (15 minutes for remote)
 const EXECUTION_TIMEOUT: Duration = Duration::from_secs(15 * 60);
 
-/// Execute an agent on a remote VM via SSH + Docker
+/// Bumped whenever the `orchestra-remote-server` wire protocol this module speaks
+/// changes, so `ensure_remote_server` knows a cached binary is stale and re-uploads.
+const REMOTE_SERVER_VERSION: &str = "1";
+
+/// Directory (relative to the remote user's home) `ensure_remote_server` caches the
+/// helper binary under, keyed by host triple.
+const REMOTE_SERVER_DIR: &str = ".orchestra/bin";
+
+/// How many SSH attempts `execute_remote_direct` makes in all (the first run plus the
+/// reconnects after the connection drops mid-run) before giving up and surfacing
+/// `ExecutorError::Remote`, when the node doesn't configure `ExecutionConfig::retry` itself.
+const DEFAULT_RECONNECT_ATTEMPTS: u32 = 4;
+
+/// First reconnect backoff, doubling after each subsequent attempt (2s, 4s, 8s, ...) up
+/// to `MAX_RECONNECT_BACKOFF`. A cold VM or a flaky link needs more than a flat 2s
+/// between tries before it's worth giving up.
+const RECONNECT_BASE_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Cap on `reconnect_backoff`'s exponential growth, so a long-running node doesn't end up
+/// waiting minutes between reconnect attempts.
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Exit code OpenSSH's `ssh(1)` reserves for "something about the connection itself went
+/// wrong" (refused, reset, timed out, multiplexed master gone) as opposed to the remote
+/// command having run and exited on its own. Used only for logging here -- the retry
+/// decision itself already turns on whether we ever saw `EXIT_SENTINEL`, which is a
+/// stronger signal than the local `ssh` client's own exit code.
+const SSH_CONNECTION_ERROR_EXIT_CODE: i32 = 255;
+
+/// Backoff before the `attempt`'th reconnect (1-indexed): `2s * 2^(attempt-1)`, capped at
+/// `MAX_RECONNECT_BACKOFF`.
+fn reconnect_backoff(attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt.saturating_sub(1)).unwrap_or(u64::MAX);
+    RECONNECT_BASE_BACKOFF
+        .saturating_mul(u32::try_from(factor).unwrap_or(u32::MAX))
+        .min(MAX_RECONNECT_BACKOFF)
+}
+
+/// Prefix `execute_remote_direct`'s remote script echoes once the agent command has
+/// exited, followed by its exit code -- how the client frame tells "more output" apart
+/// from "the run is over".
+const EXIT_SENTINEL: &str = "__ORCHESTRA_EXIT__:";
+
+/// Execute an agent on a remote VM via the `orchestra-remote-server` helper, which
+/// `ensure_remote_server` provisions on first connection and which this function then
+/// asks to spawn the agent inside tmux/Docker on the VM. Unlike `execute_remote_direct`
+/// (no helper, no Docker), the run keeps going on the VM independent of this call: the
+/// SSH channel is streamed into `on_output` in the background for as long as it stays
+/// open, and the function itself returns `Running` as soon as the `ssh` process running
+/// the helper has been spawned, without waiting for the helper to report back -- the same
+/// shape `execute_remote_interactive` returns for its detached tmux sessions.
 pub async fn execute_remote<F>(
     request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
     on_output: F,
 ) -> ExecutorResult<ExecutionResult>
 where
@@ -26,6 +78,7 @@ where
     let host = &remote_config.host;
     let user = remote_config.user.as_deref().unwrap_or("root");
     let port = remote_config.port.unwrap_or(22);
+    let target = format!("{}@{}", user, host);
 
     // Build SSH connection arguments
     let mut ssh_args = vec![
@@ -42,9 +95,11 @@ where
         ssh_args.push(key_path.clone());
     }
 
-    ssh_args.push(format!("{}@{}", user, host));
+    let connection_guard = acquire_connection(request, user, host, port, &ssh_args).await?;
+    if let Some(guard) = &connection_guard {
+        ssh_args.extend(guard.ssh_args());
+    }
 
-    // Build the remote Docker command
     let docker_config = request
         .execution_config
         .as_ref()
@@ -55,141 +110,808 @@ where
         .map(|s| s.as_str())
         .unwrap_or("orchestra-agent:full");
 
-    let agent_command = build_agent_command(&request.executor, &request.prompt, &request.options);
+    let agent_command =
+        crate::agent_command::one_shot_shell_command(&request.executor, &request.prompt, &request.options);
 
-    // Build Docker run command for remote
-    let docker_command = format!(
-        "docker run --rm {} sh -c {}",
-        image,
+    let server_path = ensure_remote_server(&ssh_args, &target).await?;
+    let session_name = format!("orchestra-{}", uuid::Uuid::new_v4());
+
+    let helper_command = format!(
+        "{} run --session {} --image {} --command {}",
+        shell_escape(&server_path),
+        shell_escape(&session_name),
+        shell_escape(image),
         shell_escape(&agent_command)
     );
 
-    ssh_args.push(docker_command);
+    let mut run_args = ssh_args.clone();
+    run_args.push(target.clone());
+    run_args.push(helper_command);
 
-    tracing::info!("Executing on remote: ssh {}@{}", user, host);
+    tracing::info!(
+        "Executing on remote via orchestra-remote-server: ssh {} ({})",
+        target,
+        session_name
+    );
 
-    // Spawn SSH process
     let mut child = Command::new("ssh")
-        .args(&ssh_args)
+        .args(&run_args)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| ExecutorError::Remote(format!("Failed to start SSH: {}", e)))?;
 
-    // Stream output
+    let registry_guard = super::track_child(registry, &request.node_id, &child);
+
     let stdout = child.stdout.take().unwrap();
     let stderr = child.stderr.take().unwrap();
-
     let mut stdout_reader = BufReader::new(stdout).lines();
     let mut stderr_reader = BufReader::new(stderr).lines();
 
-    let mut output = String::new();
-
-    let result = timeout(EXECUTION_TIMEOUT, async {
+    // Keep forwarding output for as long as the SSH channel to the helper stays open;
+    // the agent itself survives this task ending, same as `execute_remote_interactive`'s
+    // detached tmux session.
+    tokio::spawn(async move {
+        let _registry_guard = registry_guard;
+        let _connection_guard = connection_guard;
         loop {
             tokio::select! {
                 line = stdout_reader.next_line() => {
                     match line {
-                        Ok(Some(line)) => {
-                            output.push_str(&line);
-                            output.push('\n');
-                            on_output(format!("{}\n", line));
-                        }
-                        Ok(None) => break,
-                        Err(_) => break,
+                        Ok(Some(line)) => on_output(format!("{}\n", line)),
+                        _ => break,
                     }
                 }
                 line = stderr_reader.next_line() => {
                     match line {
-                        Ok(Some(line)) => {
-                            // SSH stderr might contain connection info, still output it
-                            output.push_str(&line);
-                            output.push('\n');
-                            on_output(format!("{}\n", line));
-                        }
-                        Ok(None) => {}
-                        Err(_) => {}
+                        Ok(Some(line)) => on_output(format!("{}\n", line)),
+                        Ok(None) | Err(_) => {}
                     }
                 }
             }
         }
+        let _ = child.wait().await;
+    });
+
+    let attach_command = format!("ssh -t {} tmux attach -t {}", target, session_name);
 
-        child.wait().await
+    Ok(ExecutionResult::Running {
+        session_id: session_name,
+        attach_command: Some(attach_command),
+        call_id: None,
     })
-    .await;
+}
 
-    match result {
-        Ok(Ok(status)) => {
-            if status.success() {
-                Ok(ExecutionResult::Done { output })
-            } else {
-                Ok(ExecutionResult::Error {
-                    message: format!("Remote execution failed with code {}", status.code().unwrap_or(-1)),
-                })
+/// Make sure a matching-version `orchestra-remote-server` helper binary is present on
+/// `target`, uploading the prebuilt binary for its host triple via scp the first time (or
+/// after `REMOTE_SERVER_VERSION` bumps) and caching it under `REMOTE_SERVER_DIR`.
+/// Subsequent connections skip the upload once the cached version file matches. Returns
+/// the absolute remote path to the cached binary.
+async fn ensure_remote_server(ssh_args: &[String], target: &str) -> ExecutorResult<String> {
+    let triple = remote_host_triple(ssh_args, target).await?;
+    let remote_path = format!("{}/orchestra-remote-server-{}", REMOTE_SERVER_DIR, triple);
+    let version_path = format!("{}.version", remote_path);
+
+    let mut check_args = ssh_args.to_vec();
+    check_args.push(target.to_string());
+    check_args.push(format!("cat {} 2>/dev/null", shell_escape(&version_path)));
+
+    let check = Command::new("ssh")
+        .args(&check_args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| ExecutorError::Remote(format!("Failed to check remote helper version: {}", e)))?;
+
+    if String::from_utf8_lossy(&check.stdout).trim() == REMOTE_SERVER_VERSION {
+        return Ok(remote_path);
+    }
+
+    let local_binary = local_remote_server_binary(&triple)?;
+
+    let mut mkdir_args = ssh_args.to_vec();
+    mkdir_args.push(target.to_string());
+    mkdir_args.push(format!("mkdir -p {}", shell_escape(REMOTE_SERVER_DIR)));
+    let mkdir_status = Command::new("ssh")
+        .args(&mkdir_args)
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| ExecutorError::Remote(format!("Failed to create {} on remote host: {}", REMOTE_SERVER_DIR, e)))?;
+    if !mkdir_status.success() {
+        return Err(ExecutorError::Remote(format!(
+            "Failed to create {} on remote host",
+            REMOTE_SERVER_DIR
+        )));
+    }
+
+    tracing::info!(
+        "Uploading orchestra-remote-server ({}) to {}:{}",
+        triple,
+        target,
+        remote_path
+    );
+
+    let mut scp_args = scp_args_from_ssh_args(ssh_args);
+    scp_args.push(local_binary.display().to_string());
+    scp_args.push(format!("{}:{}", target, remote_path));
+    let scp_status = Command::new("scp")
+        .args(&scp_args)
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| ExecutorError::Remote(format!("Failed to upload orchestra-remote-server: {}", e)))?;
+    if !scp_status.success() {
+        return Err(ExecutorError::Remote(
+            "scp upload of orchestra-remote-server failed".to_string(),
+        ));
+    }
+
+    let mut finalize_args = ssh_args.to_vec();
+    finalize_args.push(target.to_string());
+    finalize_args.push(format!(
+        "chmod +x {} && echo {} > {}",
+        shell_escape(&remote_path),
+        REMOTE_SERVER_VERSION,
+        shell_escape(&version_path)
+    ));
+    let finalize_status = Command::new("ssh")
+        .args(&finalize_args)
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| ExecutorError::Remote(format!("Failed to finalize orchestra-remote-server upload: {}", e)))?;
+    if !finalize_status.success() {
+        return Err(ExecutorError::Remote(
+            "Failed to mark orchestra-remote-server executable".to_string(),
+        ));
+    }
+
+    Ok(remote_path)
+}
+
+/// Detect `target`'s host triple (e.g. `linux-x86_64`, `darwin-arm64`) so
+/// `ensure_remote_server` can pick the matching prebuilt binary to upload.
+async fn remote_host_triple(ssh_args: &[String], target: &str) -> ExecutorResult<String> {
+    let mut args = ssh_args.to_vec();
+    args.push(target.to_string());
+    args.push("uname -s; uname -m".to_string());
+
+    let output = Command::new("ssh")
+        .args(&args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| ExecutorError::Remote(format!("Failed to detect remote host triple: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(ExecutorError::Remote(format!(
+            "Failed to detect remote host triple: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let mut lines = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_lowercase())
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    let os = match lines.next().as_deref() {
+        Some("linux") => "linux",
+        Some("darwin") => "darwin",
+        Some(other) => return Err(ExecutorError::Remote(format!("Unsupported remote OS: {}", other))),
+        None => return Err(ExecutorError::Remote("Remote host did not report an OS".to_string())),
+    };
+
+    let arch = match lines.next().as_deref() {
+        Some("x86_64") | Some("amd64") => "x86_64",
+        Some("aarch64") | Some("arm64") => "aarch64",
+        Some(other) => return Err(ExecutorError::Remote(format!("Unsupported remote architecture: {}", other))),
+        None => return Err(ExecutorError::Remote("Remote host did not report an architecture".to_string())),
+    };
+
+    Ok(format!("{}-{}", os, arch))
+}
+
+/// Locate the prebuilt `orchestra-remote-server` binary for `triple` that ships alongside
+/// this app, so `ensure_remote_server` has something to scp. Defaults to a
+/// `remote-server/` directory next to the running executable; overridable (for local
+/// development, where there's no bundled install) via `ORCHESTRA_REMOTE_SERVER_DIR`.
+fn local_remote_server_binary(triple: &str) -> ExecutorResult<PathBuf> {
+    let dir = match std::env::var("ORCHESTRA_REMOTE_SERVER_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|p| p.join("remote-server")))
+            .ok_or_else(|| ExecutorError::Remote("Could not resolve app directory".to_string()))?,
+    };
+
+    let path = dir.join(format!("orchestra-remote-server-{}", triple));
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(ExecutorError::Remote(format!(
+            "No prebuilt orchestra-remote-server binary for {} (looked in {})",
+            triple,
+            path.display()
+        )))
+    }
+}
+
+/// Translate `ssh`-style connection args into `scp`-style ones: same `-o`/`-i` pairs, but
+/// `-p <port>` (ssh) becomes `-P <port>` (scp).
+fn scp_args_from_ssh_args(ssh_args: &[String]) -> Vec<String> {
+    let mut out = Vec::with_capacity(ssh_args.len());
+    let mut iter = ssh_args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-p" {
+            out.push("-P".to_string());
+            if let Some(port) = iter.next() {
+                out.push(port.clone());
             }
+        } else {
+            out.push(arg.clone());
         }
-        Ok(Err(e)) => Err(ExecutorError::Remote(e.to_string())),
-        Err(_) => {
-            let _ = child.kill().await;
-            Err(ExecutorError::Timeout)
+    }
+    out
+}
+
+/// Stop a remote session started by `execute_remote`, given `container_id` in the form
+/// `"<user>@<host>:<port>#<session_name>"` -- enough to reconnect without the node's
+/// original `RemoteConfig`. `execute_remote` itself returns only the bare session name as
+/// its `session_id` and does not write this form anywhere; `stop_execution` calls this
+/// only for a `Session` whose `container_id` is already set.
+pub async fn stop_remote_session(container_id: &str) -> ExecutorResult<()> {
+    let (target_with_port, session_name) = container_id
+        .split_once('#')
+        .ok_or_else(|| ExecutorError::Remote(format!("malformed remote container id: {}", container_id)))?;
+    let (target, port) = target_with_port
+        .rsplit_once(':')
+        .ok_or_else(|| ExecutorError::Remote(format!("malformed remote container id: {}", container_id)))?;
+
+    let ssh_args = vec![
+        "-o".to_string(),
+        "StrictHostKeyChecking=no".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-p".to_string(),
+        port.to_string(),
+    ];
+
+    let mut kill_args = ssh_args;
+    kill_args.push(target.to_string());
+    kill_args.push(format!(
+        "tmux kill-session -t {0} 2>/dev/null; docker rm -f {0} 2>/dev/null || true",
+        shell_escape(session_name)
+    ));
+
+    let status = Command::new("ssh")
+        .args(&kill_args)
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| ExecutorError::Remote(format!("Failed to SSH in to stop {}: {}", session_name, e)))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ExecutorError::Remote(format!(
+            "Failed to stop remote session {} on {}",
+            session_name, target
+        )))
+    }
+}
+
+/// Execute an agent directly on a remote host over SSH, inside a detached remote tmux
+/// session, so its output can be streamed back live and a user can attach to it with
+/// their own terminal (`ssh -t host tmux attach`) the same way they would a local
+/// interactive session.
+pub async fn execute_remote_interactive<F>(
+    request: &ExecuteRequest,
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
+where
+    F: Fn(String) + Send + 'static,
+{
+    let remote_config = request
+        .execution_config
+        .as_ref()
+        .and_then(|c| c.remote.as_ref())
+        .ok_or_else(|| ExecutorError::Remote("Remote config required".to_string()))?;
+
+    let host = &remote_config.host;
+    let user = remote_config.user.as_deref().unwrap_or("root");
+    let port = remote_config.port.unwrap_or(22);
+    let target = format!("{}@{}", user, host);
+
+    let mut ssh_args = vec![
+        "-o".to_string(),
+        "StrictHostKeyChecking=no".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-p".to_string(),
+        port.to_string(),
+    ];
+
+    if let Some(key_path) = &remote_config.key_path {
+        ssh_args.push("-i".to_string());
+        ssh_args.push(key_path.clone());
+    }
+
+    let connection_guard = acquire_connection(request, user, host, port, &ssh_args).await?;
+    if let Some(guard) = &connection_guard {
+        ssh_args.extend(guard.ssh_args());
+    }
+
+    let agent_command =
+        crate::agent_command::one_shot_shell_command(&request.executor, &request.prompt, &request.options);
+
+    let cd_prefix = remote_config
+        .workdir
+        .as_ref()
+        .map(|dir| format!("cd {} && ", shell_escape(dir)))
+        .unwrap_or_default();
+
+    let mut env_prefix = String::new();
+    for var in ["ANTHROPIC_API_KEY", "OPENAI_API_KEY", "GOOGLE_API_KEY", "CLAUDE_CODE_OAUTH_TOKEN"] {
+        if let Ok(value) = std::env::var(var) {
+            env_prefix.push_str(&format!("{}={} ", var, shell_escape(&value)));
         }
     }
+
+    let inner_command = format!("{}{}{}", cd_prefix, env_prefix, agent_command);
+    let session_name = format!("orchestra-{}", uuid::Uuid::new_v4());
+    let tmux_command = format!(
+        "tmux new-session -d -s {} {}",
+        session_name,
+        shell_escape(&inner_command)
+    );
+
+    let mut start_args = ssh_args.clone();
+    start_args.push(target.clone());
+    start_args.push(tmux_command);
+
+    tracing::info!("Starting remote interactive session on {}: {}", target, session_name);
+
+    let start = Command::new("ssh")
+        .args(&start_args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| ExecutorError::Remote(format!("Failed to start SSH: {}", e)))?;
+
+    if !start.status.success() {
+        return Err(ExecutorError::Remote(format!(
+            "Failed to start remote session: {}",
+            String::from_utf8_lossy(&start.stderr)
+        )));
+    }
+
+    let attach_command = format!("ssh -t {} tmux attach -t {}", target, session_name);
+
+    // Poll the remote pane and forward only newly-appended output to the caller.
+    let poll_ssh_args = ssh_args;
+    let poll_target = target;
+    let poll_session = session_name.clone();
+    tokio::spawn(async move {
+        let _connection_guard = connection_guard;
+        let mut last_output = String::new();
+        loop {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+
+            let mut args = poll_ssh_args.clone();
+            args.push(poll_target.clone());
+            args.push(format!("tmux capture-pane -t {} -p 2>/dev/null", poll_session));
+
+            let Ok(result) = Command::new("ssh").args(&args).output().await else {
+                break;
+            };
+            let captured = String::from_utf8_lossy(&result.stdout).to_string();
+
+            if let Some(new_part) = captured.strip_prefix(&last_output) {
+                if !new_part.is_empty() {
+                    on_output(new_part.to_string());
+                }
+            } else if captured != last_output {
+                on_output(captured.clone());
+            }
+            last_output = captured;
+
+            let mut exists_args = poll_ssh_args.clone();
+            exists_args.push(poll_target.clone());
+            exists_args.push(format!("tmux has-session -t {} 2>/dev/null", poll_session));
+            match Command::new("ssh").args(&exists_args).status().await {
+                Ok(status) if status.success() => continue,
+                _ => break,
+            }
+        }
+    });
+
+    Ok(ExecutionResult::Running {
+        session_id: session_name,
+        attach_command: Some(attach_command),
+        call_id: None,
+    })
 }
 
-/// Build the agent command string
-fn build_agent_command(
-    executor: &str,
-    prompt: &str,
-    options: &Option<serde_json::Value>,
-) -> String {
-    let escaped_prompt = shell_escape(prompt);
-
-    match executor {
-        "claude" => {
-            let mut cmd = format!(
-                "claude -p {} --output-format text --no-session-persistence --permission-mode dontAsk --tools ''",
-                escaped_prompt
-            );
-
-            if let Some(opts) = options {
-                if let Some(model) = opts.get("model").and_then(|v| v.as_str()) {
-                    cmd.push_str(&format!(" --model {}", model));
+/// Execute an agent on a remote VM inside a genuine pseudo-terminal: `ssh -tt` forces the
+/// remote side to allocate a pty for `docker run -it`, and this keeps the SSH child's own
+/// stdin open as a piped writer (instead of `Stdio::null()`, like every other remote
+/// backend) so a caller can forward keystrokes through `request.remote_pty_inputs` -- the
+/// same way `sessions::manager`'s local tmux/PTY sessions already do, just over SSH.
+/// Because the remote command runs attached to a pty, its stdout and stderr are already
+/// merged into one stream by the time they reach us; only `ssh`'s own diagnostics (not the
+/// agent's own stderr) still arrive on the local child's stderr.
+pub async fn execute_remote_pty<F>(
+    request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
+where
+    F: Fn(String) + Send + 'static,
+{
+    let remote_config = request
+        .execution_config
+        .as_ref()
+        .and_then(|c| c.remote.as_ref())
+        .ok_or_else(|| ExecutorError::Remote("Remote config required".to_string()))?;
+
+    let host = &remote_config.host;
+    let user = remote_config.user.as_deref().unwrap_or("root");
+    let port = remote_config.port.unwrap_or(22);
+    let target = format!("{}@{}", user, host);
+
+    let mut ssh_args = vec![
+        "-o".to_string(),
+        "StrictHostKeyChecking=no".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-p".to_string(),
+        port.to_string(),
+    ];
+
+    if let Some(key_path) = &remote_config.key_path {
+        ssh_args.push("-i".to_string());
+        ssh_args.push(key_path.clone());
+    }
+
+    let connection_guard = acquire_connection(request, user, host, port, &ssh_args).await?;
+    if let Some(guard) = &connection_guard {
+        ssh_args.extend(guard.ssh_args());
+    }
+
+    let docker_config = request
+        .execution_config
+        .as_ref()
+        .and_then(|c| c.docker.as_ref());
+
+    let image = docker_config
+        .and_then(|c| c.image.as_ref())
+        .map(|s| s.as_str())
+        .unwrap_or("orchestra-agent:full");
+
+    let agent_command = crate::agent_command::interactive_shell_command_from_options(
+        &request.executor,
+        &request.prompt,
+        &request.options,
+    );
+    let session_name = format!("orchestra-{}", uuid::Uuid::new_v4());
+
+    let cd_prefix = remote_config
+        .workdir
+        .as_ref()
+        .map(|dir| format!("cd {} && ", shell_escape(dir)))
+        .unwrap_or_default();
+
+    let docker_command = format!(
+        "{}docker run -it --rm --name {} {} sh -c {}",
+        cd_prefix,
+        shell_escape(&session_name),
+        image,
+        shell_escape(&agent_command)
+    );
+
+    let mut args = ssh_args;
+    args.push("-tt".to_string());
+    args.push(target.clone());
+    args.push(docker_command);
+
+    tracing::info!("Starting remote PTY session on {}: {}", target, session_name);
+
+    let mut child = Command::new("ssh")
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ExecutorError::Remote(format!("Failed to start SSH: {}", e)))?;
+
+    let registry_guard = super::track_child(registry, &request.node_id, &child);
+
+    let mut stdin = child.stdin.take().unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+    let mut stdout_reader = BufReader::new(stdout).lines();
+    let mut stderr_reader = BufReader::new(stderr).lines();
+
+    let (input_tx, mut input_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    if let Some(inputs) = &request.remote_pty_inputs {
+        inputs.register(&session_name, input_tx);
+    }
+
+    let remote_pty_inputs = request.remote_pty_inputs.clone();
+    let cleanup_session_name = session_name.clone();
+    tokio::spawn(async move {
+        let _registry_guard = registry_guard;
+        let _connection_guard = connection_guard;
+        loop {
+            tokio::select! {
+                line = stdout_reader.next_line() => {
+                    match line {
+                        Ok(Some(line)) => on_output(format!("{}\n", line)),
+                        _ => break,
+                    }
+                }
+                line = stderr_reader.next_line() => {
+                    match line {
+                        Ok(Some(line)) => on_output(format!("{}\n", line)),
+                        Ok(None) | Err(_) => {}
+                    }
+                }
+                Some(input) = input_rx.recv() => {
+                    if stdin.write_all(input.as_bytes()).await.is_err() || stdin.flush().await.is_err() {
+                        break;
+                    }
                 }
             }
+        }
+        if let Some(inputs) = remote_pty_inputs {
+            inputs.unregister(&cleanup_session_name);
+        }
+        let _ = child.wait().await;
+    });
+
+    Ok(ExecutionResult::Running {
+        session_id: session_name,
+        attach_command: None,
+        call_id: None,
+    })
+}
 
-            cmd
+/// Execute an agent directly on a remote host over SSH -- no Docker, no tmux. Unlike
+/// `execute_remote`, which returns `Running` once the helper is launched, this waits for
+/// the agent to exit, and persists the agent's
+/// exit code to a status file on the remote host as its very last step, so that if the
+/// SSH connection drops mid-run, we can reconnect and poll that status file instead of
+/// losing the run's outcome or re-running the agent.
+pub async fn execute_remote_direct<F>(
+    request: &ExecuteRequest,
+    registry: &ExecutionRegistry,
+    on_output: F,
+) -> ExecutorResult<ExecutionResult>
+where
+    F: Fn(String) + Send + 'static,
+{
+    if !crate::agent_command::is_allowed_executor(&request.executor) {
+        return Err(ExecutorError::InvalidExecutor(request.executor.clone()));
+    }
+
+    let remote_config = request
+        .execution_config
+        .as_ref()
+        .and_then(|c| c.remote.as_ref())
+        .ok_or_else(|| ExecutorError::Remote("Remote config required".to_string()))?;
+
+    let host = &remote_config.host;
+    let user = remote_config.user.as_deref().unwrap_or("root");
+    let port = remote_config.port.unwrap_or(22);
+    let target = format!("{}@{}", user, host);
+
+    let mut ssh_args = vec![
+        "-o".to_string(),
+        "StrictHostKeyChecking=no".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-p".to_string(),
+        port.to_string(),
+    ];
+
+    if let Some(key_path) = &remote_config.key_path {
+        ssh_args.push("-i".to_string());
+        ssh_args.push(key_path.clone());
+    }
+
+    let connection_guard = acquire_connection(request, user, host, port, &ssh_args).await?;
+    if let Some(guard) = &connection_guard {
+        ssh_args.extend(guard.ssh_args());
+    }
+
+    let agent_command =
+        crate::agent_command::one_shot_shell_command(&request.executor, &request.prompt, &request.options);
+
+    let cd_prefix = remote_config
+        .workdir
+        .as_ref()
+        .map(|dir| format!("cd {} && ", shell_escape(dir)))
+        .unwrap_or_default();
+
+    let mut env_prefix = String::new();
+    for var in ["ANTHROPIC_API_KEY", "OPENAI_API_KEY", "GOOGLE_API_KEY", "CLAUDE_CODE_OAUTH_TOKEN"] {
+        if let Ok(value) = std::env::var(var) {
+            env_prefix.push_str(&format!("{}={} ", var, shell_escape(&value)));
         }
+    }
+
+    let job_id = uuid::Uuid::new_v4();
+    let status_path = format!("/tmp/orchestra-remote-{}.status", job_id);
 
-        "codex" => {
-            let mut cmd = "codex exec --skip-git-repo-check".to_string();
+    let run_script = format!(
+        "({}{}{}) 2>&1; code=$?; echo \"$code\" > {}; echo \"{}$code\"",
+        cd_prefix, env_prefix, agent_command, status_path, EXIT_SENTINEL
+    );
+    let resume_script = format!(
+        "test -f {status} && echo \"{sentinel}$(cat {status})\" || echo \"{sentinel}-1\"",
+        status = status_path,
+        sentinel = EXIT_SENTINEL,
+    );
 
-            if let Some(opts) = options {
-                let reasoning = opts
-                    .get("reasoningEffort")
-                    .or_else(|| opts.get("reasoningLevel"))
-                    .and_then(|v| v.as_str());
+    tracing::info!("Executing on remote (direct): ssh {}", target);
 
-                if let Some(level) = reasoning {
-                    cmd.push_str(&format!(" -c reasoning.effort={}", level));
+    let retry_config = request.execution_config.as_ref().and_then(|c| c.retry);
+    let max_attempts = retry_config
+        .map(|r| r.max_attempts.max(1))
+        .unwrap_or(DEFAULT_RECONNECT_ATTEMPTS);
+
+    let mut output = String::new();
+    let mut exit_code: Option<i32> = None;
+    let mut current_child: Option<tokio::process::Child> = None;
+    let mut registry_guard = None;
+
+    let result = timeout(EXECUTION_TIMEOUT, async {
+        for attempt in 0..max_attempts {
+            let script = if attempt == 0 { &run_script } else { &resume_script };
+
+            let mut args = ssh_args.clone();
+            args.push(target.clone());
+            args.push(script.clone());
+
+            let mut child = match Command::new("ssh")
+                .args(&args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+            {
+                Ok(child) => child,
+                Err(e) => return Err(ExecutorError::Remote(format!("Failed to start SSH: {}", e))),
+            };
+
+            if attempt == 0 {
+                registry_guard = super::track_child(registry, &request.node_id, &child);
+            }
+
+            let stdout = child.stdout.take().unwrap();
+            let stderr = child.stderr.take().unwrap();
+            let mut stdout_reader = BufReader::new(stdout).lines();
+            let mut stderr_reader = BufReader::new(stderr).lines();
+
+            let mut connection_dropped = false;
+
+            loop {
+                tokio::select! {
+                    line = stdout_reader.next_line() => {
+                        match line {
+                            Ok(Some(line)) => {
+                                if let Some(code_str) = line.strip_prefix(EXIT_SENTINEL) {
+                                    exit_code = code_str.trim().parse::<i32>().ok().filter(|c| *c >= 0);
+                                    // `-1` is `resume_script`'s "no status file yet": keep
+                                    // polling on the next attempt instead of giving up.
+                                    connection_dropped = exit_code.is_none();
+                                    break;
+                                }
+                                output.push_str(&line);
+                                output.push('\n');
+                                on_output(format!("{}\n", line));
+                            }
+                            Ok(None) => { connection_dropped = true; break; }
+                            Err(_) => { connection_dropped = true; break; }
+                        }
+                    }
+                    line = stderr_reader.next_line() => {
+                        match line {
+                            Ok(Some(line)) => {
+                                output.push_str(&line);
+                                output.push('\n');
+                                on_output(format!("{}\n", line));
+                            }
+                            Ok(None) => {}
+                            Err(_) => {}
+                        }
+                    }
                 }
             }
 
-            cmd.push_str(&format!(" {}", escaped_prompt));
-            cmd
-        }
+            current_child = Some(child);
+            let ssh_status = if let Some(child) = current_child.as_mut() {
+                child.wait().await.ok()
+            } else {
+                None
+            };
 
-        "gemini" => {
-            let model = options
-                .as_ref()
-                .and_then(|o| o.get("model"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("gemini-3-pro-preview");
+            // A non-zero agent exit is a real result, never retried; only a dropped
+            // connection before we ever saw `EXIT_SENTINEL` is transient.
+            if exit_code.is_some() || !connection_dropped {
+                break;
+            }
+
+            if let Some(status) = &ssh_status {
+                tracing::warn!(
+                    "ssh to {} exited {:?} ({}) before the agent's exit code arrived",
+                    target,
+                    status.code(),
+                    if status.code() == Some(SSH_CONNECTION_ERROR_EXIT_CODE) {
+                        "connection-level failure"
+                    } else {
+                        "connection dropped"
+                    }
+                );
+            }
 
-            format!("gemini {} -m {} -o text", escaped_prompt, model)
+            if attempt + 1 < max_attempts {
+                let delay = reconnect_backoff(attempt + 1);
+                tracing::warn!(
+                    "Lost connection to {} mid-run, reconnecting in {:?} (attempt {}/{})",
+                    target,
+                    delay,
+                    attempt + 2,
+                    max_attempts
+                );
+                on_output(format!(
+                    "[orchestra] lost connection to {}; retrying in {:?} (attempt {}/{})\n",
+                    target,
+                    delay,
+                    attempt + 2,
+                    max_attempts
+                ));
+                if let (Some(state_tx), Some(project_id)) = (&request.state_tx, &request.project_id) {
+                    crate::sessions::agent_state::report(
+                        state_tx,
+                        project_id,
+                        &request.node_id,
+                        crate::sessions::agent_state::AgentState::Retrying,
+                        Some(crate::sessions::agent_state::RetryInfo {
+                            attempt: attempt + 2,
+                            delay_ms: delay.as_millis() as u64,
+                        }),
+                    );
+                }
+                tokio::time::sleep(delay).await;
+            }
         }
 
-        _ => escaped_prompt,
+        Ok(())
+    })
+    .await;
+
+    if result.is_err() {
+        if let Some(child) = current_child.as_mut() {
+            let _ = child.kill().await;
+        }
+        return Err(ExecutorError::Timeout);
+    }
+    result.unwrap()?;
+
+    match exit_code {
+        Some(0) => Ok(ExecutionResult::Done { output }),
+        Some(code) if code > 0 => Ok(ExecutionResult::Error {
+            message: format!("Remote execution failed with code {}", code),
+        }),
+        _ => Err(ExecutorError::Remote(format!(
+            "Lost connection to {} and could not recover the run's status after {} attempt(s)",
+            target, max_attempts
+        ))),
     }
 }
 
@@ -197,3 +919,21 @@ fn build_agent_command(
 fn shell_escape(s: &str) -> String {
     format!("'{}'", s.replace("'", "'\\''"))
 }
+
+/// Acquire a `ConnectionGuard` for `(user, host, port)` from `request.remote_connections`,
+/// so every `ssh`/`scp` this execution makes afterward rides one multiplexed ControlMaster
+/// instead of paying a fresh handshake each time. `None` when the request wasn't built with
+/// a `RemoteConnectionManager` (e.g. a caller outside `AppState`'s reach) -- callers fall
+/// back to plain per-call `ssh_args` in that case, same as before this existed.
+async fn acquire_connection(
+    request: &ExecuteRequest,
+    user: &str,
+    host: &str,
+    port: u16,
+    base_ssh_args: &[String],
+) -> ExecutorResult<Option<remote_connection::ConnectionGuard>> {
+    match &request.remote_connections {
+        Some(manager) => Ok(Some(manager.acquire(user, host, port, base_ssh_args).await?)),
+        None => Ok(None),
+    }
+}
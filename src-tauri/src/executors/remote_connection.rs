@@ -0,0 +1,228 @@
+//! Persistent SSH connection manager for the remote executor.
+//!
+//! `execute_remote`/`execute_remote_direct`/`execute_remote_interactive` each used to
+//! spawn a brand-new `ssh` process per call, paying a fresh TCP connection and auth
+//! handshake every single time. `RemoteConnectionManager` keeps one multiplexed
+//! "ControlMaster" connection alive per `(user, host, port)` instead -- opened once via
+//! `-o ControlMaster=auto -o ControlPersist=600`, reused by every subsequent `ssh`/`scp`
+//! against the same target through a shared `ControlPath` socket.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+use super::{ExecutorError, ExecutorResult};
+
+/// `-o ControlPersist=<this>`: how long OpenSSH keeps an idle master connection open
+/// before tearing it down on its own.
+const CONTROL_PERSIST_SECS: &str = "600";
+
+/// `(user, host, port)` -- the same tuple a `ControlPath` of `orchestra-%h-%p-%r` keys a
+/// multiplexed connection by.
+type ConnectionKey = (String, String, u16);
+
+/// Health of a managed connection's master socket, for the UI's "connected /
+/// reconnecting" indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionHealth {
+    Connected,
+    Reconnecting,
+}
+
+/// One multiplexed SSH master, shared by every execution against the same
+/// `(user, host, port)`.
+struct ManagedConnection {
+    control_path: String,
+    /// Number of executions currently holding a `ConnectionGuard` for this connection.
+    /// `reap_idle` only forgets entries whose count has dropped back to zero, so OpenSSH's
+    /// own `ControlPersist` window (not us) decides exactly when the master actually exits.
+    ref_count: AtomicU32,
+    health: Mutex<ConnectionHealth>,
+}
+
+/// RAII handle returned by `RemoteConnectionManager::acquire`. Dropping it releases this
+/// execution's hold on the shared master; the master itself isn't closed here, only
+/// reference-counted -- `RemoteConnectionManager::reap_idle` is what forgets connections
+/// nothing is using anymore.
+pub struct ConnectionGuard {
+    connection: Arc<ManagedConnection>,
+}
+
+impl ConnectionGuard {
+    /// The `-o ControlPath=...` options every subsequent `ssh`/`scp` against this guard's
+    /// target should append to its own args, so it rides the shared master instead of
+    /// opening a fresh connection.
+    pub fn ssh_args(&self) -> Vec<String> {
+        vec![
+            "-o".to_string(),
+            "ControlMaster=auto".to_string(),
+            "-o".to_string(),
+            format!("ControlPersist={}", CONTROL_PERSIST_SECS),
+            "-o".to_string(),
+            format!("ControlPath={}", self.connection.control_path),
+        ]
+    }
+
+    pub async fn health(&self) -> ConnectionHealth {
+        *self.connection.health.lock().await
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.connection.ref_count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Registry of live ControlMaster connections, keyed by `(user, host, port)`. Cheap to
+/// clone (an `Arc` underneath); `AppState` holds one shared instance for the app's whole
+/// lifetime, the same way it holds `ExecutionRegistry`.
+#[derive(Clone)]
+pub struct RemoteConnectionManager {
+    connections: Arc<Mutex<HashMap<ConnectionKey, Arc<ManagedConnection>>>>,
+    socket_dir: Arc<std::path::PathBuf>,
+}
+
+impl Default for RemoteConnectionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RemoteConnectionManager {
+    pub fn new() -> Self {
+        Self {
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            socket_dir: Arc::new(std::env::temp_dir().join("orchestra-ssh-sockets")),
+        }
+    }
+
+    /// Get (opening if necessary) the shared ControlMaster for `(user, host, port)`,
+    /// authenticating with `base_ssh_args` (host-key/identity options -- not the
+    /// multiplexing ones, which come from the returned guard's `ssh_args`).
+    pub async fn acquire(
+        &self,
+        user: &str,
+        host: &str,
+        port: u16,
+        base_ssh_args: &[String],
+    ) -> ExecutorResult<ConnectionGuard> {
+        let key: ConnectionKey = (user.to_string(), host.to_string(), port);
+
+        let mut connections = self.connections.lock().await;
+        let connection = match connections.get(&key) {
+            Some(existing) => existing.clone(),
+            None => {
+                let connection = Arc::new(self.open_master(&key, base_ssh_args).await?);
+                connections.insert(key, connection.clone());
+                connection
+            }
+        };
+        drop(connections);
+
+        connection.ref_count.fetch_add(1, Ordering::SeqCst);
+        Ok(ConnectionGuard { connection })
+    }
+
+    /// Open a brand-new ControlMaster for `key`, backgrounded (`-f -N`) so this returns as
+    /// soon as the master has authenticated rather than blocking for the whole
+    /// `ControlPersist` window.
+    async fn open_master(
+        &self,
+        key: &ConnectionKey,
+        base_ssh_args: &[String],
+    ) -> ExecutorResult<ManagedConnection> {
+        tokio::fs::create_dir_all(self.socket_dir.as_path())
+            .await
+            .map_err(|e| ExecutorError::Remote(format!("Failed to create SSH socket dir: {}", e)))?;
+
+        let (user, host, port) = key;
+        let control_path = self
+            .socket_dir
+            .join(format!("orchestra-{}-{}-{}", host, port, user))
+            .to_string_lossy()
+            .into_owned();
+
+        let mut args = base_ssh_args.to_vec();
+        args.extend([
+            "-o".to_string(),
+            "ControlMaster=auto".to_string(),
+            "-o".to_string(),
+            format!("ControlPersist={}", CONTROL_PERSIST_SECS),
+            "-o".to_string(),
+            format!("ControlPath={}", control_path),
+            "-N".to_string(),
+            "-f".to_string(),
+            format!("{}@{}", user, host),
+        ]);
+
+        tracing::info!("Opening persistent SSH connection to {}@{}:{}", user, host, port);
+
+        let status = Command::new("ssh")
+            .args(&args)
+            .stdin(std::process::Stdio::null())
+            .status()
+            .await
+            .map_err(|e| ExecutorError::Remote(format!("Failed to open SSH master: {}", e)))?;
+
+        if !status.success() {
+            return Err(ExecutorError::Remote(format!(
+                "Failed to establish persistent SSH connection to {}@{}:{}",
+                user, host, port
+            )));
+        }
+
+        Ok(ManagedConnection {
+            control_path,
+            ref_count: AtomicU32::new(0),
+            health: Mutex::new(ConnectionHealth::Connected),
+        })
+    }
+
+    /// Check whether `(user, host, port)`'s master is still alive (`ssh -O check`),
+    /// updating and returning its tracked `ConnectionHealth`. `None` if nothing's
+    /// registered for that key (never opened, or already reaped).
+    pub async fn check_health(&self, user: &str, host: &str, port: u16) -> Option<ConnectionHealth> {
+        let key: ConnectionKey = (user.to_string(), host.to_string(), port);
+        let connection = self.connections.lock().await.get(&key)?.clone();
+
+        let alive = Command::new("ssh")
+            .args([
+                "-O",
+                "check",
+                "-o",
+                &format!("ControlPath={}", connection.control_path),
+                &format!("{}@{}", user, host),
+            ])
+            .stdin(std::process::Stdio::null())
+            .status()
+            .await
+            .map(|status| status.success())
+            .unwrap_or(false);
+
+        let health = if alive {
+            ConnectionHealth::Connected
+        } else {
+            ConnectionHealth::Reconnecting
+        };
+        *connection.health.lock().await = health;
+        Some(health)
+    }
+
+    /// Forget every registered connection whose `ref_count` has dropped back to zero,
+    /// i.e. nothing is actively executing against it right now. Doesn't itself close the
+    /// master -- OpenSSH's `ControlPersist` window (or the process exiting) does that --
+    /// this just stops the registry from handing out a guard for a connection we no
+    /// longer intend to keep reusing.
+    pub async fn reap_idle(&self) {
+        self.connections
+            .lock()
+            .await
+            .retain(|_, connection| connection.ref_count.load(Ordering::SeqCst) > 0);
+    }
+}
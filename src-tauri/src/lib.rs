@@ -1,13 +1,20 @@
+mod agent_command;
 mod commands;
+mod db;
 mod executors;
+mod orchestration;
+mod session_log;
 mod sessions;
 mod state;
+mod terminal;
 
 use std::sync::Arc;
+
 use tauri::Manager;
-use tokio::sync::Mutex;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use sessions::manager::SessionManager;
+use sessions::notifier::Notifier;
+use sessions::supervisor::Supervisor;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -21,14 +28,16 @@ pub fn run() {
 
     tracing::info!("Starting Orchestra Desktop");
 
-    // Create shared session manager
-    let session_manager = Arc::new(Mutex::new(SessionManager::new()));
-    let session_manager_for_state = session_manager.clone();
+    // SessionManager and Supervisor are both internally synchronized (Arc<Mutex<...>>)
+    // and Clone, so they're managed directly rather than wrapped in another Arc<Mutex<_>>.
+    let session_manager = SessionManager::new();
+    let supervisor = Supervisor::new();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(state::AppState::new())
-        .manage(session_manager_for_state)
+        .manage(session_manager)
+        .manage(supervisor)
         .setup(|app| {
             // Get the main window for event emission
             let window = app.get_webview_window("main")
@@ -37,26 +46,45 @@ pub fn run() {
             // Get state for accessing projects
             let app_state = app.state::<state::AppState>().inner().clone();
 
-            // Get session manager for the monitor
-            let session_manager = app.state::<Arc<Mutex<SessionManager>>>().inner().clone();
+            // Get session manager and supervisor for the monitor
+            let session_manager = app.state::<SessionManager>().inner().clone();
+            let supervisor = app.state::<Supervisor>().inner().clone();
+
+            let history = Arc::new(
+                sessions::history::HistoryStore::new(app.app_handle())
+                    .expect("failed to open run history database"),
+            );
+            app.manage(history.clone());
+
+            let db = Arc::new(db::Database::new(app.app_handle()).expect("failed to open database"));
+            app.manage(db.clone());
 
-            // Start the background completion monitor
-            // The get_node_checks closure retrieves checks from the project store
-            let get_node_checks = move |node_id: &str| -> Vec<sessions::checks::Check> {
-                // This is a sync closure but we need async access to projects
-                // For now, use blocking read - in production, consider a channel-based approach
-                let projects = app_state.projects.blocking_read();
+            let notifier = Notifier::new(window.app_handle().clone(), db.clone());
 
-                for project in projects.values() {
-                    if let Some(node) = project.nodes.iter().find(|n| n.id == node_id) {
-                        // Parse checks from the project node
-                        return parse_node_checks(&node.checks);
-                    }
-                }
-                Vec::new()
-            };
+            // Recovery needs `SessionManager`'s async lock, which this sync `setup` closure
+            // can't await directly.
+            let recovery_session_manager = session_manager.clone();
+            let recovery_db = db.clone();
+            tauri::async_runtime::spawn(async move {
+                recovery_session_manager.attach_database(recovery_db).await;
+            });
 
-            sessions::monitor::start_monitor(window, session_manager, get_node_checks);
+            // Drain node state transitions (see `sessions::agent_state`) into `AppState`
+            // and the UI, before anything starts reporting them.
+            if let Some(state_rx) = app_state.take_state_rx() {
+                sessions::agent_state::start_state_sink(window.clone(), app_state.clone(), state_rx);
+            }
+
+            // Start the background completion monitor. It also acts as the session
+            // supervisor's tick: see `sessions::supervisor`.
+            sessions::monitor::start_monitor(
+                window,
+                session_manager,
+                app_state,
+                supervisor,
+                notifier,
+                history,
+            );
 
             tracing::info!("Session completion monitor started");
             Ok(())
@@ -67,25 +95,41 @@ pub fn run() {
             commands::projects::create_project,
             commands::projects::save_project,
             commands::projects::delete_project,
+            commands::nodes::add_node,
+            commands::nodes::update_node,
+            commands::nodes::delete_node,
+            commands::nodes::set_node_status,
             commands::execution::execute_node,
+            commands::execution::execute_project,
             commands::execution::stop_execution,
+            commands::execution::send_remote_pty_input,
+            commands::execution::resize_pty,
+            commands::execution::send_input,
+            commands::execution::close_input,
+            commands::execution::get_screen,
+            commands::execution::get_session_log,
+            commands::execution::list_session_logs,
             commands::sessions::create_interactive_session,
             commands::sessions::attach_session,
             commands::sessions::send_session_input,
             commands::sessions::capture_session_output,
+            commands::sessions::stream_session_output,
+            commands::sessions::resize_session,
             commands::sessions::kill_interactive_session,
             commands::sessions::list_interactive_sessions,
+            commands::sessions::switch_session,
             commands::sessions::get_attach_command,
             commands::sessions::open_in_ghostty,
+            commands::sessions::list_workers,
+            commands::sessions::pause_worker,
+            commands::sessions::resume_worker,
+            commands::sessions::cancel_worker,
+            commands::history::list_runs,
+            commands::history::get_run,
+            commands::history::replay_run,
+            commands::notifications::list_notifications,
+            commands::notifications::acknowledge_notification,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
-
-/// Parse checks from the project node format into our Check enum
-fn parse_node_checks(checks: &[serde_json::Value]) -> Vec<sessions::checks::Check> {
-    checks
-        .iter()
-        .filter_map(|v| serde_json::from_value(v.clone()).ok())
-        .collect()
-}
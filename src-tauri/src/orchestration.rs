@@ -0,0 +1,325 @@
+//! Lua-scriptable multi-step node execution (`commands::execution::ExecuteNodeInput::script`).
+//! A script sequences host-provided `run(command, {name=, cwd=, env=})` calls (synchronous
+//! subprocess, returns `{exit_status, stdout, stderr}`) and `step(name)` markers, instead of
+//! a node being limited to exactly one agent invocation. Mirrors `sessions::checks`'s
+//! `mlua`-sandboxing approach (strip `os`/`io`/`require`/`dofile`/`loadfile`, run on its own
+//! thread since `mlua::Lua` isn't `Send`) but streams events out over a channel as the
+//! script runs instead of waiting for one final result, so the host side can emit
+//! `execution://step`/`execution://chunk` live.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle of a single step, reported as it starts and as it finishes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Started,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepEvent {
+    pub step_name: String,
+    pub status: StepStatus,
+    pub exit_code: Option<i32>,
+}
+
+/// What `run_script` reports back to its caller as the script executes.
+pub enum ScriptEvent {
+    Step(StepEvent),
+    /// One `run()` call's stdout or stderr, tagged with the step that was active when it
+    /// ran.
+    Output {
+        step: String,
+        stream: String,
+        chunk: String,
+    },
+}
+
+struct ScriptState {
+    current_step: String,
+    last_exit_code: Option<i32>,
+    /// Whether `current_step` has actually been reported `Started` yet -- deferred until
+    /// the first `step()` or `run()` call, so a script that does neither never emits a
+    /// dangling step for work that didn't happen.
+    started: bool,
+}
+
+/// Default step name for output produced before a script's first explicit `step()` call.
+const DEFAULT_STEP: &str = "main";
+
+/// Run `script` on a dedicated thread (see module docs for why) and return a channel of
+/// `ScriptEvent`s as it executes; the channel closes when the script finishes, whether it
+/// succeeded, errored, or raised a Lua error, so a caller can simply loop over it.
+pub fn spawn_script(script: String, cwd: Option<String>) -> Receiver<ScriptEvent> {
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+        if let Err(e) = run_script(&script, cwd.as_deref(), &tx) {
+            tracing::warn!("Orchestration script failed: {e}");
+        }
+    });
+    rx
+}
+
+fn run_script(script: &str, cwd: Option<&str>, tx: &Sender<ScriptEvent>) -> Result<(), String> {
+    let lua = mlua::Lua::new();
+    let globals = lua.globals();
+
+    // Sandbox: scripts shell out only through the `run` function below.
+    for dangerous in ["os", "io", "require", "dofile", "loadfile"] {
+        globals
+            .set(dangerous, mlua::Value::Nil)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let state = Rc::new(RefCell::new(ScriptState {
+        current_step: DEFAULT_STEP.to_string(),
+        last_exit_code: None,
+        started: false,
+    }));
+
+    {
+        let state = state.clone();
+        let tx = tx.clone();
+        let step_fn = lua
+            .create_function(move |_, name: String| {
+                enter_step(&state, &tx, name);
+                Ok(())
+            })
+            .map_err(|e| e.to_string())?;
+        globals.set("step", step_fn).map_err(|e| e.to_string())?;
+    }
+
+    {
+        let state = state.clone();
+        let tx = tx.clone();
+        let default_cwd = cwd.map(|c| c.to_string());
+        let run_fn = lua
+            .create_function(
+                move |lua, (command, opts): (String, Option<mlua::Table>)| {
+                    let mut run_cwd = default_cwd.clone();
+                    let mut env_pairs: Vec<(String, String)> = Vec::new();
+                    let mut run_name: Option<String> = None;
+                    if let Some(opts) = &opts {
+                        if let Ok(Some(c)) = opts.get::<_, Option<String>>("cwd") {
+                            run_cwd = Some(c);
+                        }
+                        if let Ok(Some(n)) = opts.get::<_, Option<String>>("name") {
+                            run_name = Some(n);
+                        }
+                        if let Ok(Some(env_table)) = opts.get::<_, Option<mlua::Table>>("env") {
+                            for pair in env_table.pairs::<String, String>() {
+                                if let Ok((k, v)) = pair {
+                                    env_pairs.push((k, v));
+                                }
+                            }
+                        }
+                    }
+
+                    // `{name=}` is shorthand for wrapping this single call in its own step,
+                    // equivalent to calling `step(name)` immediately before it.
+                    match run_name {
+                        Some(name) => enter_step(&state, &tx, name),
+                        None => ensure_started(&state, &tx),
+                    }
+
+                    let mut cmd = std::process::Command::new("sh");
+                    cmd.args(["-c", &command]);
+                    if let Some(dir) = &run_cwd {
+                        cmd.current_dir(dir);
+                    }
+                    for (k, v) in &env_pairs {
+                        cmd.env(k, v);
+                    }
+
+                    let output = cmd.output().map_err(mlua::Error::external)?;
+                    let exit_status = output.status.code().unwrap_or(-1);
+                    state.borrow_mut().last_exit_code = Some(exit_status);
+
+                    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+                    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+                    let step_name = state.borrow().current_step.clone();
+                    if !stdout.is_empty() {
+                        let _ = tx.send(ScriptEvent::Output {
+                            step: step_name.clone(),
+                            stream: "stdout".to_string(),
+                            chunk: stdout.clone(),
+                        });
+                    }
+                    if !stderr.is_empty() {
+                        let _ = tx.send(ScriptEvent::Output {
+                            step: step_name,
+                            stream: "stderr".to_string(),
+                            chunk: stderr.clone(),
+                        });
+                    }
+
+                    let result = lua.create_table()?;
+                    result.set("exit_status", exit_status)?;
+                    result.set("stdout", stdout)?;
+                    result.set("stderr", stderr)?;
+                    Ok(result)
+                },
+            )
+            .map_err(|e| e.to_string())?;
+        globals.set("run", run_fn).map_err(|e| e.to_string())?;
+    }
+
+    let exec_result = lua.load(script).exec();
+    finish_current_step(&state, &tx);
+    exec_result.map_err(|e| e.to_string())
+}
+
+/// Finish whatever step is current, then start `name` as the new current step and emit
+/// its `Started` event. Shared by the `step()` host function and `run()`'s `{name=}`
+/// shorthand.
+fn enter_step(state: &Rc<RefCell<ScriptState>>, tx: &Sender<ScriptEvent>, name: String) {
+    finish_current_step(state, tx);
+    let mut s = state.borrow_mut();
+    s.current_step = name.clone();
+    s.last_exit_code = None;
+    s.started = true;
+    drop(s);
+    let _ = tx.send(ScriptEvent::Step(StepEvent {
+        step_name: name,
+        status: StepStatus::Started,
+        exit_code: None,
+    }));
+}
+
+/// Emit `Started` for the current step the first time either `step()` or `run()` touches
+/// it (a script that never calls `step()` still gets one implicit `"main"` step).
+fn ensure_started(state: &Rc<RefCell<ScriptState>>, tx: &Sender<ScriptEvent>) {
+    let already_started = state.borrow().started;
+    if already_started {
+        return;
+    }
+    state.borrow_mut().started = true;
+    let step_name = state.borrow().current_step.clone();
+    let _ = tx.send(ScriptEvent::Step(StepEvent {
+        step_name,
+        status: StepStatus::Started,
+        exit_code: None,
+    }));
+}
+
+/// Emit `Succeeded`/`Failed` for whichever step is current, based on its last `run()`
+/// exit status (no `run()` calls at all counts as success). No-op if that step was never
+/// reported `Started` in the first place.
+fn finish_current_step(state: &Rc<RefCell<ScriptState>>, tx: &Sender<ScriptEvent>) {
+    let s = state.borrow();
+    if !s.started {
+        return;
+    }
+    let status = match s.last_exit_code {
+        Some(0) | None => StepStatus::Succeeded,
+        Some(_) => StepStatus::Failed,
+    };
+    let event = StepEvent {
+        step_name: s.current_step.clone(),
+        status,
+        exit_code: s.last_exit_code,
+    };
+    drop(s);
+    let _ = tx.send(ScriptEvent::Step(event));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(script: &str) -> Vec<ScriptEvent> {
+        spawn_script(script.to_string(), None).iter().collect()
+    }
+
+    fn step_events(events: &[ScriptEvent]) -> Vec<(&str, StepStatus, Option<i32>)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                ScriptEvent::Step(s) => Some((s.step_name.as_str(), s.status, s.exit_code)),
+                ScriptEvent::Output { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn script_with_no_run_or_step_emits_nothing() {
+        let events = run("local x = 1 + 1");
+        assert!(step_events(&events).is_empty());
+    }
+
+    #[test]
+    fn bare_run_gets_an_implicit_main_step() {
+        let events = run(r#"run("true")"#);
+        assert_eq!(
+            step_events(&events),
+            vec![("main", StepStatus::Started, None), ("main", StepStatus::Succeeded, Some(0))]
+        );
+    }
+
+    #[test]
+    fn explicit_steps_are_sequenced_in_order() {
+        let events = run(
+            r#"
+            step("first")
+            run("true")
+            step("second")
+            run("true")
+            "#,
+        );
+        assert_eq!(
+            step_events(&events),
+            vec![
+                ("first", StepStatus::Started, None),
+                ("first", StepStatus::Succeeded, Some(0)),
+                ("second", StepStatus::Started, None),
+                ("second", StepStatus::Succeeded, Some(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn nonzero_exit_marks_the_step_failed() {
+        let events = run(r#"run("exit 1")"#);
+        assert_eq!(
+            step_events(&events),
+            vec![("main", StepStatus::Started, None), ("main", StepStatus::Failed, Some(1))]
+        );
+    }
+
+    #[test]
+    fn run_with_name_opens_its_own_step_like_an_explicit_step_call() {
+        let events = run(r#"run("true", {name = "build"})"#);
+        assert_eq!(
+            step_events(&events),
+            vec![("build", StepStatus::Started, None), ("build", StepStatus::Succeeded, Some(0))]
+        );
+    }
+
+    #[test]
+    fn entering_a_new_step_finishes_the_previous_one_first() {
+        let events = run(
+            r#"
+            step("a")
+            run("exit 1")
+            step("b")
+            run("true")
+            "#,
+        );
+        assert_eq!(
+            step_events(&events),
+            vec![
+                ("a", StepStatus::Started, None),
+                ("a", StepStatus::Failed, Some(1)),
+                ("b", StepStatus::Started, None),
+                ("b", StepStatus::Succeeded, Some(0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Append-only, file-backed log of a session's `ExecutionChunkEvent`s.
+//!
+//! `execution://chunk`/`execution://step` events are transient: they're only seen by a
+//! window that's open and subscribed at the moment they're emitted. This module tees the
+//! same bytes to disk (one file per `session_id`, under the app-data logs directory) so a
+//! reopened window can reconstruct scrollback, and so a finished run stays auditable after
+//! its `RunningProcess` is gone. Rotates by size rather than time, since a session's
+//! lifetime (not wall-clock) is what bounds how much it can write.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+/// Rotate a session's log once its active file exceeds this size.
+const ROTATE_AT_BYTES: u64 = 10 * 1024 * 1024;
+/// Keep at most this many rotated-out generations (`.log.1` .. `.log.N`) per session,
+/// oldest dropped first.
+const MAX_ROTATED_GENERATIONS: u32 = 3;
+
+/// A session's last known outcome, as recorded by `SessionLogStore::record_status`.
+/// `None` fields mean the session is either still running or exited without the owning
+/// `execute_node` background task recording a status (e.g. the process was killed before
+/// `wait()` returned).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLogStatus {
+    pub session_id: String,
+    pub success: Option<bool>,
+    pub exit_code: Option<i32>,
+}
+
+/// Tees `ExecutionChunkEvent` bytes to per-session files on disk, with size-based
+/// rotation. Cheap to clone (an `Arc`'d directory path and a shared map of open file
+/// handles), so it's threaded through `commands::execution`'s pump tasks the same way
+/// `AppState`'s other shared state is.
+#[derive(Clone)]
+pub struct SessionLogStore {
+    dir: Arc<PathBuf>,
+    writers: Arc<Mutex<HashMap<String, Arc<Mutex<File>>>>>,
+}
+
+impl SessionLogStore {
+    pub fn new(dir: PathBuf) -> std::io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: Arc::new(dir),
+            writers: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    fn log_path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{session_id}.log"))
+    }
+
+    fn status_path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{session_id}.status.json"))
+    }
+
+    /// Append one tagged line (`[stream] <chunk>`) to `session_id`'s log file, opening and
+    /// caching the handle on first use. Errors are logged and swallowed -- a failure to
+    /// write the audit log shouldn't interrupt the live event stream this tees from.
+    pub fn append(&self, session_id: &str, stream: &str, chunk: &str) {
+        if chunk.is_empty() {
+            return;
+        }
+        if let Err(e) = self.try_append(session_id, stream, chunk) {
+            tracing::warn!("Failed to append to session log {session_id}: {e}");
+        }
+    }
+
+    fn try_append(&self, session_id: &str, stream: &str, chunk: &str) -> std::io::Result<()> {
+        let file = self.open_writer(session_id)?;
+        let mut file = file.lock().unwrap();
+
+        if file.metadata()?.len() > ROTATE_AT_BYTES {
+            self.rotate(session_id, &mut file)?;
+        }
+
+        for line in chunk.split_inclusive('\n') {
+            write!(file, "[{stream}] {line}")?;
+            if !line.ends_with('\n') {
+                writeln!(file)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_writer(&self, session_id: &str) -> std::io::Result<Arc<Mutex<File>>> {
+        let mut writers = self.writers.lock().unwrap();
+        if let Some(file) = writers.get(session_id) {
+            return Ok(file.clone());
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path(session_id))?;
+        let file = Arc::new(Mutex::new(file));
+        writers.insert(session_id.to_string(), file.clone());
+        Ok(file)
+    }
+
+    /// Shift `.log.N` -> `.log.N+1` (dropping anything past `MAX_ROTATED_GENERATIONS`),
+    /// copy the current log to `.log.1`, then truncate the still-open active handle in
+    /// place so `try_append`'s caller can keep writing to it without reopening.
+    fn rotate(&self, session_id: &str, file: &mut File) -> std::io::Result<()> {
+        let active = self.log_path(session_id);
+        let rotated = |n: u32| active.with_extension(format!("log.{n}"));
+
+        for n in (1..MAX_ROTATED_GENERATIONS).rev() {
+            let from = rotated(n);
+            if from.exists() {
+                fs::rename(&from, rotated(n + 1))?;
+            }
+        }
+        fs::copy(&active, rotated(1))?;
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+
+    /// Record a session's final outcome, so `list` can report it without the process
+    /// still being alive.
+    pub fn record_status(&self, session_id: &str, success: bool, exit_code: Option<i32>) {
+        let status = SessionLogStatus {
+            session_id: session_id.to_string(),
+            success: Some(success),
+            exit_code,
+        };
+        let path = self.status_path(session_id);
+        match serde_json::to_vec(&status) {
+            Ok(bytes) => {
+                if let Err(e) = fs::write(&path, bytes) {
+                    tracing::warn!("Failed to write session status {session_id}: {e}");
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize session status {session_id}: {e}"),
+        }
+    }
+
+    /// Read `session_id`'s captured log from `offset` to its current end, so a reattaching
+    /// client can resume instead of refetching everything it already has.
+    pub fn read_from(&self, session_id: &str, offset: u64) -> std::io::Result<Vec<u8>> {
+        let mut file = match File::open(self.log_path(session_id)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Every session with a captured log, with its final status if one was recorded.
+    /// Unordered -- callers that care about recency should sort by their own timestamps.
+    pub fn list(&self) -> std::io::Result<Vec<SessionLogStatus>> {
+        let mut sessions = Vec::new();
+        for entry in fs::read_dir(&*self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let Some(session_id) = session_id_from_log_path(&path) else {
+                continue;
+            };
+
+            let status = fs::read(self.status_path(&session_id))
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<SessionLogStatus>(&bytes).ok());
+
+            sessions.push(status.unwrap_or(SessionLogStatus {
+                session_id,
+                success: None,
+                exit_code: None,
+            }));
+        }
+        Ok(sessions)
+    }
+}
+
+/// Extract `session_id` from a `<session_id>.log` path, skipping rotated (`.log.N`) and
+/// status (`.status.json`) files.
+fn session_id_from_log_path(path: &Path) -> Option<String> {
+    if path.extension()? != "log" {
+        return None;
+    }
+    path.file_stem()?.to_str().map(str::to_string)
+}
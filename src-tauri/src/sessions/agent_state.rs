@@ -0,0 +1,269 @@
+//! Typed node lifecycle state. `Node::status` used to be a free-form `String`, reset on
+//! startup by `commands::projects::list_projects` with ad-hoc `== "running"` string
+//! comparisons; this module replaces that with a closed set of states, an explicit
+//! allowed-transition table, and a single channel (`StateReporter`) that every state
+//! change is sent through, so `AppState` persistence and the UI event stay in lockstep
+//! instead of each call site having to remember to do both.
+
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle state of a single graph node's execution.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentState {
+    Pending,
+    Starting,
+    Running,
+    AwaitingInput,
+    Checking,
+    Retrying,
+    Done,
+    Failed,
+    Blocked,
+}
+
+impl AgentState {
+    /// Whether `self -> next` is a transition this codebase is ever expected to make.
+    /// `state_sink` rejects anything else rather than persisting it, so a mis-wired call
+    /// site (e.g. jumping straight from `Pending` to `Done`) shows up as a warning log
+    /// instead of silent, unexplainable state in the UI. Resetting back to `Pending` is
+    /// always allowed, since that's also how a stuck node is manually re-armed.
+    pub fn can_transition(self, next: AgentState) -> bool {
+        use AgentState::*;
+        if next == Pending {
+            return true;
+        }
+        matches!(
+            (self, next),
+            (Pending, Starting)
+                | (Pending, Blocked)
+                | (Starting, Running)
+                | (Starting, Failed)
+                | (Running, AwaitingInput)
+                | (Running, Checking)
+                | (Running, Retrying)
+                | (Running, Done)
+                | (Running, Failed)
+                | (Running, Blocked)
+                | (AwaitingInput, Running)
+                | (AwaitingInput, Done)
+                | (AwaitingInput, Failed)
+                | (Checking, Done)
+                | (Checking, Failed)
+                | (Checking, Blocked)
+                | (Retrying, Running)
+                | (Retrying, Failed)
+        )
+    }
+}
+
+/// Extra detail carried alongside a transition into `AgentState::Retrying`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryInfo {
+    pub attempt: u32,
+    pub delay_ms: u64,
+}
+
+/// One state change to apply to a node. Sent over a `StateReporter` so `state_sink` can
+/// validate it against `AgentState::can_transition`, apply it to `AppState`, persist, and
+/// emit the matching UI event all in one place.
+#[derive(Debug, Clone)]
+pub struct StateTransition {
+    pub project_id: String,
+    pub node_id: String,
+    pub state: AgentState,
+    pub retry: Option<RetryInfo>,
+}
+
+/// Sending half of the node state-reporting channel. Cheap to clone; every scheduler or
+/// executor that needs to report a transition gets its own clone rather than reaching
+/// into `AppState` directly.
+pub type StateReporter = tokio::sync::mpsc::UnboundedSender<StateTransition>;
+
+/// Send `state` for `node_id`, logging (rather than propagating) a send failure -- the
+/// only way `send` fails is if `state_sink`'s task has already ended, which isn't
+/// something a caller mid-execution can usefully react to.
+pub fn report(
+    reporter: &StateReporter,
+    project_id: &str,
+    node_id: &str,
+    state: AgentState,
+    retry: Option<RetryInfo>,
+) {
+    let transition = StateTransition {
+        project_id: project_id.to_string(),
+        node_id: node_id.to_string(),
+        state,
+        retry,
+    };
+    if reporter.send(transition).is_err() {
+        tracing::warn!(
+            "Dropped state transition for node {} ({:?}): state sink is gone",
+            node_id,
+            state
+        );
+    }
+}
+
+/// Drain `rx` and apply each transition to `app_state.projects`, persisting and emitting
+/// a `"session://state"` event for every one that's actually allowed by
+/// `AgentState::can_transition`. Runs until the sender side is dropped (i.e. never, in
+/// practice -- `AppState` holds a clone for the app's whole lifetime).
+pub fn start_state_sink(
+    window: tauri::WebviewWindow,
+    app_state: crate::state::AppState,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<StateTransition>,
+) {
+    use tauri::Emitter;
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(transition) = rx.recv().await {
+            let StateTransition {
+                project_id,
+                node_id,
+                state,
+                retry,
+            } = transition;
+
+            let applied = {
+                let mut projects = app_state.projects.write().await;
+                match projects.get_mut(&project_id) {
+                    Some(project) => {
+                        let current = project.nodes.iter().find(|n| n.id == node_id).map(|n| n.status);
+                        match current {
+                            Some(current) if current.can_transition(state) => {
+                                if let Some(node) =
+                                    project.nodes.iter_mut().find(|n| n.id == node_id)
+                                {
+                                    node.status = state;
+                                }
+                                project.updated_at = chrono::Utc::now().timestamp_millis();
+                                true
+                            }
+                            Some(current) => {
+                                tracing::warn!(
+                                    "Rejecting node {} transition {:?} -> {:?}: not allowed",
+                                    node_id,
+                                    current,
+                                    state
+                                );
+                                false
+                            }
+                            None => false,
+                        }
+                    }
+                    None => false,
+                }
+            };
+
+            if !applied {
+                continue;
+            }
+
+            if let Err(e) = app_state.persist_projects().await {
+                tracing::warn!(
+                    "Failed to persist node {} status ({:?}) for project {}: {}",
+                    node_id,
+                    state,
+                    project_id,
+                    e
+                );
+            }
+
+            if let Err(e) = window.emit(
+                "session://state",
+                AgentStateEvent {
+                    project_id,
+                    node_id,
+                    state,
+                    retry,
+                },
+            ) {
+                tracing::warn!("Failed to emit node state event: {}", e);
+            }
+        }
+    });
+}
+
+/// UI-facing mirror of a `StateTransition`, emitted on `"session://state"`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentStateEvent {
+    pub project_id: String,
+    pub node_id: String,
+    pub state: AgentState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_STATES: [AgentState; 9] = [
+        AgentState::Pending,
+        AgentState::Starting,
+        AgentState::Running,
+        AgentState::AwaitingInput,
+        AgentState::Checking,
+        AgentState::Retrying,
+        AgentState::Done,
+        AgentState::Failed,
+        AgentState::Blocked,
+    ];
+
+    #[test]
+    fn any_state_can_reset_to_pending() {
+        for state in ALL_STATES {
+            assert!(state.can_transition(AgentState::Pending), "{state:?} -> Pending");
+        }
+    }
+
+    #[test]
+    fn allowed_transitions() {
+        use AgentState::*;
+        let allowed = [
+            (Pending, Starting),
+            (Pending, Blocked),
+            (Starting, Running),
+            (Starting, Failed),
+            (Running, AwaitingInput),
+            (Running, Checking),
+            (Running, Retrying),
+            (Running, Done),
+            (Running, Failed),
+            (Running, Blocked),
+            (AwaitingInput, Running),
+            (AwaitingInput, Done),
+            (AwaitingInput, Failed),
+            (Checking, Done),
+            (Checking, Failed),
+            (Checking, Blocked),
+            (Retrying, Running),
+            (Retrying, Failed),
+        ];
+        for (from, to) in allowed {
+            assert!(from.can_transition(to), "{from:?} -> {to:?} should be allowed");
+        }
+    }
+
+    #[test]
+    fn disallowed_transitions_are_rejected() {
+        use AgentState::*;
+        let disallowed = [
+            (Pending, Done),
+            (Pending, Running),
+            (Starting, Done),
+            (Starting, AwaitingInput),
+            (Done, Running),
+            (Failed, Running),
+            (Blocked, Running),
+            (Checking, Running),
+            (AwaitingInput, Blocked),
+        ];
+        for (from, to) in disallowed {
+            assert!(!from.can_transition(to), "{from:?} -> {to:?} should not be allowed");
+        }
+    }
+}
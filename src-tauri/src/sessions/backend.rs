@@ -0,0 +1,114 @@
+//! Abstraction over the transport an interactive session runs on top of. `tmux` (see
+//! `sessions::tmux`) is the default where it's installed; `sessions::pty` is a native
+//! pseudo-terminal fallback (via `portable-pty`) for machines without it, and is also
+//! the only backend that can report/propagate terminal window size.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub struct BackendError(pub String);
+
+impl From<crate::sessions::tmux::TmuxError> for BackendError {
+    fn from(e: crate::sessions::tmux::TmuxError) -> Self {
+        Self(e.0)
+    }
+}
+
+/// Which `InteractiveBackend` a session was created on. Stored on `Session` so later
+/// operations (capture/send/resize/kill) know which backend to dispatch to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractiveBackendKind {
+    Tmux,
+    Pty,
+}
+
+impl InteractiveBackendKind {
+    /// Lowercase name for persistence (`db::Session::backend`) and logging.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InteractiveBackendKind::Tmux => "tmux",
+            InteractiveBackendKind::Pty => "pty",
+        }
+    }
+}
+
+/// Surface every interactive session backend must implement, regardless of how it's
+/// actually hosting the agent process (a tmux pane, a native PTY, ...).
+pub trait InteractiveBackend: Send + Sync {
+    fn create_session(
+        &self,
+        session_id: &str,
+        command: &str,
+        cwd: Option<&str>,
+    ) -> Result<(), BackendError>;
+
+    fn capture_output(&self, session_id: &str, lines: usize) -> Result<String, BackendError>;
+
+    fn send_input(&self, session_id: &str, input: &str) -> Result<(), BackendError>;
+
+    /// Propagate a terminal window-size change to the session's child process.
+    fn resize(&self, session_id: &str, rows: u16, cols: u16) -> Result<(), BackendError>;
+
+    fn kill_session(&self, session_id: &str) -> Result<(), BackendError>;
+
+    /// Whether the session's underlying process/pane is still alive.
+    fn session_exists(&self, session_id: &str) -> bool;
+
+    /// PID of the process running the agent, for sending it signals directly
+    /// (pause/resume via SIGSTOP/SIGCONT, `OnBusyUpdate::Signal`/`Restart`).
+    fn pane_pid(&self, session_id: &str) -> Result<i32, BackendError>;
+
+    /// Shell command a user could run to attach an external terminal to this session,
+    /// if one exists (tmux does; a native PTY has no separate server to attach to).
+    /// When `detach` is set, the command kicks any other client already attached to
+    /// this session off first (remux-style "take over" rather than sharing a view).
+    fn attach_command(&self, session_id: &str, detach: bool) -> Option<String>;
+}
+
+/// `InteractiveBackend` implemented on top of the existing `sessions::tmux` module.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TmuxBackend;
+
+impl InteractiveBackend for TmuxBackend {
+    fn create_session(
+        &self,
+        session_id: &str,
+        command: &str,
+        cwd: Option<&str>,
+    ) -> Result<(), BackendError> {
+        Ok(crate::sessions::tmux::create_session(session_id, command, cwd)?)
+    }
+
+    fn capture_output(&self, session_id: &str, lines: usize) -> Result<String, BackendError> {
+        Ok(crate::sessions::tmux::capture_pane(session_id, lines)?)
+    }
+
+    fn send_input(&self, session_id: &str, input: &str) -> Result<(), BackendError> {
+        Ok(crate::sessions::tmux::send_keys(session_id, input)?)
+    }
+
+    fn resize(&self, _session_id: &str, _rows: u16, _cols: u16) -> Result<(), BackendError> {
+        // tmux panes size themselves off the attached client; there's no send-keys
+        // equivalent to push a size to a detached session.
+        Err(BackendError(
+            "resize is not supported on the tmux backend".to_string(),
+        ))
+    }
+
+    fn kill_session(&self, session_id: &str) -> Result<(), BackendError> {
+        Ok(crate::sessions::tmux::kill_session(session_id)?)
+    }
+
+    fn session_exists(&self, session_id: &str) -> bool {
+        crate::sessions::tmux::session_exists(session_id)
+    }
+
+    fn pane_pid(&self, session_id: &str) -> Result<i32, BackendError> {
+        Ok(crate::sessions::tmux::pane_pid(session_id)?)
+    }
+
+    fn attach_command(&self, session_id: &str, detach: bool) -> Option<String> {
+        Some(crate::sessions::tmux::get_attach_command(session_id, detach))
+    }
+}
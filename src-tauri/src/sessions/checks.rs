@@ -1,9 +1,21 @@
+use glob::Pattern;
+use mlua::Lua;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+/// Wall-clock budget for a `Check::Script` run, so a misbehaving or infinite-looping
+/// script can't hang the check pipeline.
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Represents a check to validate after an agent completes
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -44,6 +56,21 @@ pub enum Check {
         #[serde(rename = "maxRetries")]
         max_retries: Option<u32>,
     },
+    /// Evaluated with `mlua` against a `node` table (`output`, `exit_code`, `session_id`,
+    /// `node_id`, `cwd`) and must return `{ passed = bool, message = string }`. The script
+    /// gets `run(cmd)` (shells out, returns `{exit_code, stdout, stderr}`), `regex_match(text,
+    /// pattern)`, `read_file(path)` (returns the file's contents, or `nil` if it can't be
+    /// read), and `path_exists(path)`, with relative paths resolved against `node.cwd`;
+    /// everything else dangerous (`os`, `io`, `require`, `dofile`, `loadfile`) is stripped
+    /// from its globals before it runs.
+    Script {
+        id: String,
+        lua: String,
+        #[serde(rename = "autoRetry")]
+        auto_retry: Option<bool>,
+        #[serde(rename = "maxRetries")]
+        max_retries: Option<u32>,
+    },
 }
 
 impl Check {
@@ -68,12 +95,28 @@ impl Check {
                 auto_retry,
                 max_retries,
                 ..
+            }
+            | Check::Script {
+                auto_retry,
+                max_retries,
+                ..
             } => (auto_retry.unwrap_or(false), max_retries.unwrap_or(2).min(10)),
             Check::HumanApproval { .. } => (false, 0),
         }
     }
 }
 
+/// Everything a check might need beyond a working directory. Most check types only ever
+/// look at `cwd`; `Check::Script` is the one that gets the rest, as Lua globals.
+#[derive(Debug, Clone, Default)]
+pub struct CheckContext {
+    pub cwd: Option<String>,
+    pub output: String,
+    pub exit_code: i32,
+    pub session_id: String,
+    pub node_id: String,
+}
+
 /// Result of running a single check
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -82,19 +125,103 @@ pub struct CheckResult {
     pub check_type: String,
     pub passed: bool,
     pub message: Option<String>,
+    /// Per-test counts, populated only for a `Check::TestRunner` run whose framework we
+    /// could parse structured results from; `None` otherwise (unknown framework, a
+    /// framework with no structured parser such as `npm`, or output that didn't parse).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests_passed: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests_failed: Option<u32>,
+    /// Names of the tests that failed, when `tests_failed` is known.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
 }
 
-/// Run all checks and return results
-pub fn run_checks(checks: &[Check], cwd: Option<&str>) -> Vec<CheckResult> {
-    checks.iter().map(|check| run_single_check(check, cwd)).collect()
+/// Parsed outcome of a structured-mode test run (`cargo test --format json`, `jest
+/// --json`, `pytest --json-report`), used by `Check::TestRunner` to report which tests
+/// failed instead of just an exit code.
+struct TestOutcome {
+    passed: u32,
+    failed: u32,
+    failures: Vec<String>,
+}
+
+/// Number of checks `run_checks` runs at once by default.
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Run all checks and return results, in the same order as `checks` regardless of
+/// completion order. Dispatches up to `DEFAULT_CONCURRENCY` at a time; see
+/// `run_checks_concurrent` for control over that limit and reproducible shuffling.
+pub fn run_checks(checks: &[Check], ctx: &CheckContext) -> Vec<CheckResult> {
+    run_checks_concurrent(checks, ctx, DEFAULT_CONCURRENCY, None)
+}
+
+/// Like `run_checks`, but lets the caller cap how many checks run at once (each check
+/// shells out via blocking `std::process::Command`, so this dispatches onto a small pool
+/// of worker threads rather than the caller's own thread) and optionally shuffle
+/// dispatch order first via a `seed` (`SmallRng::seed_from_u64`). Shuffling reproducibly
+/// flushes out hidden ordering dependencies between checks -- e.g. one check's command
+/// mutating a file another reads -- without making a flaky run impossible to replay.
+///
+/// `Check::HumanApproval` checks need a person at a UI, so they're always run serially,
+/// in their original order, on the calling thread before any worker touches the rest.
+pub fn run_checks_concurrent(
+    checks: &[Check],
+    ctx: &CheckContext,
+    concurrency: usize,
+    seed: Option<u64>,
+) -> Vec<CheckResult> {
+    let mut results: Vec<Option<CheckResult>> = vec![None; checks.len()];
+
+    let mut concurrent_indices = Vec::new();
+    for (idx, check) in checks.iter().enumerate() {
+        if matches!(check, Check::HumanApproval { .. }) {
+            results[idx] = Some(run_single_check(check, ctx));
+        } else {
+            concurrent_indices.push(idx);
+        }
+    }
+
+    if let Some(seed) = seed {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        concurrent_indices.shuffle(&mut rng);
+    }
+
+    let next = AtomicUsize::new(0);
+    let slots: Vec<Mutex<Option<CheckResult>>> =
+        concurrent_indices.iter().map(|_| Mutex::new(None)).collect();
+    let worker_count = concurrency.max(1).min(concurrent_indices.len().max(1));
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            scope.spawn(|| loop {
+                let slot = next.fetch_add(1, Ordering::SeqCst);
+                if slot >= concurrent_indices.len() {
+                    break;
+                }
+                let check_idx = concurrent_indices[slot];
+                let result = run_single_check(&checks[check_idx], ctx);
+                *slots[slot].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    for (slot, check_idx) in concurrent_indices.into_iter().enumerate() {
+        results[check_idx] = slots[slot].lock().unwrap().take();
+    }
+
+    results
+        .into_iter()
+        .map(|r| r.expect("every index is dispatched exactly once, either inline or on a worker"))
+        .collect()
 }
 
 /// Run a single check and return its result
-pub fn run_single_check(check: &Check, cwd: Option<&str>) -> CheckResult {
+pub fn run_single_check(check: &Check, ctx: &CheckContext) -> CheckResult {
     let (auto_retry, max_retries) = check.retry_config();
     let mut attempt = 0u32;
     loop {
-        let result = run_single_check_once(check, cwd);
+        let result = run_single_check_once(check, ctx);
         if result.passed {
             return result;
         }
@@ -107,7 +234,198 @@ pub fn run_single_check(check: &Check, cwd: Option<&str>) -> CheckResult {
     }
 }
 
-fn run_single_check_once(check: &Check, cwd: Option<&str>) -> CheckResult {
+/// Debounce window for coalescing a burst of filesystem events (e.g. an editor's
+/// write-then-rename save, or an agent touching several files in one go) into one re-run.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// One file a node expects to produce, parsed from `Node::deliverables`' free-form JSON
+/// the same way `Check` is parsed from `Node::checks` -- entries that don't match this
+/// shape are silently skipped rather than failing the whole list. `run_checks_watch` uses
+/// `path` both to watch for the file's arrival and to decide which non-path-based checks
+/// (e.g. `Check::Command`) should re-run once it shows up.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deliverable {
+    pub id: String,
+    pub path: String,
+}
+
+/// Handle to a `run_checks_watch` loop. Dropping it (or calling `stop`) tears down the
+/// filesystem watcher and lets its background thread exit.
+pub struct WatchHandle {
+    stop: Arc<AtomicBool>,
+    _watcher: RecommendedWatcher,
+}
+
+impl WatchHandle {
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Paths a single check cares about: `FileExists`/`Contains` watch their own resolved
+/// `path`; every other check type has no file of its own, so it only re-runs when a
+/// change matches one of `extra_globs` or a deliverable path.
+fn check_watch_paths(check: &Check, cwd: Option<&str>) -> Vec<PathBuf> {
+    match check {
+        Check::FileExists { path, .. } | Check::Contains { path, .. } => {
+            vec![resolve_path(path, cwd)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn check_is_affected(
+    check: &Check,
+    own_paths: &[PathBuf],
+    extra_patterns: &[Pattern],
+    changed: &[PathBuf],
+) -> bool {
+    // Human approval can't be auto-(re)evaluated; it always needs the explicit UI action.
+    if matches!(check, Check::HumanApproval { .. }) {
+        return false;
+    }
+    if !own_paths.is_empty() {
+        return changed
+            .iter()
+            .any(|c| own_paths.iter().any(|p| c == p || c.starts_with(p)));
+    }
+    if extra_patterns.is_empty() {
+        return false;
+    }
+    changed
+        .iter()
+        .any(|c| extra_patterns.iter().any(|pat| pat.matches_path(c)))
+}
+
+/// Like `run_checks`, but instead of running once, keeps the checks up to date as files
+/// change: every `FileExists`/`Check::Contains` path (resolved via `resolve_path`) plus
+/// `extra_globs` and every `deliverables` path is registered with a recursive filesystem
+/// watcher rooted at `ctx.cwd`, incoming events are debounced by `WATCH_DEBOUNCE`, and each
+/// debounced batch re-runs only the checks whose watched paths intersect the changed set —
+/// via `run_single_check_once`, so `auto_retry`/backoff (meant for one-shot
+/// `run_single_check`) don't also kick in here; the next filesystem event *is* the retry.
+///
+/// `on_deliverable` fires once per `deliverables` entry whose resolved path shows up in a
+/// debounced batch, before that batch's checks are re-run, so a caller can report "this
+/// deliverable just appeared" as a distinct event from "these checks just re-ran".
+///
+/// Runs an initial full pass (with `run_checks`, retries included) before watching starts.
+/// `on_results` is called once up front with that pass, then again after every re-run.
+pub fn run_checks_watch<F, D>(
+    checks: Vec<Check>,
+    ctx: CheckContext,
+    deliverables: Vec<Deliverable>,
+    extra_globs: Vec<String>,
+    on_results: F,
+    on_deliverable: D,
+) -> Result<WatchHandle, String>
+where
+    F: Fn(Vec<CheckResult>) + Send + 'static,
+    D: Fn(&Deliverable) + Send + 'static,
+{
+    on_results(run_checks(&checks, &ctx));
+
+    let cwd = ctx.cwd.clone();
+    let root = cwd.clone().unwrap_or_else(|| ".".to_string());
+
+    let resolved_deliverables: Vec<(Deliverable, PathBuf)> = deliverables
+        .into_iter()
+        .map(|d| {
+            let path = resolve_path(&d.path, cwd.as_deref());
+            (d, path)
+        })
+        .collect();
+
+    let extra_patterns: Vec<Pattern> = extra_globs
+        .iter()
+        .filter_map(|pattern| Pattern::new(pattern).ok())
+        .chain(
+            resolved_deliverables
+                .iter()
+                .filter_map(|(_, path)| Pattern::new(&path.to_string_lossy()).ok()),
+        )
+        .collect();
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| format!("Failed to create filesystem watcher: {e}"))?;
+
+    watcher
+        .watch(Path::new(&root), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {root}: {e}"))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+
+    thread::spawn(move || loop {
+        if thread_stop.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let Ok(first) = rx.recv_timeout(Duration::from_millis(500)) else {
+            continue;
+        };
+
+        let mut changed: Vec<PathBuf> = first.paths;
+        let deadline = std::time::Instant::now() + WATCH_DEBOUNCE;
+        while let Some(remaining) =
+            deadline.checked_duration_since(std::time::Instant::now())
+        {
+            match rx.recv_timeout(remaining) {
+                Ok(event) => changed.extend(event.paths),
+                Err(_) => break,
+            }
+        }
+
+        if thread_stop.load(Ordering::SeqCst) {
+            return;
+        }
+
+        for (deliverable, path) in &resolved_deliverables {
+            if changed.iter().any(|c| c == path) {
+                on_deliverable(deliverable);
+            }
+        }
+
+        let affected: Vec<Check> = checks
+            .iter()
+            .filter(|check| {
+                let own_paths = check_watch_paths(check, cwd.as_deref());
+                check_is_affected(check, &own_paths, &extra_patterns, &changed)
+            })
+            .cloned()
+            .collect();
+
+        if affected.is_empty() {
+            continue;
+        }
+
+        let results = affected
+            .iter()
+            .map(|check| run_single_check_once(check, &ctx))
+            .collect();
+        on_results(results);
+    });
+
+    Ok(WatchHandle {
+        stop,
+        _watcher: watcher,
+    })
+}
+
+fn run_single_check_once(check: &Check, ctx: &CheckContext) -> CheckResult {
+    let cwd = ctx.cwd.as_deref();
     match check {
         Check::FileExists { id, path, .. } => {
             let full_path = resolve_path(path, cwd);
@@ -121,6 +439,9 @@ fn run_single_check_once(check: &Check, cwd: Option<&str>) -> CheckResult {
                 } else {
                     Some(format!("File not found: {}", path))
                 },
+                tests_passed: None,
+                tests_failed: None,
+                failures: Vec::new(),
             }
         }
 
@@ -149,12 +470,18 @@ fn run_single_check_once(check: &Check, cwd: Option<&str>) -> CheckResult {
                             stderr.to_string()
                         })
                     },
+                    tests_passed: None,
+                    tests_failed: None,
+                    failures: Vec::new(),
                 },
                 Err(e) => CheckResult {
                     id: id.clone(),
                     check_type: "command".into(),
                     passed: false,
                     message: Some(format!("Failed to execute command: {}", e)),
+                    tests_passed: None,
+                    tests_failed: None,
+                    failures: Vec::new(),
                 },
             }
         }
@@ -173,6 +500,9 @@ fn run_single_check_once(check: &Check, cwd: Option<&str>) -> CheckResult {
                         } else {
                             Some(format!("Pattern '{}' not found in {}", pattern, path))
                         },
+                        tests_passed: None,
+                        tests_failed: None,
+                        failures: Vec::new(),
                     }
                 }
                 Err(e) => CheckResult {
@@ -180,6 +510,9 @@ fn run_single_check_once(check: &Check, cwd: Option<&str>) -> CheckResult {
                     check_type: "contains".into(),
                     passed: false,
                     message: Some(format!("Failed to read file {}: {}", path, e)),
+                    tests_passed: None,
+                    tests_failed: None,
+                    failures: Vec::new(),
                 },
             }
         }
@@ -192,37 +525,336 @@ fn run_single_check_once(check: &Check, cwd: Option<&str>) -> CheckResult {
                 check_type: "human_approval".into(),
                 passed: false,
                 message: Some("Awaiting human approval".into()),
+                tests_passed: None,
+                tests_failed: None,
+                failures: Vec::new(),
             }
         }
 
-        Check::TestRunner { id, framework, .. } => {
-            let cmd = match framework.as_str() {
-                "npm" => "npm test",
-                "pytest" => "pytest",
-                "jest" => "npx jest",
-                "cargo" => "cargo test",
-                _ => {
-                    return CheckResult {
-                        id: id.clone(),
-                        check_type: "test_runner".into(),
-                        passed: false,
-                        message: Some(format!("Unknown test framework: {}", framework)),
-                    };
-                }
-            };
+        Check::TestRunner { id, framework, .. } => run_test_runner_check(id, framework, cwd),
 
-            // Delegate to command check logic
-            let temp_check = Check::Command {
+        Check::Script { id, lua, .. } => match run_lua_script(lua, ctx) {
+            Ok((passed, message)) => CheckResult {
                 id: id.clone(),
-                cmd: cmd.to_string(),
-                auto_retry: None,
-                max_retries: None,
+                check_type: "script".into(),
+                passed,
+                message,
+                tests_passed: None,
+                tests_failed: None,
+                failures: Vec::new(),
+            },
+            Err(e) => CheckResult {
+                id: id.clone(),
+                check_type: "script".into(),
+                passed: false,
+                message: Some(format!("Lua script error: {e}")),
+                tests_passed: None,
+                tests_failed: None,
+                failures: Vec::new(),
+            },
+        },
+    }
+}
+
+/// Run a `Check::TestRunner`: invoke the framework in its structured/JSON reporting mode
+/// and parse per-test results so failures can be surfaced by name, falling back to plain
+/// exit-code pass/fail (like `Check::Command`) when the framework has no structured parser
+/// (`npm`) or its structured output doesn't parse (wrong plugin/version installed, mixed
+/// stdout, etc). An unknown framework fails the check without running anything.
+fn run_test_runner_check(id: &str, framework: &str, cwd: Option<&str>) -> CheckResult {
+    let pytest_report_path = std::env::temp_dir().join(format!(
+        "orchestra-pytest-report-{}.json",
+        uuid::Uuid::new_v4()
+    ));
+
+    let cmd = match framework {
+        "npm" => "npm test".to_string(),
+        "pytest" => format!(
+            "pytest --json-report --json-report-file={}",
+            pytest_report_path.display()
+        ),
+        "jest" => "npx jest --json".to_string(),
+        "cargo" => "cargo test -- -Z unstable-options --format json".to_string(),
+        _ => {
+            return CheckResult {
+                id: id.to_string(),
+                check_type: "test_runner".into(),
+                passed: false,
+                message: Some(format!("Unknown test framework: {}", framework)),
+                tests_passed: None,
+                tests_failed: None,
+                failures: Vec::new(),
+            };
+        }
+    };
+
+    let mut command = Command::new("sh");
+    command.args(["-c", &cmd]);
+    if let Some(dir) = cwd {
+        command.current_dir(dir);
+    }
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => {
+            return CheckResult {
+                id: id.to_string(),
+                check_type: "test_runner".into(),
+                passed: false,
+                message: Some(format!("Failed to execute command: {}", e)),
+                tests_passed: None,
+                tests_failed: None,
+                failures: Vec::new(),
             };
-            let mut result = run_single_check_once(&temp_check, cwd);
-            result.check_type = "test_runner".into();
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let outcome = match framework {
+        "cargo" => parse_cargo_test_json(&stdout),
+        "jest" => parse_jest_json(&stdout),
+        "pytest" => {
+            let outcome = parse_pytest_json_report(&pytest_report_path);
+            let _ = std::fs::remove_file(&pytest_report_path);
+            outcome
+        }
+        _ => None,
+    };
+
+    match outcome {
+        Some(outcome) => CheckResult {
+            id: id.to_string(),
+            check_type: "test_runner".into(),
+            passed: outcome.failed == 0 && output.status.success(),
+            message: if outcome.failed == 0 {
+                None
+            } else {
+                Some(format!(
+                    "{} test(s) failed: {}",
+                    outcome.failed,
+                    outcome.failures.join(", ")
+                ))
+            },
+            tests_passed: Some(outcome.passed),
+            tests_failed: Some(outcome.failed),
+            failures: outcome.failures,
+        },
+        None => CheckResult {
+            id: id.to_string(),
+            check_type: "test_runner".into(),
+            passed: output.status.success(),
+            message: if output.status.success() {
+                None
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                Some(if stderr.is_empty() {
+                    format!(
+                        "Command exited with code {}",
+                        output.status.code().unwrap_or(-1)
+                    )
+                } else {
+                    stderr.to_string()
+                })
+            },
+            tests_passed: None,
+            tests_failed: None,
+            failures: Vec::new(),
+        },
+    }
+}
+
+/// Parse `cargo test -- --format json` output: one JSON object per line, with per-test
+/// events shaped like `{"type":"test","event":"ok"|"failed","name":"..."}`.
+fn parse_cargo_test_json(stdout: &str) -> Option<TestOutcome> {
+    let mut passed = 0u32;
+    let mut failed = 0u32;
+    let mut failures = Vec::new();
+    let mut saw_any = false;
+
+    for line in stdout.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if value.get("type").and_then(|v| v.as_str()) != Some("test") {
+            continue;
+        }
+        match value.get("event").and_then(|v| v.as_str()) {
+            Some("ok") => {
+                passed += 1;
+                saw_any = true;
+            }
+            Some("failed") => {
+                failed += 1;
+                saw_any = true;
+                if let Some(name) = value.get("name").and_then(|v| v.as_str()) {
+                    failures.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    saw_any.then_some(TestOutcome {
+        passed,
+        failed,
+        failures,
+    })
+}
+
+/// Parse `jest --json` output: a single JSON object on stdout with `numPassedTests`/
+/// `numFailedTests` and per-assertion results nested under `testResults`.
+fn parse_jest_json(stdout: &str) -> Option<TestOutcome> {
+    let value: serde_json::Value = serde_json::from_str(stdout.trim()).ok()?;
+    let passed = value.get("numPassedTests")?.as_u64()? as u32;
+    let failed = value.get("numFailedTests")?.as_u64()? as u32;
+
+    let failures = value
+        .get("testResults")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|suite| suite.get("assertionResults").and_then(|v| v.as_array()))
+        .flatten()
+        .filter(|assertion| assertion.get("status").and_then(|s| s.as_str()) == Some("failed"))
+        .filter_map(|assertion| {
+            assertion
+                .get("fullName")
+                .and_then(|n| n.as_str())
+                .map(|s| s.to_string())
+        })
+        .collect();
+
+    Some(TestOutcome {
+        passed,
+        failed,
+        failures,
+    })
+}
+
+/// Parse a `pytest-json-report`-style report file: a `summary` object with `passed`/
+/// `failed` counts and a `tests` array with each test's `nodeid`/`outcome`.
+fn parse_pytest_json_report(path: &Path) -> Option<TestOutcome> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let summary = value.get("summary")?;
+    let passed = summary.get("passed").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    let failed = summary.get("failed").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+    let failures = value
+        .get("tests")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|t| t.get("outcome").and_then(|o| o.as_str()) == Some("failed"))
+        .filter_map(|t| t.get("nodeid").and_then(|n| n.as_str()).map(|s| s.to_string()))
+        .collect();
+
+    Some(TestOutcome {
+        passed,
+        failed,
+        failures,
+    })
+}
+
+/// Evaluate a `Check::Script` body in a sandboxed Lua interpreter: `os`/`io`/`require`/
+/// `dofile`/`loadfile` are stripped from its globals, it's given a `node` table and
+/// `run`/`regex_match`/`read_file`/`path_exists` host functions, and it's run on a worker
+/// thread so `SCRIPT_TIMEOUT` can be enforced regardless of what the script does.
+fn run_lua_script(script: &str, ctx: &CheckContext) -> Result<(bool, Option<String>), String> {
+    let script = script.to_string();
+    let cwd = ctx.cwd.clone();
+    let output = ctx.output.clone();
+    let exit_code = ctx.exit_code;
+    let session_id = ctx.session_id.clone();
+    let node_id = ctx.node_id.clone();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = thread::spawn(move || {
+        let result = eval_lua_script(&script, cwd, &output, exit_code, &session_id, &node_id)
+            .map_err(|e| e.to_string());
+        let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(SCRIPT_TIMEOUT) {
+        Ok(result) => {
+            let _ = handle.join();
             result
         }
+        Err(_) => Err(format!(
+            "script exceeded {}s timeout",
+            SCRIPT_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+fn eval_lua_script(
+    script: &str,
+    cwd: Option<String>,
+    output: &str,
+    exit_code: i32,
+    session_id: &str,
+    node_id: &str,
+) -> mlua::Result<(bool, Option<String>)> {
+    let lua = Lua::new();
+    let globals = lua.globals();
+
+    // Sandbox: scripts shell out only through the `run` function we provide below.
+    for dangerous in ["os", "io", "require", "dofile", "loadfile"] {
+        globals.set(dangerous, mlua::Value::Nil)?;
     }
+
+    let node = lua.create_table()?;
+    node.set("output", output.to_string())?;
+    node.set("exit_code", exit_code)?;
+    node.set("session_id", session_id.to_string())?;
+    node.set("node_id", node_id.to_string())?;
+    node.set("cwd", cwd.clone().unwrap_or_default())?;
+    globals.set("node", node)?;
+
+    let run_cwd = cwd.clone();
+    let run_fn = lua.create_function(move |lua, cmd: String| {
+        let mut command = Command::new("sh");
+        command.args(["-c", &cmd]);
+        if let Some(dir) = &run_cwd {
+            command.current_dir(dir);
+        }
+        let output = command.output().map_err(mlua::Error::external)?;
+        let result = lua.create_table()?;
+        result.set("exit_code", output.status.code().unwrap_or(-1))?;
+        result.set("stdout", String::from_utf8_lossy(&output.stdout).to_string())?;
+        result.set("stderr", String::from_utf8_lossy(&output.stderr).to_string())?;
+        Ok(result)
+    })?;
+    globals.set("run", run_fn)?;
+
+    let regex_match_fn = lua.create_function(|_, (text, pattern): (String, String)| {
+        let re = regex::Regex::new(&pattern).map_err(mlua::Error::external)?;
+        Ok(re.is_match(&text))
+    })?;
+    globals.set("regex_match", regex_match_fn)?;
+
+    let read_file_cwd = cwd.clone();
+    let read_file_fn = lua.create_function(move |_, path: String| {
+        let resolved = resolve_path(&path, read_file_cwd.as_deref());
+        Ok(std::fs::read_to_string(resolved).ok())
+    })?;
+    globals.set("read_file", read_file_fn)?;
+
+    let path_exists_cwd = cwd.clone();
+    let path_exists_fn = lua.create_function(move |_, path: String| {
+        let resolved = resolve_path(&path, path_exists_cwd.as_deref());
+        Ok(resolved.exists())
+    })?;
+    globals.set("path_exists", path_exists_fn)?;
+
+    let result: mlua::Table = lua.load(script).eval()?;
+    let passed: bool = result.get("passed").unwrap_or(false);
+    let message: Option<String> = result.get("message").ok();
+    Ok((passed, message))
 }
 
 /// Resolve a path relative to the working directory
@@ -244,6 +876,13 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
 
+    fn ctx(cwd: Option<&str>) -> CheckContext {
+        CheckContext {
+            cwd: cwd.map(|s| s.to_string()),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_file_exists_check() {
         let dir = tempdir().unwrap();
@@ -257,7 +896,7 @@ mod tests {
             max_retries: None,
         };
 
-        let result = run_single_check(&check, Some(dir.path().to_str().unwrap()));
+        let result = run_single_check(&check, &ctx(Some(dir.path().to_str().unwrap())));
         assert!(result.passed);
         assert!(result.message.is_none());
     }
@@ -271,7 +910,7 @@ mod tests {
             max_retries: None,
         };
 
-        let result = run_single_check(&check, None);
+        let result = run_single_check(&check, &ctx(None));
         assert!(!result.passed);
         assert!(result.message.is_some());
     }
@@ -285,7 +924,7 @@ mod tests {
             max_retries: None,
         };
 
-        let result = run_single_check(&check, None);
+        let result = run_single_check(&check, &ctx(None));
         assert!(result.passed);
     }
 
@@ -298,7 +937,7 @@ mod tests {
             max_retries: None,
         };
 
-        let result = run_single_check(&check, None);
+        let result = run_single_check(&check, &ctx(None));
         assert!(!result.passed);
     }
 
@@ -316,7 +955,157 @@ mod tests {
             max_retries: None,
         };
 
-        let result = run_single_check(&check, Some(dir.path().to_str().unwrap()));
+        let result = run_single_check(&check, &ctx(Some(dir.path().to_str().unwrap())));
         assert!(result.passed);
     }
+
+    #[test]
+    fn test_script_check_sees_node_context_and_passes() {
+        let check = Check::Script {
+            id: "test".into(),
+            lua: "return { passed = node.exit_code == 0, message = node.session_id }".into(),
+            auto_retry: None,
+            max_retries: None,
+        };
+
+        let mut context = ctx(None);
+        context.exit_code = 0;
+        context.session_id = "orchestra-abc".into();
+
+        let result = run_single_check(&check, &context);
+        assert!(result.passed);
+        assert_eq!(result.message.as_deref(), Some("orchestra-abc"));
+    }
+
+    #[test]
+    fn test_script_check_sandbox_blocks_os_execute() {
+        let check = Check::Script {
+            id: "test".into(),
+            lua: "return { passed = os == nil }".into(),
+            auto_retry: None,
+            max_retries: None,
+        };
+
+        let result = run_single_check(&check, &ctx(None));
+        assert!(result.passed);
+    }
+
+    #[test]
+    fn test_script_check_read_file_and_path_exists() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("deliverable.json"), r#"{"ok":true}"#).unwrap();
+
+        let check = Check::Script {
+            id: "test".into(),
+            lua: r#"
+                local contents = read_file("deliverable.json")
+                return {
+                    passed = path_exists("deliverable.json")
+                        and not path_exists("missing.json")
+                        and contents ~= nil
+                        and string.find(contents, "ok") ~= nil,
+                }
+            "#
+            .into(),
+            auto_retry: None,
+            max_retries: None,
+        };
+
+        let result = run_single_check(&check, &ctx(Some(dir.path().to_str().unwrap())));
+        assert!(result.passed);
+    }
+
+    #[test]
+    fn test_parse_cargo_test_json_collects_failure_names() {
+        let stdout = r#"
+            {"type":"suite","event":"started","test_count":2}
+            {"type":"test","event":"started","name":"it_works"}
+            {"type":"test","name":"it_works","event":"ok"}
+            {"type":"test","event":"started","name":"it_breaks"}
+            {"type":"test","name":"it_breaks","event":"failed"}
+            {"type":"suite","event":"failed","test_count":2}
+        "#;
+
+        let outcome = parse_cargo_test_json(stdout).expect("should parse");
+        assert_eq!(outcome.passed, 1);
+        assert_eq!(outcome.failed, 1);
+        assert_eq!(outcome.failures, vec!["it_breaks".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_cargo_test_json_rejects_non_json_output() {
+        assert!(parse_cargo_test_json("running 2 tests\ntest result: ok").is_none());
+    }
+
+    #[test]
+    fn test_parse_jest_json_collects_failure_names() {
+        let stdout = r#"{
+            "numPassedTests": 1,
+            "numFailedTests": 1,
+            "testResults": [{
+                "assertionResults": [
+                    {"fullName": "adds numbers", "status": "passed"},
+                    {"fullName": "subtracts numbers", "status": "failed"}
+                ]
+            }]
+        }"#;
+
+        let outcome = parse_jest_json(stdout).expect("should parse");
+        assert_eq!(outcome.passed, 1);
+        assert_eq!(outcome.failed, 1);
+        assert_eq!(outcome.failures, vec!["subtracts numbers".to_string()]);
+    }
+
+    #[test]
+    fn test_runner_fails_unknown_framework_without_test_counts() {
+        let check = Check::TestRunner {
+            id: "test".into(),
+            framework: "deno".into(),
+            auto_retry: None,
+            max_retries: None,
+        };
+
+        let result = run_single_check(&check, &ctx(None));
+        assert!(!result.passed);
+        assert!(result.tests_passed.is_none());
+        assert!(result.message.unwrap().contains("Unknown test framework"));
+    }
+
+    #[test]
+    fn run_checks_concurrent_preserves_input_order() {
+        let dir = tempdir().unwrap();
+        let checks: Vec<Check> = (0..8)
+            .map(|i| Check::Command {
+                id: format!("check-{i}"),
+                cmd: "true".into(),
+                auto_retry: None,
+                max_retries: None,
+            })
+            .collect();
+
+        let results = run_checks_concurrent(&checks, &ctx(dir.path().to_str()), 4, Some(42));
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        let expected: Vec<String> = (0..8).map(|i| format!("check-{i}")).collect();
+        assert_eq!(ids, expected);
+        assert!(results.iter().all(|r| r.passed));
+    }
+
+    #[test]
+    fn run_checks_concurrent_runs_human_approval_serially() {
+        let checks = vec![
+            Check::Command {
+                id: "cmd".into(),
+                cmd: "true".into(),
+                auto_retry: None,
+                max_retries: None,
+            },
+            Check::HumanApproval { id: "approve".into() },
+        ];
+
+        let results = run_checks_concurrent(&checks, &ctx(None), 4, None);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, "cmd");
+        assert_eq!(results[1].id, "approve");
+        assert!(!results[1].passed);
+    }
 }
@@ -48,3 +48,55 @@ pub struct SessionAwaitingInputClearedEvent {
     pub node_id: String,
     pub timestamp: i64,
 }
+
+/// Event emitted when a file matching one of a live session's `Node::deliverables`
+/// appears or changes, via `checks::run_checks_watch`. Distinct from
+/// `SessionChecksUpdatedEvent`, which carries the check re-run the same filesystem change
+/// also triggered.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliverableDetectedEvent {
+    pub session_id: String,
+    pub node_id: String,
+    pub deliverable_id: String,
+    pub path: String,
+    pub timestamp: i64,
+}
+
+/// Event emitted with the results of an incremental check re-run triggered by
+/// `checks::run_checks_watch` while a session is still live -- as opposed to
+/// `SessionCompletedEvent::check_results`, which only fires once the session itself has
+/// finished.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionChecksUpdatedEvent {
+    pub session_id: String,
+    pub node_id: String,
+    pub check_results: Vec<CheckResult>,
+}
+
+/// In-app notification stream payload, mirroring the row `Notifier` just persisted via
+/// `db::Database::record_notification`. Emitted for every row `Notifier` persists
+/// (events with no resolvable project, or whose write fails, get none), regardless of whether the project has any `NotifySink`s
+/// configured -- unlike those, this channel is always on.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationCreatedEvent {
+    pub id: String,
+    pub event_type: String,
+    pub project_id: String,
+    pub node_id: Option<String>,
+    pub message: String,
+    pub priority: String,
+    pub created_at: i64,
+}
+
+/// A chunk of live terminal output from a native PTY session, pushed by
+/// `commands::sessions::stream_session_output` so the UI's built-in terminal view can
+/// render it without polling `capture_session_output`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyOutputEvent {
+    pub session_id: String,
+    pub chunk: String,
+}
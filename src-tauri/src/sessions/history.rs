@@ -0,0 +1,373 @@
+//! SQLite-backed audit trail of completed interactive sessions.
+//!
+//! Unlike `SessionManager`, which only tracks sessions while they're live, `HistoryStore`
+//! persists every run's output, exit code, and check results so a node's current run can
+//! be diffed against its last one, and so a past run can be relaunched via `replay_run`.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+
+use super::checks::CheckResult;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Run not found: {0}")]
+    NotFound(String),
+}
+
+pub type HistoryResult<T> = Result<T, HistoryError>;
+
+/// Everything needed to relaunch the session that produced a run, mirroring
+/// `commands::sessions::CreateInteractiveSessionInput`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayRequest {
+    pub node_id: String,
+    pub agent: String,
+    pub model: Option<String>,
+    pub extra_args: Option<Vec<String>>,
+    pub prompt: String,
+    pub cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub id: String,
+    pub node_id: String,
+    pub project_id: Option<String>,
+    pub executor: String,
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub exit_code: i32,
+    pub success: bool,
+    pub output: String,
+    pub check_results: Vec<CheckResult>,
+    /// Present when this run came from a session we can relaunch (i.e. every run
+    /// recorded by the monitor); `None` would only happen for a hand-inserted row.
+    pub replay: Option<ReplayRequest>,
+}
+
+/// Thread-safe wrapper over the `runs`/`check_results` tables, following the same
+/// pattern as `db::Database`.
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    pub fn new(app: &AppHandle) -> HistoryResult<Self> {
+        let app_dir = app
+            .path()
+            .app_data_dir()
+            .expect("Failed to get app data directory");
+
+        std::fs::create_dir_all(&app_dir)?;
+
+        let db_path = app_dir.join("orchestra-history.db");
+        tracing::info!("Opening run history database at {:?}", db_path);
+
+        let conn = Connection::open(&db_path)?;
+        initialize_schema(&conn)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    #[cfg(test)]
+    pub fn new_in_memory() -> HistoryResult<Self> {
+        let conn = Connection::open_in_memory()?;
+        initialize_schema(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Record a completed run and its check results. Called by the monitor right
+    /// alongside `SessionCompletedEvent` emission.
+    pub fn record_run(
+        &self,
+        node_id: &str,
+        project_id: Option<&str>,
+        executor: &str,
+        started_at: i64,
+        finished_at: i64,
+        exit_code: i32,
+        success: bool,
+        output: &str,
+        check_results: &[CheckResult],
+        replay: Option<&ReplayRequest>,
+    ) -> HistoryResult<String> {
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let replay_json = replay.map(serde_json::to_string).transpose()?;
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO runs (id, node_id, project_id, executor, started_at, finished_at,
+                                exit_code, success, output, replay)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                &run_id,
+                node_id,
+                project_id,
+                executor,
+                started_at,
+                finished_at,
+                exit_code,
+                success,
+                output,
+                replay_json,
+            ],
+        )?;
+
+        for (idx, result) in check_results.iter().enumerate() {
+            let failures_json = if result.failures.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_string(&result.failures)?)
+            };
+
+            tx.execute(
+                "INSERT INTO check_results (run_id, idx, id, check_type, passed, message,
+                                             tests_passed, tests_failed, failures)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    &run_id,
+                    idx as i64,
+                    &result.id,
+                    &result.check_type,
+                    result.passed,
+                    &result.message,
+                    result.tests_passed,
+                    result.tests_failed,
+                    failures_json,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// List runs for a node, most recent first.
+    pub fn list_runs(&self, node_id: &str) -> HistoryResult<Vec<RunRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM runs WHERE node_id = ? ORDER BY started_at DESC",
+        )?;
+        let run_ids = stmt
+            .query_map([node_id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+        drop(conn);
+
+        run_ids
+            .iter()
+            .map(|id| self.get_run(id).map(|r| r.expect("run just listed by id")))
+            .collect()
+    }
+
+    /// Fetch a single run with its check results.
+    pub fn get_run(&self, run_id: &str) -> HistoryResult<Option<RunRecord>> {
+        let conn = self.conn.lock().unwrap();
+
+        let row = conn
+            .query_row(
+                "SELECT id, node_id, project_id, executor, started_at, finished_at,
+                        exit_code, success, output, replay
+                 FROM runs WHERE id = ?",
+                [run_id],
+                |row| {
+                    let replay_json: Option<String> = row.get(9)?;
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, i64>(4)?,
+                        row.get::<_, i64>(5)?,
+                        row.get::<_, i32>(6)?,
+                        row.get::<_, bool>(7)?,
+                        row.get::<_, String>(8)?,
+                        replay_json,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let Some((id, node_id, project_id, executor, started_at, finished_at, exit_code, success, output, replay_json)) = row else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT id, check_type, passed, message, tests_passed, tests_failed, failures
+             FROM check_results WHERE run_id = ? ORDER BY idx ASC",
+        )?;
+        let check_results = stmt
+            .query_map([run_id], |row| {
+                let failures_json: Option<String> = row.get(6)?;
+                Ok((
+                    CheckResult {
+                        id: row.get(0)?,
+                        check_type: row.get(1)?,
+                        passed: row.get(2)?,
+                        message: row.get(3)?,
+                        tests_passed: row.get(4)?,
+                        tests_failed: row.get(5)?,
+                        failures: Vec::new(),
+                    },
+                    failures_json,
+                ))
+            })?
+            .map(|row| -> HistoryResult<CheckResult> {
+                let (mut result, failures_json) = row?;
+                if let Some(json) = failures_json {
+                    result.failures = serde_json::from_str(&json)?;
+                }
+                Ok(result)
+            })
+            .collect::<HistoryResult<Vec<_>>>()?;
+
+        let replay = replay_json
+            .map(|s| serde_json::from_str(&s))
+            .transpose()?;
+
+        Ok(Some(RunRecord {
+            id,
+            node_id,
+            project_id,
+            executor,
+            started_at,
+            finished_at,
+            exit_code,
+            success,
+            output,
+            check_results,
+            replay,
+        }))
+    }
+}
+
+fn initialize_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS runs (
+            id TEXT PRIMARY KEY,
+            node_id TEXT NOT NULL,
+            project_id TEXT,
+            executor TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            finished_at INTEGER NOT NULL,
+            exit_code INTEGER NOT NULL,
+            success INTEGER NOT NULL DEFAULT 0,
+            output TEXT NOT NULL DEFAULT '',
+            replay TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS check_results (
+            run_id TEXT NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+            idx INTEGER NOT NULL,
+            id TEXT NOT NULL,
+            check_type TEXT NOT NULL,
+            passed INTEGER NOT NULL,
+            message TEXT,
+            tests_passed INTEGER,
+            tests_failed INTEGER,
+            failures TEXT,
+            PRIMARY KEY (run_id, idx)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_runs_node_id ON runs(node_id);
+        "#,
+    )
+}
+
+// Mirrors `db::Database`: a raw `Connection` isn't `Clone`, and Tauri state is shared via
+// `Arc` under the hood, so this type is managed directly rather than cloned.
+unsafe impl Send for HistoryStore {}
+unsafe impl Sync for HistoryStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_replay() -> ReplayRequest {
+        ReplayRequest {
+            node_id: "node-1".to_string(),
+            agent: "claude".to_string(),
+            model: Some("sonnet".to_string()),
+            extra_args: None,
+            prompt: "do the thing".to_string(),
+            cwd: None,
+        }
+    }
+
+    #[test]
+    fn record_and_get_run_round_trips_check_results() {
+        let store = HistoryStore::new_in_memory().unwrap();
+        let results = vec![CheckResult {
+            id: "check-1".to_string(),
+            check_type: "command".to_string(),
+            passed: true,
+            message: None,
+            tests_passed: None,
+            tests_failed: None,
+            failures: Vec::new(),
+        }];
+
+        let run_id = store
+            .record_run(
+                "node-1",
+                Some("project-1"),
+                "claude",
+                1_000,
+                2_000,
+                0,
+                true,
+                "hello world",
+                &results,
+                Some(&sample_replay()),
+            )
+            .unwrap();
+
+        let run = store.get_run(&run_id).unwrap().expect("run exists");
+        assert_eq!(run.node_id, "node-1");
+        assert_eq!(run.output, "hello world");
+        assert_eq!(run.check_results.len(), 1);
+        assert_eq!(run.check_results[0].id, "check-1");
+        assert!(run.replay.is_some());
+    }
+
+    #[test]
+    fn list_runs_orders_most_recent_first() {
+        let store = HistoryStore::new_in_memory().unwrap();
+        store
+            .record_run("node-1", None, "claude", 1_000, 1_500, 0, true, "first", &[], None)
+            .unwrap();
+        store
+            .record_run("node-1", None, "claude", 2_000, 2_500, 0, true, "second", &[], None)
+            .unwrap();
+
+        let runs = store.list_runs("node-1").unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].output, "second");
+        assert_eq!(runs[1].output, "first");
+    }
+
+    #[test]
+    fn get_run_returns_none_for_unknown_id() {
+        let store = HistoryStore::new_in_memory().unwrap();
+        assert!(store.get_run("does-not-exist").unwrap().is_none());
+    }
+}
@@ -2,6 +2,14 @@
 //!
 //! This module analyzes terminal output to detect patterns that indicate
 //! an agent is waiting for user input (questions, prompts, choices, etc.)
+//!
+//! Detection is driven by a `DetectorProfile` per agent type: a set of weighted
+//! patterns plus a couple of tunable weights. Built-in profiles cover Claude, Codex
+//! and Gemini; a project can override or add to these at runtime via
+//! `ProjectContext.variables` (see [`profiles_from_variables`]) so users can tune
+//! detection for custom CLI agents without recompiling.
+
+use serde::{Deserialize, Serialize};
 
 /// Result of input detection analysis
 #[derive(Debug, Clone)]
@@ -12,43 +20,281 @@ pub struct InputDetectionResult {
     pub detected_question: Option<String>,
     /// Confidence level (0.0 - 1.0)
     pub confidence: f32,
+    /// Name of the rule that contributed the deciding match, for debugging why a
+    /// session was (or wasn't) flagged as awaiting input.
+    pub matched_rule: Option<String>,
+}
+
+/// A single named pattern and the confidence it contributes when found in the
+/// recent output. `name` is surfaced in `InputDetectionResult::matched_rule` so a
+/// false positive/negative can be traced back to the rule that caused it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightedPattern {
+    pub name: String,
+    pub pattern: String,
+    pub weight: f32,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+fn pattern(name: &str, pattern: &str, weight: f32) -> WeightedPattern {
+    WeightedPattern {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+        weight,
+        case_sensitive: false,
+    }
+}
+
+/// Patterns shared by every agent: generic confirmation/prompt phrasing that isn't
+/// specific to any one CLI's output style.
+fn common_patterns() -> Vec<WeightedPattern> {
+    vec![
+        pattern("prompt:arrow", "> ", 0.3),
+        pattern("prompt:double-arrow", ">> ", 0.3),
+        pattern("prompt:triple-arrow", ">>> ", 0.3),
+        pattern("prompt:yn-upper", "[y/n]", 0.3),
+        pattern("prompt:yn-yes-default", "[Y/n]", 0.3),
+        pattern("prompt:yn-no-default", "[y/N]", 0.3),
+        pattern("prompt:yes-no-paren", "(yes/no)", 0.3),
+        pattern("prompt:yn-paren", "(y/n)", 0.3),
+        pattern("prompt:yes-no-bracket", "[yes/no]", 0.3),
+        pattern("prompt:press-enter", "press enter", 0.3),
+        pattern("prompt:continue", "continue?", 0.3),
+        pattern("prompt:proceed", "proceed?", 0.3),
+        pattern("prompt:confirm", "confirm", 0.3),
+    ]
+}
+
+/// A pluggable source of detection rules for one agent type. Implementations
+/// contribute their own weighted patterns on top of the shared [`common_patterns`],
+/// plus the weight given to the two structural signals (a trailing `?` and a
+/// numbered-choice list) that aren't agent-specific.
+pub trait DetectorProfile: Send + Sync {
+    /// The `agent` string this profile applies to (matched case-insensitively).
+    fn agent(&self) -> &str;
+    /// Weighted patterns checked against the recent, ANSI-stripped output.
+    fn patterns(&self) -> &[WeightedPattern];
+    /// Confidence added once when one of the last few lines ends in `?`.
+    fn question_mark_weight(&self) -> f32 {
+        0.4
+    }
+    /// Confidence added when the output contains a numbered-choice list.
+    fn numbered_choice_weight(&self) -> f32 {
+        0.25
+    }
+}
+
+/// A `DetectorProfile` built from a fixed pattern list, used for both the built-in
+/// profiles and ones loaded from `ProjectContext.variables` at runtime.
+pub struct StaticProfile {
+    agent: String,
+    patterns: Vec<WeightedPattern>,
+    question_mark_weight: f32,
+    numbered_choice_weight: f32,
+}
+
+impl DetectorProfile for StaticProfile {
+    fn agent(&self) -> &str {
+        &self.agent
+    }
+
+    fn patterns(&self) -> &[WeightedPattern] {
+        &self.patterns
+    }
+
+    fn question_mark_weight(&self) -> f32 {
+        self.question_mark_weight
+    }
+
+    fn numbered_choice_weight(&self) -> f32 {
+        self.numbered_choice_weight
+    }
+}
+
+fn claude_profile() -> StaticProfile {
+    let mut patterns = common_patterns();
+    patterns.extend([
+        pattern("claude:what-would-you-like", "What would you like", 0.35),
+        pattern("claude:would-you-like-me-to", "Would you like me to", 0.35),
+        pattern("claude:should-i", "Should I", 0.35),
+        pattern("claude:do-you-want", "Do you want", 0.35),
+        pattern("claude:how-would-you-like", "How would you like", 0.35),
+        pattern("claude:which-option", "Which option", 0.35),
+        pattern("claude:please-choose", "Please choose", 0.35),
+        pattern("claude:select-one", "Select one", 0.35),
+        pattern("claude:enter-your", "Enter your", 0.35),
+        pattern("claude:type-your", "Type your", 0.35),
+        pattern("claude:provide-the", "Provide the", 0.35),
+    ]);
+    StaticProfile {
+        agent: "claude".to_string(),
+        patterns,
+        question_mark_weight: 0.4,
+        numbered_choice_weight: 0.25,
+    }
+}
+
+fn codex_profile() -> StaticProfile {
+    let mut patterns = common_patterns();
+    patterns.extend([
+        pattern("codex:allow-command", "Allow command", 0.35),
+        pattern("codex:approve-this", "approve this", 0.35),
+        pattern("codex:run-this-command", "run this command", 0.35),
+        pattern("codex:yes-no-prompt", "(y)es", 0.35),
+        pattern("codex:apply-patch", "Apply patch", 0.3),
+    ]);
+    StaticProfile {
+        agent: "codex".to_string(),
+        patterns,
+        question_mark_weight: 0.4,
+        numbered_choice_weight: 0.25,
+    }
+}
+
+fn gemini_profile() -> StaticProfile {
+    let mut patterns = common_patterns();
+    patterns.extend([
+        pattern("gemini:do-you-want-me-to", "do you want me to", 0.35),
+        pattern("gemini:shall-i", "Shall I", 0.35),
+        pattern("gemini:waiting-for-input", "waiting for your input", 0.4),
+        pattern("gemini:allow-this-action", "allow this action", 0.35),
+    ]);
+    StaticProfile {
+        agent: "gemini".to_string(),
+        patterns,
+        question_mark_weight: 0.4,
+        numbered_choice_weight: 0.25,
+    }
+}
+
+/// Fallback profile for agent strings that don't match a known built-in, covering
+/// only the patterns common to every agent.
+fn generic_profile(agent: &str) -> StaticProfile {
+    StaticProfile {
+        agent: agent.to_string(),
+        patterns: common_patterns(),
+        question_mark_weight: 0.4,
+        numbered_choice_weight: 0.25,
+    }
+}
+
+/// Shape of a custom profile as stored in `ProjectContext.variables`, e.g.:
+///
+/// ```json
+/// { "inputDetectionProfiles": [
+///     { "agent": "my-agent", "patterns": [{"name": "my-agent:ok", "pattern": "OK?", "weight": 0.4}] }
+/// ] }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileConfig {
+    agent: String,
+    #[serde(default)]
+    patterns: Vec<WeightedPattern>,
+    question_mark_weight: Option<f32>,
+    numbered_choice_weight: Option<f32>,
+}
+
+impl From<ProfileConfig> for StaticProfile {
+    fn from(config: ProfileConfig) -> Self {
+        StaticProfile {
+            agent: config.agent,
+            patterns: config.patterns,
+            question_mark_weight: config.question_mark_weight.unwrap_or(0.4),
+            numbered_choice_weight: config.numbered_choice_weight.unwrap_or(0.25),
+        }
+    }
+}
+
+/// Parse any user-supplied `DetectorProfile`s out of a project's
+/// `ProjectContext.variables`. Malformed or absent config yields an empty list rather
+/// than an error -- detection falls back to the built-in profiles.
+fn profiles_from_variables(variables: &serde_json::Value) -> Vec<StaticProfile> {
+    let Some(configs) = variables
+        .get("inputDetectionProfiles")
+        .and_then(|v| v.as_array())
+    else {
+        return Vec::new();
+    };
+
+    configs
+        .iter()
+        .filter_map(|v| serde_json::from_value::<ProfileConfig>(v.clone()).ok())
+        .map(StaticProfile::from)
+        .collect()
+}
+
+/// Resolve the `DetectorProfile` to use for `agent`: a runtime override from
+/// `variables` takes precedence, then the matching built-in, then the generic
+/// common-patterns-only fallback.
+fn resolve_profile(agent: &str, variables: &serde_json::Value) -> StaticProfile {
+    let custom = profiles_from_variables(variables);
+    if let Some(profile) = custom
+        .into_iter()
+        .find(|p| p.agent.eq_ignore_ascii_case(agent))
+    {
+        return profile;
+    }
+
+    match agent.to_lowercase().as_str() {
+        "claude" => claude_profile(),
+        "codex" => codex_profile(),
+        "gemini" => gemini_profile(),
+        _ => generic_profile(agent),
+    }
 }
 
-/// Patterns that indicate an agent is waiting for input
-const QUESTION_ENDINGS: &[&str] = &["?"];
-
-const PROMPT_INDICATORS: &[&str] = &[
-    "> ",
-    ">> ",
-    ">>> ",
-    "[y/n]",
-    "[Y/n]",
-    "[y/N]",
-    "(yes/no)",
-    "(y/n)",
-    "[yes/no]",
-    "Press Enter",
-    "press enter",
-    "Continue?",
-    "Proceed?",
-    "confirm",
-    "Confirm",
-];
-
-/// Claude-specific patterns
-const CLAUDE_PATTERNS: &[&str] = &[
-    "What would you like",
-    "Would you like me to",
-    "Should I",
-    "Do you want",
-    "How would you like",
-    "Which option",
-    "Please choose",
-    "Select one",
-    "Enter your",
-    "Type your",
-    "Provide the",
-];
+/// Strip ANSI/VT escape sequences and collapse carriage-return overwrites so spinner
+/// redraws and colored prompts don't corrupt the line-based heuristics below.
+///
+/// Handles CSI sequences (`ESC [ ... final-byte`), OSC sequences (`ESC ] ... BEL` or
+/// `ESC ] ... ESC \`), and single-character escapes (e.g. `ESC (` charset selection).
+/// After escapes are removed, each line is collapsed to whatever follows its last
+/// `\r`, mimicking how a terminal renders carriage-return overwrites.
+fn strip_ansi_and_overwrites(text: &str) -> String {
+    let mut clean = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            clean.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some('[') => {
+                chars.next();
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() || c == '@' || c == '~' {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                chars.next();
+                let mut prev = None;
+                for c in chars.by_ref() {
+                    if c == '\u{7}' || (c == '\\' && prev == Some('\u{1b}')) {
+                        break;
+                    }
+                    prev = Some(c);
+                }
+            }
+            Some(_) => {
+                chars.next();
+            }
+            None => {}
+        }
+    }
+
+    clean
+        .split('\n')
+        .map(|line| line.rsplit('\r').next().unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
 /// Patterns indicating numbered choices (e.g., "1. Option A\n2. Option B")
 fn has_numbered_choices(text: &str) -> bool {
@@ -83,7 +329,7 @@ fn has_numbered_choices(text: &str) -> bool {
 }
 
 /// Extract the likely question from the output
-fn extract_question(text: &str) -> Option<String> {
+fn extract_question(text: &str, profile: &dyn DetectorProfile) -> Option<String> {
     let lines: Vec<&str> = text.lines().collect();
 
     // Look from the end backwards for the question
@@ -98,16 +344,15 @@ fn extract_question(text: &str) -> Option<String> {
             return Some(trimmed.to_string());
         }
 
-        // Check for prompt indicators
-        for pattern in PROMPT_INDICATORS {
-            if trimmed.contains(pattern) {
-                return Some(trimmed.to_string());
-            }
-        }
-
-        // Check for Claude patterns
-        for pattern in CLAUDE_PATTERNS {
-            if trimmed.contains(pattern) {
+        // Check against this profile's patterns
+        let lower = trimmed.to_lowercase();
+        for p in profile.patterns() {
+            let hit = if p.case_sensitive {
+                trimmed.contains(p.pattern.as_str())
+            } else {
+                lower.contains(&p.pattern.to_lowercase())
+            };
+            if hit {
                 return Some(trimmed.to_string());
             }
         }
@@ -119,15 +364,24 @@ fn extract_question(text: &str) -> Option<String> {
     None
 }
 
-/// Analyze terminal output to detect if an agent is waiting for input
+/// Analyze terminal output to detect if an agent is waiting for user input
 ///
 /// # Arguments
 /// * `output` - The terminal output to analyze (typically last 50 lines)
-/// * `agent` - The agent type ("claude", "codex", "gemini") for agent-specific patterns
+/// * `agent` - The agent type ("claude", "codex", "gemini", or a custom agent string)
+/// * `variables` - The owning project's `ProjectContext.variables`, consulted for a
+///   user-supplied `DetectorProfile` override (see [`profiles_from_variables`])
 ///
 /// # Returns
 /// An `InputDetectionResult` with detection status and any extracted question
-pub fn detect_input_waiting(output: &str, agent: &str) -> InputDetectionResult {
+pub fn detect_input_waiting(
+    output: &str,
+    agent: &str,
+    variables: &serde_json::Value,
+) -> InputDetectionResult {
+    let profile = resolve_profile(agent, variables);
+    let output = strip_ansi_and_overwrites(output);
+
     // Only analyze the last portion of output (last 15 lines or so)
     let lines: Vec<&str> = output.lines().collect();
     let start_idx = lines.len().saturating_sub(15);
@@ -135,6 +389,15 @@ pub fn detect_input_waiting(output: &str, agent: &str) -> InputDetectionResult {
 
     let mut confidence = 0.0f32;
     let mut detected_question = None;
+    let mut matched_rule: Option<String> = None;
+    let mut best_weight = 0.0f32;
+
+    let mut record_match = |name: &str, weight: f32| {
+        if weight > best_weight {
+            best_weight = weight;
+            matched_rule = Some(name.to_string());
+        }
+    };
 
     // Check for question marks at end of lines
     for line in lines.iter().rev().take(5) {
@@ -143,44 +406,41 @@ pub fn detect_input_waiting(output: &str, agent: &str) -> InputDetectionResult {
             continue;
         }
 
-        for ending in QUESTION_ENDINGS {
-            if trimmed.ends_with(ending) {
-                confidence += 0.4;
-                if detected_question.is_none() {
-                    detected_question = Some(trimmed.to_string());
-                }
-                break;
+        if trimmed.ends_with('?') {
+            confidence += profile.question_mark_weight();
+            record_match("structural:trailing-question-mark", profile.question_mark_weight());
+            if detected_question.is_none() {
+                detected_question = Some(trimmed.to_string());
             }
+            break;
         }
     }
 
     let recent_lower = recent_output.to_lowercase();
 
-    // Check for prompt indicators (case-insensitive)
-    if PROMPT_INDICATORS
-        .iter()
-        .any(|p| recent_lower.contains(&p.to_lowercase()))
-    {
-        confidence += 0.3;
-    }
-
-    // Check for Claude-specific patterns (case-insensitive)
-    if agent == "claude"
-        && CLAUDE_PATTERNS
-            .iter()
-            .any(|p| recent_lower.contains(&p.to_lowercase()))
-    {
-        confidence += 0.35;
+    // Check this profile's weighted patterns (case-insensitive unless the pattern
+    // opts in to case-sensitive matching)
+    for p in profile.patterns() {
+        let hit = if p.case_sensitive {
+            recent_output.contains(p.pattern.as_str())
+        } else {
+            recent_lower.contains(&p.pattern.to_lowercase())
+        };
+        if hit {
+            confidence += p.weight;
+            record_match(&p.name, p.weight);
+        }
     }
 
     // Check for numbered choices
     if has_numbered_choices(&recent_output) {
-        confidence += 0.25;
+        confidence += profile.numbered_choice_weight();
+        record_match("structural:numbered-choices", profile.numbered_choice_weight());
     }
 
     // Try to extract question if we haven't found one yet
     if detected_question.is_none() && confidence > 0.3 {
-        detected_question = extract_question(&recent_output);
+        detected_question = extract_question(&recent_output, &profile);
     }
 
     // Cap confidence at 1.0
@@ -190,6 +450,7 @@ pub fn detect_input_waiting(output: &str, agent: &str) -> InputDetectionResult {
         waiting_for_input: confidence >= 0.5,
         detected_question,
         confidence,
+        matched_rule,
     }
 }
 
@@ -197,10 +458,14 @@ pub fn detect_input_waiting(output: &str, agent: &str) -> InputDetectionResult {
 mod tests {
     use super::*;
 
+    fn no_variables() -> serde_json::Value {
+        serde_json::Value::Object(serde_json::Map::new())
+    }
+
     #[test]
     fn test_simple_question() {
         let output = "Some output here\nWhat would you like me to do?";
-        let result = detect_input_waiting(output, "claude");
+        let result = detect_input_waiting(output, "claude", &no_variables());
         assert!(result.waiting_for_input);
         assert!(result.detected_question.is_some());
     }
@@ -208,7 +473,7 @@ mod tests {
     #[test]
     fn test_yes_no_prompt() {
         let output = "Ready to proceed. Continue? [y/n]";
-        let result = detect_input_waiting(output, "claude");
+        let result = detect_input_waiting(output, "claude", &no_variables());
         assert!(result.waiting_for_input);
     }
 
@@ -218,14 +483,52 @@ mod tests {
 1. Create new file
 2. Modify existing
 3. Delete and recreate"#;
-        let result = detect_input_waiting(output, "claude");
+        let result = detect_input_waiting(output, "claude", &no_variables());
         assert!(result.waiting_for_input);
     }
 
     #[test]
     fn test_no_question() {
         let output = "Running tests...\nAll 42 tests passed.\nDone.";
-        let result = detect_input_waiting(output, "claude");
+        let result = detect_input_waiting(output, "claude", &no_variables());
         assert!(!result.waiting_for_input);
     }
+
+    #[test]
+    fn test_strips_ansi_and_carriage_returns() {
+        let output = "\x1b[2K\x1b[1;32mSpinning...\x1b[0m\rWhat would you like me to do?";
+        let result = detect_input_waiting(output, "claude", &no_variables());
+        assert!(result.waiting_for_input);
+        assert_eq!(
+            result.detected_question.as_deref(),
+            Some("What would you like me to do?")
+        );
+    }
+
+    #[test]
+    fn test_codex_profile_matches_codex_specific_pattern() {
+        let output = "I'd like to run this command:\nAllow command? [y/n]";
+        let result = detect_input_waiting(output, "codex", &no_variables());
+        assert!(result.waiting_for_input);
+    }
+
+    #[test]
+    fn test_custom_agent_uses_runtime_profile_override() {
+        let variables = serde_json::json!({
+            "inputDetectionProfiles": [{
+                "agent": "my-agent",
+                "patterns": [{"name": "my-agent:ready", "pattern": "ready for input", "weight": 0.6}]
+            }]
+        });
+        let result = detect_input_waiting("Task done, ready for input", "my-agent", &variables);
+        assert!(result.waiting_for_input);
+        assert_eq!(result.matched_rule.as_deref(), Some("my-agent:ready"));
+    }
+
+    #[test]
+    fn test_unknown_agent_falls_back_to_generic_profile() {
+        let output = "Working...\nProceed? [y/n]";
+        let result = detect_input_waiting(output, "some-unlisted-agent", &no_variables());
+        assert!(result.waiting_for_input);
+    }
 }
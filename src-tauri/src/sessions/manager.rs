@@ -1,9 +1,34 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tokio::time::Duration;
 
+use super::backend::{InteractiveBackend, InteractiveBackendKind, TmuxBackend};
+use super::checks::WatchHandle;
+use super::pty::PtyBackend;
 use super::tmux;
+use crate::db::Database;
+
+/// How many times in all (the first try plus retries) `create_session` tries to start a
+/// backend before giving up, for when e.g. the tmux server is still coming up right after install.
+const SESSION_CREATE_MAX_ATTEMPTS: u32 = 3;
+
+/// First backoff before retrying a failed `InteractiveBackend::create_session`, doubling
+/// after each subsequent attempt -- same shape as
+/// `executors::remote::reconnect_backoff`, just for a much shorter-lived failure mode.
+const SESSION_CREATE_BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Cap on `session_create_backoff`'s exponential growth.
+const SESSION_CREATE_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Backoff before the `attempt`'th retry (1-indexed) of a failed `create_session`.
+fn session_create_backoff(attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt.saturating_sub(1)).unwrap_or(u64::MAX);
+    SESSION_CREATE_BASE_BACKOFF
+        .saturating_mul(factor as u32)
+        .min(SESSION_CREATE_MAX_BACKOFF)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +42,13 @@ pub struct Session {
     pub exit_code: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cwd: Option<String>,
+    /// Inputs the session was created with, kept around so `sessions::history` can
+    /// relaunch an equivalent session via `replay_run`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_args: Option<Vec<String>>,
+    pub prompt: String,
     /// Hash of last captured output (for staleness detection)
     #[serde(skip)]
     pub last_output_hash: Option<u64>,
@@ -29,15 +61,26 @@ pub struct Session {
     /// Node label for notifications
     #[serde(skip_serializing_if = "Option::is_none")]
     pub node_label: Option<String>,
+    /// Which `InteractiveBackend` is hosting this session.
+    pub backend: InteractiveBackendKind,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum SessionStatus {
+    /// Backend process/SSH connection is being established; no output has arrived yet.
+    Connecting,
     Running,
     AwaitingInput,
+    /// A transient connection failure is being retried with exponential backoff (see
+    /// `session_create_backoff`); `next_at` is the epoch-millis timestamp
+    /// the next attempt is scheduled for, for the UI's own countdown.
+    Retrying { attempt: u32, next_at: i64 },
     Completed,
     Failed,
+    /// Retries were exhausted without the backend ever coming back, as opposed to
+    /// `Failed` (the agent itself ran and exited non-zero).
+    TimedOut,
 }
 
 pub struct StalenessUpdate {
@@ -50,15 +93,158 @@ pub struct StalenessUpdate {
 #[derive(Clone)]
 pub struct SessionManager {
     sessions: Arc<Mutex<HashMap<String, Session>>>,
+    tmux_backend: TmuxBackend,
+    pty_backend: PtyBackend,
+    /// Most- and previously-attached session id, for remux-style `switch_session`
+    /// navigation when the UI is juggling many concurrent runs.
+    attached: Arc<Mutex<AttachedSessions>>,
+    /// Live `checks::run_checks_watch` handle for each session that has one, keyed by
+    /// session id. Kept separate from `Session` itself since a `WatchHandle` isn't
+    /// `Serialize`/`Clone`; dropping an entry (on kill or removal) stops its watcher
+    /// thread via `WatchHandle`'s `Drop` impl.
+    watch_handles: Arc<Mutex<HashMap<String, WatchHandle>>>,
+    /// Persistence layer, so sessions survive an app restart. `None` until
+    /// `attach_database` is called from `lib.rs`'s `setup` once the database is open;
+    /// session lifecycle methods silently skip persistence until then.
+    db: Arc<Mutex<Option<Arc<Database>>>>,
+}
+
+#[derive(Default)]
+struct AttachedSessions {
+    current: Option<String>,
+    previous: Option<String>,
 }
 
 impl SessionManager {
     pub fn new() -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            tmux_backend: TmuxBackend,
+            pty_backend: PtyBackend::new(),
+            attached: Arc::new(Mutex::new(AttachedSessions::default())),
+            watch_handles: Arc::new(Mutex::new(HashMap::new())),
+            db: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Wire up the database once it's open, and run the startup recovery pass: reconcile
+    /// whatever `sessions` rows were left `running`/`awaiting_input` from a previous
+    /// launch against the tmux sessions that are actually still alive. A survivor is
+    /// re-hydrated into the in-memory `HashMap`; everything else is marked `failed`.
+    ///
+    /// Only tmux-backed sessions can be recovered this way -- a `Pty` session's child
+    /// process has no controlling terminal left to reattach to once the app that spawned
+    /// it (and held the PTY master) has restarted.
+    pub async fn attach_database(&self, db: Arc<Database>) {
+        self.recover_sessions(&db).await;
+        *self.db.lock().await = Some(db);
+    }
+
+    async fn recover_sessions(&self, db: &Database) {
+        // Drain any session left `job_status = queued` by a crash between
+        // `db::create_session`'s insert and this same `create_session`'s own
+        // queued->running transition a few lines below -- that's the only window a row
+        // can still be sitting in the queue, since nothing else ever enqueues one.
+        // Claiming it here (rather than leaving it queued forever) is what lets
+        // `heartbeat`/`orphan_stale_sessions` reason about it like any other job; the
+        // tmux-liveness reconciliation below still decides whether it actually survives.
+        loop {
+            match db.claim_next_session() {
+                Ok(Some(claimed)) => {
+                    tracing::info!(
+                        "Claimed session {} left queued by an earlier crash",
+                        claimed.id
+                    );
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("Failed to drain queued sessions on startup: {e}");
+                    break;
+                }
+            }
+        }
+
+        let rows = match db.list_sessions() {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::warn!("Failed to list sessions for recovery: {e}");
+                return;
+            }
+        };
+
+        let live_tmux_sessions: HashSet<String> = tmux::list_sessions().into_iter().collect();
+        let mut sessions = self.sessions.lock().await;
+
+        for row in rows {
+            let status = match row.status.as_str() {
+                "running" => SessionStatus::Running,
+                "awaiting_input" => SessionStatus::AwaitingInput,
+                _ => continue, // already terminal (or disconnected) -- nothing to recover
+            };
+
+            let is_live_tmux_session =
+                row.backend.as_deref() == Some("tmux") && live_tmux_sessions.contains(&row.id);
+
+            if !is_live_tmux_session {
+                if let Err(e) = db.set_session_status(&row.id, "failed") {
+                    tracing::warn!("Failed to mark orphaned session {} failed: {e}", row.id);
+                }
+                continue;
+            }
+
+            tracing::info!("Recovered session {} from a previous run", row.id);
+            sessions.insert(
+                row.id.clone(),
+                Session {
+                    id: row.id,
+                    node_id: row.node_id,
+                    agent: row.agent_type,
+                    status,
+                    created_at: row.started_at,
+                    exit_code: None,
+                    cwd: None,
+                    model: None,
+                    extra_args: None,
+                    prompt: String::new(),
+                    last_output_hash: None,
+                    stale_poll_count: 0,
+                    detected_question: None,
+                    node_label: None,
+                    backend: InteractiveBackendKind::Tmux,
+                },
+            );
         }
     }
 
+    /// Register a live deliverable/check watch for `session_id`, replacing (and thereby
+    /// stopping) any watch already registered for it. Torn down automatically whenever
+    /// the session is, in `kill_session`/`remove_session`.
+    pub async fn set_deliverable_watch(&self, session_id: &str, handle: WatchHandle) {
+        self.watch_handles
+            .lock()
+            .await
+            .insert(session_id.to_string(), handle);
+    }
+
+    /// The `InteractiveBackend` implementation for `kind`, for dispatching a session
+    /// operation once its backend is known (e.g. from a looked-up `Session`).
+    pub fn backend(&self, kind: InteractiveBackendKind) -> &dyn InteractiveBackend {
+        match kind {
+            InteractiveBackendKind::Tmux => &self.tmux_backend,
+            InteractiveBackendKind::Pty => &self.pty_backend,
+        }
+    }
+
+    /// Owned, `'static` handle to the PTY backend, for callers (e.g. the monitor) that
+    /// need to dispatch from inside a `spawn_blocking` closure.
+    pub fn pty_backend(&self) -> PtyBackend {
+        self.pty_backend.clone()
+    }
+
+    pub async fn backend_kind(&self, session_id: &str) -> Option<InteractiveBackendKind> {
+        self.sessions.lock().await.get(session_id).map(|s| s.backend)
+    }
+
     pub async fn create_session(
         &self,
         node_id: &str,
@@ -70,10 +256,93 @@ impl SessionManager {
     ) -> Result<Session, String> {
         let session_id = format!("orchestra-{}", uuid::Uuid::new_v4());
 
-        let agent_kind = parse_agent(agent)?;
-        let command = build_agent_command(&session_id, agent_kind, model, extra_args, prompt)?;
+        let command = build_agent_command(&session_id, agent, model, extra_args, prompt)?;
+
+        // Prefer tmux (attach from an external terminal, proper scrollback); fall back
+        // to the native PTY backend on machines that don't have it installed.
+        let backend_kind = if tmux::is_available() {
+            InteractiveBackendKind::Tmux
+        } else {
+            InteractiveBackendKind::Pty
+        };
+
+        // Visible to `list_sessions` for the whole establishing/retrying window below,
+        // so a caller watching the session list (rather than just awaiting this method)
+        // sees it progress through `Connecting`/`Retrying` instead of not existing at
+        // all until the backend finally starts.
+        let placeholder = Session {
+            id: session_id.clone(),
+            node_id: node_id.to_string(),
+            agent: agent.to_string(),
+            status: SessionStatus::Connecting,
+            created_at: chrono::Utc::now().timestamp_millis(),
+            exit_code: None,
+            cwd: cwd.map(|s| s.to_string()),
+            model: model.map(|s| s.to_string()),
+            extra_args: extra_args.map(|a| a.to_vec()),
+            prompt: prompt.to_string(),
+            last_output_hash: None,
+            stale_poll_count: 0,
+            detected_question: None,
+            node_label: None,
+            backend: backend_kind,
+        };
+        self.sessions
+            .lock()
+            .await
+            .insert(session_id.clone(), placeholder);
+
+        let mut create_result = self.backend(backend_kind).create_session(&session_id, &command, cwd);
+        for attempt in 1..SESSION_CREATE_MAX_ATTEMPTS {
+            if create_result.is_ok() {
+                break;
+            }
+            let delay = session_create_backoff(attempt);
+            let next_at = chrono::Utc::now().timestamp_millis() + delay.as_millis() as i64;
+            tracing::warn!(
+                "Failed to start {:?} session {session_id}, retrying in {:?} (attempt {}/{})",
+                backend_kind,
+                delay,
+                attempt + 1,
+                SESSION_CREATE_MAX_ATTEMPTS
+            );
+            if let Some(session) = self.sessions.lock().await.get_mut(&session_id) {
+                session.status = SessionStatus::Retrying {
+                    attempt: attempt + 1,
+                    next_at,
+                };
+            }
+            tokio::time::sleep(delay).await;
+            create_result = self.backend(backend_kind).create_session(&session_id, &command, cwd);
+        }
 
-        tmux::create_session(&session_id, &command, cwd).map_err(|e| e.0)?;
+        if let Err(e) = create_result {
+            if let Some(session) = self.sessions.lock().await.get_mut(&session_id) {
+                session.status = SessionStatus::TimedOut;
+            }
+            return Err(e.0);
+        }
+
+        let attach_command = self.backend(backend_kind).attach_command(&session_id, false);
+        if let Some(db) = self.db.lock().await.clone() {
+            if let Err(e) = db.create_session(
+                &session_id,
+                node_id,
+                agent,
+                Some(backend_kind.as_str()),
+                attach_command.as_deref(),
+            ) {
+                tracing::warn!("Failed to persist session {session_id}: {e}");
+            }
+            // `create_session` runs the backend eagerly rather than queuing it for a
+            // worker to pick up via `Database::claim_next_session`, so there's no
+            // separate claim step to transition `job_status` out of its `queued`
+            // default -- do it here so `heartbeat`/`orphan_stale_sessions` see a
+            // `running` job from the moment this session actually starts running.
+            if let Err(e) = db.set_session_status(&session_id, "running") {
+                tracing::warn!("Failed to mark session {session_id} running: {e}");
+            }
+        }
 
         let session = Session {
             id: session_id.clone(),
@@ -83,10 +352,14 @@ impl SessionManager {
             created_at: chrono::Utc::now().timestamp_millis(),
             exit_code: None,
             cwd: cwd.map(|s| s.to_string()),
+            model: model.map(|s| s.to_string()),
+            extra_args: extra_args.map(|a| a.to_vec()),
+            prompt: prompt.to_string(),
             last_output_hash: None,
             stale_poll_count: 0,
             detected_question: None,
             node_label: None,
+            backend: backend_kind,
         };
 
         self.sessions
@@ -101,12 +374,82 @@ impl SessionManager {
         self.sessions.lock().await.values().cloned().collect()
     }
 
+    /// Record `session_id` as the most-recently-attached session, sliding the old
+    /// current down into "previous" so `switch_session` can default to it.
+    pub async fn mark_attached(&self, session_id: &str) {
+        let mut attached = self.attached.lock().await;
+        if attached.current.as_deref() != Some(session_id) {
+            attached.previous = attached.current.take();
+            attached.current = Some(session_id.to_string());
+        }
+    }
+
+    /// The previously-attached session id (the one `switch_session` defaults to).
+    pub async fn previous_attached(&self) -> Option<String> {
+        self.attached.lock().await.previous.clone()
+    }
+
+    /// The currently-attached session id.
+    pub async fn current_attached(&self) -> Option<String> {
+        self.attached.lock().await.current.clone()
+    }
+
+    /// Switch attachment to `session_id`, or to the previously-attached session when
+    /// `None` (remux's `switch-client -l`). Returns the session now considered current.
+    pub async fn switch_session(&self, session_id: Option<&str>) -> Result<Session, String> {
+        let target = match session_id {
+            Some(id) => id.to_string(),
+            None => self
+                .previous_attached()
+                .await
+                .ok_or_else(|| "No previous session to switch to".to_string())?,
+        };
+
+        let session = self
+            .sessions
+            .lock()
+            .await
+            .get(&target)
+            .cloned()
+            .ok_or_else(|| format!("Unknown session {target}"))?;
+
+        self.mark_attached(&target).await;
+        Ok(session)
+    }
+
     pub async fn kill_session(&self, session_id: &str) -> Result<(), String> {
-        tmux::kill_session(session_id).map_err(|e| e.0)?;
+        let backend_kind = self
+            .backend_kind(session_id)
+            .await
+            .ok_or_else(|| format!("Unknown session {session_id}"))?;
+        self.backend(backend_kind)
+            .kill_session(session_id)
+            .map_err(|e| e.0)?;
         self.sessions.lock().await.remove(session_id);
+        self.watch_handles.lock().await.remove(session_id);
+        self.forget_attached(session_id).await;
+
+        if let Some(db) = self.db.lock().await.clone() {
+            if let Err(e) = db.delete_session(session_id) {
+                tracing::warn!("Failed to delete persisted session {session_id}: {e}");
+            }
+        }
+
         Ok(())
     }
 
+    /// Clear `session_id` out of the attached-session tracking (e.g. after it's killed
+    /// or removed) so `switch_session`/the "previous" indicator don't point at a session
+    /// that no longer exists.
+    async fn forget_attached(&self, session_id: &str) {
+        let mut attached = self.attached.lock().await;
+        if attached.current.as_deref() == Some(session_id) {
+            attached.current = attached.previous.take();
+        } else if attached.previous.as_deref() == Some(session_id) {
+            attached.previous = None;
+        }
+    }
+
     /// Mark a session as completed with the given exit code
     pub async fn mark_completed(&self, session_id: &str, exit_code: i32) {
         if let Some(session) = self.sessions.lock().await.get_mut(session_id) {
@@ -117,19 +460,54 @@ impl SessionManager {
             };
             session.exit_code = Some(exit_code);
         }
+
+        if let Some(db) = self.db.lock().await.clone() {
+            if let Err(e) = db.mark_session_completed(session_id, exit_code) {
+                tracing::warn!("Failed to persist completion of session {session_id}: {e}");
+            }
+        }
+    }
+
+    /// Bump the persisted session's `last_heartbeat`, so a future restart's
+    /// `orphan_stale_sessions` sweep doesn't mistake a still-live session for one that
+    /// crashed mid-run. Called by `sessions::monitor`'s poll tick for every session it's
+    /// actively tracking.
+    pub async fn heartbeat(&self, session_id: &str) {
+        if let Some(db) = self.db.lock().await.clone() {
+            if let Err(e) = db.heartbeat_session(session_id) {
+                tracing::warn!("Failed to heartbeat session {session_id}: {e}");
+            }
+        }
     }
 
     /// Remove a session from tracking (used when session dies unexpectedly)
     pub async fn remove_session(&self, session_id: &str) {
         self.sessions.lock().await.remove(session_id);
+        self.watch_handles.lock().await.remove(session_id);
+        self.forget_attached(session_id).await;
     }
 
     /// Mark a session as awaiting input with optional detected question
     pub async fn mark_awaiting_input(&self, session_id: &str, detected_question: Option<String>) {
-        if let Some(session) = self.sessions.lock().await.get_mut(session_id) {
+        let newly_awaiting = if let Some(session) = self.sessions.lock().await.get_mut(session_id) {
             if session.status != SessionStatus::AwaitingInput {
                 session.status = SessionStatus::AwaitingInput;
                 session.detected_question = detected_question;
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        };
+
+        if newly_awaiting {
+            if let Some(db) = self.db.lock().await.clone() {
+                if let Err(e) = db.set_session_status(session_id, "awaiting_input") {
+                    tracing::warn!(
+                        "Failed to persist awaiting-input status for session {session_id}: {e}"
+                    );
+                }
             }
         }
     }
@@ -170,139 +548,21 @@ impl SessionManager {
     // populate it from the backend project store.
 }
 
-#[derive(Debug, Clone, Copy)]
-enum AgentKind {
-    Claude,
-    Codex,
-    Gemini,
-}
-
-fn parse_agent(agent: &str) -> Result<AgentKind, String> {
-    match agent {
-        "claude" => Ok(AgentKind::Claude),
-        "codex" => Ok(AgentKind::Codex),
-        "gemini" => Ok(AgentKind::Gemini),
-        other => Err(format!("Unsupported agent type: {}", other)),
-    }
-}
-
-fn validate_model(model: &str) -> Result<(), String> {
-    if model.is_empty() {
-        return Err("Model must not be empty".to_string());
-    }
-    if model.len() > 128 {
-        return Err("Model is too long".to_string());
-    }
-    if !model
-        .chars()
-        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | ':' | '/'))
-    {
-        return Err("Model contains invalid characters".to_string());
-    }
-    Ok(())
-}
-
-fn sh_escape_single_arg(s: &str) -> String {
-    // Wrap in single quotes and escape embedded single quotes: ' -> '\''.
-    format!("'{}'", s.replace('\'', "'\\''"))
-}
-
 fn build_agent_command(
     session_id: &str,
-    agent: AgentKind,
+    agent: &str,
     model: Option<&str>,
     extra_args: Option<&[String]>,
     prompt: &str,
 ) -> Result<String, String> {
     let exit_file = format!("/tmp/orchestra-sessions/{}.exit", session_id);
 
-    let model = match model {
-        Some(m) => {
-            validate_model(m)?;
-            Some(m)
-        }
-        None => None,
-    };
-
-    let extra_args = extra_args.unwrap_or(&[]);
-    if extra_args.len() > 64 {
-        return Err("Too many extraArgs (max 64)".to_string());
-    }
-    for a in extra_args {
-        if a.is_empty() {
-            return Err("extraArgs contains an empty argument".to_string());
-        }
-        if a.len() > 1024 {
-            return Err("extraArgs contains an argument that is too long".to_string());
-        }
-        if a.contains('\0') {
-            return Err("extraArgs contains an invalid character".to_string());
-        }
-    }
-
-    let prompt = prompt.trim();
-    let mut argv: Vec<&str> = Vec::new();
-    match agent {
-        AgentKind::Claude => {
-            // Claude Code CLI (aligned with `executors/local.rs`):
-            // One-shot uses `-p/--print`, but interactive sessions should start interactive by
-            // default and pass the initial message as a positional [prompt] argument.
-            //   claude --allowedTools ... --model sonnet [extraArgs...] [prompt]
-            argv.push("claude");
-            argv.push("--allowedTools");
-            argv.push("Bash,Read,Write,Edit,Glob,Grep");
-            if let Some(m) = model {
-                argv.push("--model");
-                argv.push(m);
-            }
-            for a in extra_args {
-                argv.push(a);
-            }
-            if !prompt.is_empty() {
-                argv.push(prompt);
-            }
-        }
-        AgentKind::Codex => {
-            // Codex CLI (aligned with `executors/local.rs`):
-            // One-shot uses `codex exec`, but interactive sessions should omit the subcommand.
-            //   codex [--model ...] [extraArgs...] [prompt]
-            argv.push("codex");
-            if let Some(m) = model {
-                argv.push("--model");
-                argv.push(m);
-            }
-            for a in extra_args {
-                argv.push(a);
-            }
-            if !prompt.is_empty() {
-                argv.push(prompt);
-            }
-        }
-        AgentKind::Gemini => {
-            // Gemini CLI:
-            // Positional prompt defaults to one-shot; for interactive, use -i/--prompt-interactive.
-            //   gemini [-m model] [extraArgs...] [-i prompt]
-            argv.push("gemini");
-            if let Some(m) = model {
-                argv.push("-m");
-                argv.push(m);
-            }
-            for a in extra_args {
-                argv.push(a);
-            }
-            if !prompt.is_empty() {
-                argv.push("-i");
-                argv.push(prompt);
-            }
-        }
-    }
-
-    let (prog, args) = argv.split_first().ok_or_else(|| "empty argv".to_string())?;
-    let mut agent_cmd = prog.to_string();
-    for a in args {
-        agent_cmd.push(' ');
-        agent_cmd.push_str(&sh_escape_single_arg(a));
-    }
+    let agent_cmd = crate::agent_command::interactive_shell_command(
+        agent,
+        model,
+        extra_args.unwrap_or(&[]),
+        prompt,
+    )?;
 
     // Wrap the command to:
     // 1. Create the exit directory
@@ -323,7 +583,7 @@ mod tests {
     fn build_agent_command_claude_includes_prompt_flag() {
         let cmd = build_agent_command(
             "orchestra-test",
-            AgentKind::Claude,
+            "claude",
             Some("sonnet"),
             None,
             "hello",
@@ -343,7 +603,7 @@ mod tests {
     fn build_agent_command_codex_is_interactive_by_default() {
         let cmd = build_agent_command(
             "orchestra-test",
-            AgentKind::Codex,
+            "codex",
             Some("gpt-5"),
             None,
             "do it",
@@ -358,7 +618,7 @@ mod tests {
     fn build_agent_command_codex_includes_extra_args_before_prompt() {
         let cmd = build_agent_command(
             "orchestra-test",
-            AgentKind::Codex,
+            "codex",
             Some("gpt-5"),
             Some(&vec!["--yolo".to_string()]),
             "do it",
@@ -0,0 +1,16 @@
+//! Interactive agent sessions: tmux- or PTY-backed creation/attach, completion and
+//! staleness monitoring, post-run checks, session supervision, and fanning lifecycle
+//! events out to external sinks (see `notifier`).
+
+pub mod agent_state;
+pub mod backend;
+pub mod checks;
+pub mod events;
+pub mod history;
+pub mod input_detection;
+pub mod manager;
+pub mod monitor;
+pub mod notifier;
+pub mod pty;
+pub mod supervisor;
+pub mod tmux;
@@ -1,5 +1,6 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use tauri::{Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
@@ -8,12 +9,16 @@ use tokio::time::{interval, Duration};
 
 use crate::state::AppState;
 
+use super::backend::{InteractiveBackend, InteractiveBackendKind};
 use super::checks::{self, Check};
 use super::events::{
     SessionAwaitingInputClearedEvent, SessionAwaitingInputEvent, SessionCompletedEvent,
 };
+use super::history::{HistoryStore, ReplayRequest};
 use super::input_detection::detect_input_waiting;
-use super::manager::{SessionManager, SessionStatus};
+use super::manager::{Session, SessionManager, SessionStatus};
+use super::notifier::Notifier;
+use super::supervisor::{Supervisor, WorkerControl};
 use super::tmux;
 
 /// Number of stale polls before considering the agent to be waiting for input.
@@ -49,22 +54,59 @@ fn truncate_one_line(s: &str, max_chars: usize) -> String {
     out
 }
 
-async fn tmux_capture_pane(session_id: String, lines: usize) -> Result<String, String> {
-    task::spawn_blocking(move || tmux::capture_pane(&session_id, lines).map_err(|e| e.0))
-        .await
-        .map_err(|e| format!("capture task failed: {e}"))?
+/// Capture a session's output through whichever `InteractiveBackend` it's hosted on.
+async fn capture_pane(
+    session_manager: &SessionManager,
+    session_id: String,
+    backend: InteractiveBackendKind,
+    lines: usize,
+) -> Result<String, String> {
+    match backend {
+        InteractiveBackendKind::Tmux => {
+            task::spawn_blocking(move || tmux::capture_pane(&session_id, lines).map_err(|e| e.0))
+                .await
+                .map_err(|e| format!("capture task failed: {e}"))?
+        }
+        InteractiveBackendKind::Pty => {
+            let pty = session_manager.pty_backend();
+            task::spawn_blocking(move || pty.capture_output(&session_id, lines).map_err(|e| e.0))
+                .await
+                .map_err(|e| format!("capture task failed: {e}"))?
+        }
+    }
 }
 
-async fn tmux_session_exists(session_id: String) -> bool {
-    task::spawn_blocking(move || tmux::session_exists(&session_id))
-        .await
-        .unwrap_or(false)
+/// Whether a session's underlying process is still alive, through whichever
+/// `InteractiveBackend` it's hosted on.
+async fn session_exists(
+    session_manager: &SessionManager,
+    session_id: String,
+    backend: InteractiveBackendKind,
+) -> bool {
+    match backend {
+        InteractiveBackendKind::Tmux => {
+            task::spawn_blocking(move || tmux::session_exists(&session_id))
+                .await
+                .unwrap_or(false)
+        }
+        InteractiveBackendKind::Pty => {
+            let pty = session_manager.pty_backend();
+            task::spawn_blocking(move || pty.session_exists(&session_id))
+                .await
+                .unwrap_or(false)
+        }
+    }
 }
 
 async fn get_node_checks_and_label(
     app_state: &AppState,
     node_id: &str,
-) -> (Vec<Check>, Option<String>) {
+) -> (
+    Vec<Check>,
+    Option<String>,
+    Vec<crate::commands::projects::NotifyRule>,
+    Option<String>,
+) {
     let projects = app_state.projects.read().await;
     for project in projects.values() {
         if let Some(node) = project.nodes.iter().find(|n| n.id == node_id) {
@@ -73,17 +115,75 @@ async fn get_node_checks_and_label(
                 .iter()
                 .filter_map(|v| serde_json::from_value(v.clone()).ok())
                 .collect::<Vec<Check>>();
-            return (checks, Some(node.title.clone()));
+            return (
+                checks,
+                Some(node.title.clone()),
+                project.notify.clone(),
+                Some(project.id.clone()),
+            );
         }
     }
-    (Vec::new(), None)
+    (Vec::new(), None, Vec::new(), None)
+}
+
+/// Look up the `ProjectContext.variables` for the project that owns `node_id`, so
+/// `detect_input_waiting` can pick up user-supplied `DetectorProfile` overrides. Falls
+/// back to an empty JSON object (meaning "no overrides") if the node isn't found.
+async fn get_project_variables(app_state: &AppState, node_id: &str) -> serde_json::Value {
+    let projects = app_state.projects.read().await;
+    for project in projects.values() {
+        if project.nodes.iter().any(|n| n.id == node_id) {
+            return project.context.variables.clone();
+        }
+    }
+    serde_json::Value::Object(serde_json::Map::new())
+}
+
+/// Record a finished run (and its check results) to the history store, for
+/// `list_runs`/`get_run`/`replay_run`. Logs and swallows errors: a failed history write
+/// shouldn't stop the monitor from emitting the completion event itself.
+fn record_run_history(
+    history: &HistoryStore,
+    session: &Session,
+    project_id: Option<&str>,
+    event: &SessionCompletedEvent,
+) {
+    let replay = ReplayRequest {
+        node_id: session.node_id.clone(),
+        agent: session.agent.clone(),
+        model: session.model.clone(),
+        extra_args: session.extra_args.clone(),
+        prompt: session.prompt.clone(),
+        cwd: session.cwd.clone(),
+    };
+
+    if let Err(e) = history.record_run(
+        &session.node_id,
+        project_id,
+        &session.agent,
+        session.created_at,
+        chrono::Utc::now().timestamp_millis(),
+        event.exit_code,
+        event.success,
+        &event.output,
+        &event.check_results,
+        Some(&replay),
+    ) {
+        tracing::warn!("Failed to record run history for {}: {}", session.id, e);
+    }
 }
 
 /// Start the background monitor that detects agent completion and input waiting.
+/// This loop also serves as the session supervisor's tick: it keeps each session's
+/// `Worker` status (Active/Idle/Dead) in sync and drains pending pause/resume/cancel
+/// requests made through `Supervisor`.
 pub fn start_monitor(
     window: tauri::WebviewWindow,
     session_manager: SessionManager,
     app_state: AppState,
+    supervisor: Supervisor,
+    notifier: Notifier,
+    history: Arc<HistoryStore>,
 ) {
     let app_handle = window.app_handle().clone();
 
@@ -108,6 +208,23 @@ pub fn start_monitor(
                 .collect::<Vec<_>>();
 
             for session in sessions {
+                supervisor
+                    .ensure_registered(&session.id, &session.node_id)
+                    .await;
+
+                // Drain any pending pause/resume/cancel request before doing anything else.
+                if let Some(WorkerControl::Cancel) =
+                    supervisor.try_recv_control(&session.id).await
+                {
+                    tracing::info!("Cancelling session {} via supervisor", session.id);
+                    if let Err(e) = session_manager.kill_session(&session.id).await {
+                        tracing::warn!("Failed to cancel session {}: {}", session.id, e);
+                    }
+                    supervisor.mark_dead(&session.id).await;
+                    supervisor.remove(&session.id).await;
+                    continue;
+                }
+
                 let exit_file = format!("/tmp/orchestra-sessions/{}.exit", session.id);
 
                 // Agent completed (exit code file written by wrapper)
@@ -124,17 +241,23 @@ pub fn start_monitor(
                         .and_then(|s| s.trim().parse::<i32>().ok())
                         .unwrap_or(-1);
 
-                    let output = tmux_capture_pane(session.id.clone(), 1000)
+                    let output = capture_pane(&session_manager, session.id.clone(), session.backend, 1000)
                         .await
                         .map(|s| s.trim().to_string())
                         .unwrap_or_default();
 
-                    let (node_checks, _label) =
+                    let (node_checks, _label, notify_rules, project_id) =
                         get_node_checks_and_label(&app_state, &session.node_id).await;
 
-                    let cwd = session.cwd.clone();
+                    let check_ctx = checks::CheckContext {
+                        cwd: session.cwd.clone(),
+                        output: output.clone(),
+                        exit_code,
+                        session_id: session.id.clone(),
+                        node_id: session.node_id.clone(),
+                    };
                     let check_results_join = task::spawn_blocking(move || {
-                        checks::run_checks(&node_checks, cwd.as_deref())
+                        checks::run_checks(&node_checks, &check_ctx)
                     })
                     .await;
                     let (check_results, all_checks_passed) = match check_results_join {
@@ -165,28 +288,39 @@ pub fn start_monitor(
                     if let Err(e) = window.emit("session://completed", &event) {
                         tracing::error!("Failed to emit session completed event: {}", e);
                     }
+                    notifier.notify_completed(&notify_rules, project_id.as_deref(), &event).await;
+                    record_run_history(&history, &session, project_id.as_deref(), &event);
 
                     // Keep the tmux session (user may inspect) but remove the exit file marker.
                     if let Err(e) = tokio::fs::remove_file(&exit_file).await {
                         tracing::warn!("Failed to remove exit file {}: {}", exit_file, e);
                     }
 
+                    supervisor.mark_dead(&session.id).await;
+                    supervisor.remove(&session.id).await;
+
                     continue;
                 }
 
-                // Session died unexpectedly (user killed tmux session, etc.)
-                if !tmux_session_exists(session.id.clone()).await {
+                // Session died unexpectedly (user killed the underlying tmux/PTY session, etc.)
+                if !session_exists(&session_manager, session.id.clone(), session.backend).await {
                     tracing::info!(
                         "Detected dead session {} (node {})",
                         session.id,
                         session.node_id
                     );
 
-                    let (node_checks, _label) =
+                    let (node_checks, _label, notify_rules, project_id) =
                         get_node_checks_and_label(&app_state, &session.node_id).await;
-                    let cwd = session.cwd.clone();
+                    let check_ctx = checks::CheckContext {
+                        cwd: session.cwd.clone(),
+                        output: "Session terminated".into(),
+                        exit_code: -1,
+                        session_id: session.id.clone(),
+                        node_id: session.node_id.clone(),
+                    };
                     let check_results_join = task::spawn_blocking(move || {
-                        checks::run_checks(&node_checks, cwd.as_deref())
+                        checks::run_checks(&node_checks, &check_ctx)
                     })
                     .await;
                     let (check_results, all_checks_passed) = match check_results_join {
@@ -213,13 +347,21 @@ pub fn start_monitor(
                     if let Err(e) = window.emit("session://completed", &event) {
                         tracing::error!("Failed to emit session completed event: {}", e);
                     }
+                    notifier.notify_completed(&notify_rules, project_id.as_deref(), &event).await;
+                    record_run_history(&history, &session, project_id.as_deref(), &event);
 
                     session_manager.remove_session(&session.id).await;
+                    supervisor.mark_dead(&session.id).await;
+                    supervisor.remove(&session.id).await;
                     continue;
                 }
 
+                // Still alive -- bump its persisted heartbeat (see `SessionManager::heartbeat`)
+                // before doing anything else with it this tick.
+                session_manager.heartbeat(&session.id).await;
+
                 // Capture output for staleness detection and for clearing AwaitingInput when output resumes.
-                let output = match tmux_capture_pane(session.id.clone(), 50).await {
+                let output = match capture_pane(&session_manager, session.id.clone(), session.backend, 50).await {
                     Ok(o) => o,
                     Err(e) => {
                         tracing::warn!("Failed to capture pane for {}: {}", session.id, e);
@@ -236,6 +378,10 @@ pub fn start_monitor(
                     None => continue,
                 };
 
+                supervisor
+                    .note_tick(&session.id, update.is_stale, update.stale_count)
+                    .await;
+
                 // If output changed while we were awaiting input, clear the inbox item.
                 if update.cleared_awaiting_input {
                     if let Err(e) = window.emit(
@@ -256,12 +402,20 @@ pub fn start_monitor(
                 }
 
                 if update.is_stale && update.stale_count >= STALE_THRESHOLD {
-                    let detection = detect_input_waiting(&output, &session.agent);
+                    let variables = get_project_variables(&app_state, &session.node_id).await;
+                    let detection = detect_input_waiting(&output, &session.agent, &variables);
                     if !detection.waiting_for_input {
                         continue;
                     }
 
-                    let (_checks, node_label) =
+                    tracing::debug!(
+                        "Session {} awaiting input (confidence {:.2}, rule {:?})",
+                        session.id,
+                        detection.confidence,
+                        detection.matched_rule
+                    );
+
+                    let (_checks, node_label, notify_rules, project_id) =
                         get_node_checks_and_label(&app_state, &session.node_id).await;
                     let node_label = node_label.unwrap_or_else(|| "Agent".to_string());
 
@@ -308,6 +462,7 @@ pub fn start_monitor(
                     if let Err(e) = window.emit("session://awaiting_input", &event) {
                         tracing::error!("Failed to emit awaiting input event: {}", e);
                     }
+                    notifier.notify_awaiting_input(&notify_rules, project_id.as_deref(), &event).await;
                 }
             }
         }
@@ -0,0 +1,279 @@
+//! Fans `SessionCompletedEvent`/`SessionAwaitingInputEvent` out to external sinks
+//! (webhook, desktop notification, shell command) configured per project via
+//! `Project::notify`, in addition to the Tauri window emitter the monitor already uses.
+
+use std::process::Stdio;
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{sleep, Duration};
+
+use crate::commands::projects::{NotifyOn, NotifyRule, NotifySink};
+use crate::db::Database;
+
+use super::events::{NotificationCreatedEvent, SessionAwaitingInputEvent, SessionCompletedEvent};
+
+/// Tauri event every persisted notification is also emitted under, for an always-on
+/// in-app notification center independent of a project's configured `NotifySink`s.
+const NOTIFICATION_CREATED_EVENT: &str = "notification://created";
+
+/// Backoff schedule for retrying a failed webhook POST before giving up on it.
+const WEBHOOK_RETRY_DELAYS: &[Duration] = &[
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(15),
+];
+
+/// Env vars that must never appear in a payload handed to an external sink.
+const REDACTED_ENV_VARS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "OPENAI_API_KEY",
+    "GOOGLE_API_KEY",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+];
+
+#[derive(Clone)]
+pub struct Notifier {
+    app_handle: AppHandle,
+    db: Arc<Database>,
+}
+
+impl Notifier {
+    pub fn new(app_handle: AppHandle, db: Arc<Database>) -> Self {
+        Self { app_handle, db }
+    }
+
+    pub async fn notify_completed(
+        &self,
+        rules: &[NotifyRule],
+        project_id: Option<&str>,
+        event: &SessionCompletedEvent,
+    ) {
+        let payload = serde_json::json!({
+            "on": "completed",
+            "sessionId": event.session_id,
+            "nodeId": event.node_id,
+            "success": event.success,
+            "exitCode": event.exit_code,
+            "output": redact(&event.output),
+            "allChecksPassed": event.all_checks_passed,
+        });
+
+        let title = if event.success {
+            "Orchestra: run completed"
+        } else {
+            "Orchestra: run finished with failures"
+        };
+        let body = format!("Node {} finished (exit {})", event.node_id, event.exit_code);
+        let priority = if event.success { "normal" } else { "high" };
+
+        self.persist_and_emit(
+            "completed",
+            project_id,
+            Some(&event.node_id),
+            &body,
+            priority,
+        );
+        self.dispatch(rules, NotifyOn::Completed, &payload, title, &body)
+            .await;
+    }
+
+    pub async fn notify_awaiting_input(
+        &self,
+        rules: &[NotifyRule],
+        project_id: Option<&str>,
+        event: &SessionAwaitingInputEvent,
+    ) {
+        let payload = serde_json::json!({
+            "on": "awaitingInput",
+            "sessionId": event.session_id,
+            "nodeId": event.node_id,
+            "nodeLabel": event.node_label,
+            "detectedQuestion": event.detected_question.as_deref().map(redact),
+        });
+
+        let title = format!("{} needs input", event.node_label);
+        let body = event
+            .detected_question
+            .as_deref()
+            .unwrap_or("Agent is waiting for your response");
+
+        // Blocks the node's progress until answered, so it always outranks a completion.
+        self.persist_and_emit(
+            "awaiting_input",
+            project_id,
+            Some(&event.node_id),
+            body,
+            "high",
+        );
+        self.dispatch(rules, NotifyOn::AwaitingInput, &payload, &title, body)
+            .await;
+    }
+
+    /// Write a row to `notification_events` and push it out over the always-on in-app
+    /// event stream, regardless of whether `project_id` has any `NotifySink`s configured.
+    /// Best-effort: a project-less event (e.g. a node we couldn't resolve back to its
+    /// project) is skipped silently and a failed write is logged and otherwise ignored,
+    /// since the
+    /// Tauri window emit the caller already did is the notification of record either way.
+    fn persist_and_emit(
+        &self,
+        event_type: &str,
+        project_id: Option<&str>,
+        node_id: Option<&str>,
+        message: &str,
+        priority: &str,
+    ) {
+        let Some(project_id) = project_id else {
+            return;
+        };
+
+        match self
+            .db
+            .record_notification(event_type, project_id, node_id, message, priority)
+        {
+            Ok(record) => {
+                let event = NotificationCreatedEvent {
+                    id: record.id,
+                    event_type: record.event_type,
+                    project_id: record.project_id,
+                    node_id: record.node_id,
+                    message: record.message,
+                    priority: record.priority,
+                    created_at: record.created_at,
+                };
+                if let Err(e) = self.app_handle.emit(NOTIFICATION_CREATED_EVENT, &event) {
+                    tracing::warn!("Failed to emit notification created event: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to persist notification: {}", e),
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        rules: &[NotifyRule],
+        on: NotifyOn,
+        payload: &serde_json::Value,
+        title: &str,
+        body: &str,
+    ) {
+        for rule in rules.iter().filter(|r| r.on == on) {
+            match &rule.sink {
+                NotifySink::Webhook { url } => self.send_webhook(url, payload).await,
+                NotifySink::Desktop => self.send_desktop(title, body),
+                NotifySink::Command { cmd } => self.run_command(cmd, payload).await,
+            }
+        }
+    }
+
+    async fn send_webhook(&self, url: &str, payload: &serde_json::Value) {
+        let body = payload.to_string();
+        let attempts = std::iter::once(Duration::ZERO).chain(WEBHOOK_RETRY_DELAYS.iter().copied());
+
+        for (attempt, delay) in attempts.enumerate() {
+            if attempt > 0 {
+                sleep(delay).await;
+            }
+            match post_json(url, &body).await {
+                Ok(()) => return,
+                Err(e) => {
+                    tracing::warn!(
+                        "Webhook notify to {} failed (attempt {}): {}",
+                        url,
+                        attempt + 1,
+                        e
+                    );
+                }
+            }
+        }
+
+        tracing::error!(
+            "Webhook notify to {} gave up after {} attempts",
+            url,
+            WEBHOOK_RETRY_DELAYS.len() + 1
+        );
+    }
+
+    fn send_desktop(&self, title: &str, body: &str) {
+        if let Err(e) = self
+            .app_handle
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+        {
+            tracing::warn!("Failed to show desktop notification: {}", e);
+        }
+    }
+
+    async fn run_command(&self, cmd: &str, payload: &serde_json::Value) {
+        let mut command = Command::new("sh");
+        command.args(["-c", cmd]);
+        command.env("ORCHESTRA_EVENT", payload.to_string());
+
+        match command.status().await {
+            Ok(status) if !status.success() => {
+                tracing::warn!("Notify command `{}` exited with {}", cmd, status);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to run notify command `{}`: {}", cmd, e),
+        }
+    }
+}
+
+/// POST `body` as the request body of a JSON webhook call, shelling out to `curl` the same
+/// way the rest of this codebase reaches for external CLIs instead of an HTTP client crate.
+async fn post_json(url: &str, body: &str) -> Result<(), String> {
+    let mut child = Command::new("curl")
+        .args([
+            "-fsS",
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to spawn curl: {e}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(body.as_bytes())
+            .await
+            .map_err(|e| format!("failed to write webhook body: {e}"))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("curl failed: {e}"))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Strip any configured API key's value out of `text` before it leaves the process.
+fn redact(text: &str) -> String {
+    let mut redacted = text.to_string();
+    for var in REDACTED_ENV_VARS {
+        if let Ok(value) = std::env::var(var) {
+            if !value.is_empty() {
+                redacted = redacted.replace(&value, "[REDACTED]");
+            }
+        }
+    }
+    redacted
+}
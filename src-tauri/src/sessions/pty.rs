@@ -0,0 +1,229 @@
+//! Native PTY-backed interactive sessions, for machines without `tmux`. Each session
+//! owns a real pseudo-terminal (via `portable-pty`); a background thread drains its
+//! output into a bounded ring buffer so `capture_output` works without tmux's scrollback,
+//! and `resize` propagates window-size changes straight to the child process, which
+//! plain `send-keys` to a tmux pane cannot do.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+
+use super::backend::{BackendError, InteractiveBackend};
+
+/// Cap on how many bytes of output we retain per session. Old bytes are dropped from
+/// the front once this is exceeded; this is a live-tail buffer, not a full transcript.
+const RING_BUFFER_CAP: usize = 1024 * 1024;
+
+/// Backing store for `capture_output` (tail by line count) and `read_new_output` (tail by
+/// byte cursor). `total_written` only ever grows, so a cursor from a previous
+/// `read_new_output` call stays meaningful even after old bytes are evicted from `data`.
+struct RingBuffer {
+    data: VecDeque<u8>,
+    total_written: u64,
+}
+
+struct PtySessionHandle {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    buffer: Arc<Mutex<RingBuffer>>,
+}
+
+#[derive(Clone, Default)]
+pub struct PtyBackend {
+    sessions: Arc<Mutex<HashMap<String, PtySessionHandle>>>,
+}
+
+impl PtyBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl InteractiveBackend for PtyBackend {
+    fn create_session(
+        &self,
+        session_id: &str,
+        command: &str,
+        cwd: Option<&str>,
+    ) -> Result<(), BackendError> {
+        let pty_system = native_pty_system();
+        let pair = pty_system
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| BackendError(format!("Failed to open PTY: {e}")))?;
+
+        let mut cmd = CommandBuilder::new("sh");
+        cmd.arg("-c");
+        cmd.arg(command);
+        if let Some(dir) = cwd {
+            cmd.cwd(dir);
+        }
+
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| BackendError(format!("Failed to spawn in PTY: {e}")))?;
+
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| BackendError(format!("Failed to take PTY writer: {e}")))?;
+
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| BackendError(format!("Failed to clone PTY reader: {e}")))?;
+
+        let buffer = Arc::new(Mutex::new(RingBuffer {
+            data: VecDeque::new(),
+            total_written: 0,
+        }));
+        let reader_buffer = buffer.clone();
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 4096];
+            loop {
+                match reader.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        let mut guard = reader_buffer.lock().unwrap();
+                        guard.data.extend(chunk[..n].iter().copied());
+                        guard.total_written += n as u64;
+                        let overflow = guard.data.len().saturating_sub(RING_BUFFER_CAP);
+                        if overflow > 0 {
+                            guard.data.drain(..overflow);
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        self.sessions.lock().unwrap().insert(
+            session_id.to_string(),
+            PtySessionHandle {
+                master: pair.master,
+                writer,
+                child,
+                buffer,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn capture_output(&self, session_id: &str, lines: usize) -> Result<String, BackendError> {
+        let guard = self.sessions.lock().unwrap();
+        let session = guard
+            .get(session_id)
+            .ok_or_else(|| BackendError(format!("no PTY session {session_id}")))?;
+        let bytes: Vec<u8> = session.buffer.lock().unwrap().data.iter().copied().collect();
+        let text = String::from_utf8_lossy(&bytes);
+        Ok(text
+            .lines()
+            .rev()
+            .take(lines)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn send_input(&self, session_id: &str, input: &str) -> Result<(), BackendError> {
+        let mut guard = self.sessions.lock().unwrap();
+        let session = guard
+            .get_mut(session_id)
+            .ok_or_else(|| BackendError(format!("no PTY session {session_id}")))?;
+        session
+            .writer
+            .write_all(input.as_bytes())
+            .and_then(|_| session.writer.write_all(b"\r"))
+            .and_then(|_| session.writer.flush())
+            .map_err(|e| BackendError(format!("Failed to write to PTY: {e}")))
+    }
+
+    fn resize(&self, session_id: &str, rows: u16, cols: u16) -> Result<(), BackendError> {
+        let guard = self.sessions.lock().unwrap();
+        let session = guard
+            .get(session_id)
+            .ok_or_else(|| BackendError(format!("no PTY session {session_id}")))?;
+        session
+            .master
+            .resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| BackendError(format!("Failed to resize PTY: {e}")))
+    }
+
+    fn kill_session(&self, session_id: &str) -> Result<(), BackendError> {
+        let mut guard = self.sessions.lock().unwrap();
+        if let Some(mut session) = guard.remove(session_id) {
+            session
+                .child
+                .kill()
+                .map_err(|e| BackendError(format!("Failed to kill PTY child: {e}")))?;
+        }
+        Ok(())
+    }
+
+    fn session_exists(&self, session_id: &str) -> bool {
+        let mut guard = self.sessions.lock().unwrap();
+        match guard.get_mut(session_id) {
+            Some(session) => !matches!(session.child.try_wait(), Ok(Some(_))),
+            None => false,
+        }
+    }
+
+    fn pane_pid(&self, session_id: &str) -> Result<i32, BackendError> {
+        let guard = self.sessions.lock().unwrap();
+        let session = guard
+            .get(session_id)
+            .ok_or_else(|| BackendError(format!("no PTY session {session_id}")))?;
+        session
+            .child
+            .process_id()
+            .map(|pid| pid as i32)
+            .ok_or_else(|| BackendError("PTY child has no pid (already reaped?)".to_string()))
+    }
+
+    fn attach_command(&self, _session_id: &str, _detach: bool) -> Option<String> {
+        // No separate server process to attach an external terminal to; the Orchestra
+        // UI's own terminal view is the only client for a PTY-backed session.
+        None
+    }
+}
+
+impl PtyBackend {
+    /// Output appended since `cursor` (a byte offset into this session's total output
+    /// stream, as returned by a previous call), plus the cursor to pass next time. Lets a
+    /// caller tail a live PTY session without re-reading its whole scrollback on every
+    /// poll, unlike `capture_output`. If the ring buffer has since evicted bytes the
+    /// caller never saw, this returns everything still buffered instead of erroring.
+    pub fn read_new_output(
+        &self,
+        session_id: &str,
+        cursor: u64,
+    ) -> Result<(String, u64), BackendError> {
+        let guard = self.sessions.lock().unwrap();
+        let session = guard
+            .get(session_id)
+            .ok_or_else(|| BackendError(format!("no PTY session {session_id}")))?;
+
+        let buffer = session.buffer.lock().unwrap();
+        let dropped = buffer.total_written.saturating_sub(buffer.data.len() as u64);
+        let skip = cursor.max(dropped).saturating_sub(dropped) as usize;
+        let bytes: Vec<u8> = buffer.data.iter().skip(skip).copied().collect();
+
+        Ok((String::from_utf8_lossy(&bytes).into_owned(), buffer.total_written))
+    }
+}
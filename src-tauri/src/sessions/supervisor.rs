@@ -0,0 +1,234 @@
+//! Session supervisor: a registry of per-session `Worker` handles sitting on top of
+//! `SessionManager`, so the UI can see whether an agent is actively producing output,
+//! idling while awaiting input, or dead, and can pause/resume/cancel a specific run.
+//!
+//! The monitor's polling loop (`sessions::monitor`) drives worker state transitions from
+//! the same staleness/exit-file detection it already performs, and checks each worker's
+//! control channel for a pending `Pause`/`Resume`/`Cancel` request on every tick.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Mutex};
+
+use super::backend::InteractiveBackend;
+use super::manager::SessionManager;
+
+/// Coarse-grained liveness for a supervised session, derived from the richer
+/// `SessionStatus` plus output staleness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerState {
+    /// Producing output, or too recently started to judge.
+    Active,
+    /// Output has gone stale (covers both "awaiting input" and a silent hang).
+    Idle,
+    /// The underlying tmux session is gone.
+    Dead,
+}
+
+/// A request sent to a running worker's control channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerControl {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+/// Snapshot of a worker's status for the UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerStatus {
+    pub session_id: String,
+    pub node_id: String,
+    pub state: WorkerState,
+    pub last_activity_at: i64,
+    pub stale_count: u32,
+    pub paused: bool,
+}
+
+struct Worker {
+    node_id: String,
+    control_tx: mpsc::UnboundedSender<WorkerControl>,
+    control_rx: mpsc::UnboundedReceiver<WorkerControl>,
+    state: WorkerState,
+    last_activity_at: i64,
+    stale_count: u32,
+    paused: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    workers: Arc<Mutex<HashMap<String, Worker>>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new worker for `session_id`. Replaces any previous worker for the
+    /// same id (e.g. a re-run that reuses the session id).
+    pub async fn register(&self, session_id: &str, node_id: &str) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let worker = Worker {
+            node_id: node_id.to_string(),
+            control_tx: tx,
+            control_rx: rx,
+            state: WorkerState::Active,
+            last_activity_at: chrono::Utc::now().timestamp_millis(),
+            stale_count: 0,
+            paused: false,
+        };
+        self.workers
+            .lock()
+            .await
+            .insert(session_id.to_string(), worker);
+    }
+
+    /// Drain any pending control request for `session_id` (non-blocking). The monitor
+    /// calls this each tick so a `Cancel` can be routed through `SessionManager::kill_session`.
+    pub async fn try_recv_control(&self, session_id: &str) -> Option<WorkerControl> {
+        let mut guard = self.workers.lock().await;
+        guard.get_mut(session_id)?.control_rx.try_recv().ok()
+    }
+
+    /// Register a worker for `session_id` only if one doesn't already exist, so the
+    /// monitor can lazily back-fill tracking for sessions it didn't itself create.
+    pub async fn ensure_registered(&self, session_id: &str, node_id: &str) {
+        if self.workers.lock().await.contains_key(session_id) {
+            return;
+        }
+        self.register(session_id, node_id).await;
+    }
+
+    pub async fn list_workers(&self) -> Vec<WorkerStatus> {
+        self.workers
+            .lock()
+            .await
+            .iter()
+            .map(|(session_id, w)| WorkerStatus {
+                session_id: session_id.clone(),
+                node_id: w.node_id.clone(),
+                state: w.state,
+                last_activity_at: w.last_activity_at,
+                stale_count: w.stale_count,
+                paused: w.paused,
+            })
+            .collect()
+    }
+
+    /// Update a worker's derived state from the monitor's staleness detection.
+    pub async fn note_tick(&self, session_id: &str, is_stale: bool, stale_count: u32) {
+        if let Some(worker) = self.workers.lock().await.get_mut(session_id) {
+            worker.stale_count = stale_count;
+            if !is_stale {
+                worker.last_activity_at = chrono::Utc::now().timestamp_millis();
+            }
+            worker.state = if worker.paused {
+                WorkerState::Idle
+            } else if is_stale {
+                WorkerState::Idle
+            } else {
+                WorkerState::Active
+            };
+        }
+    }
+
+    pub async fn mark_dead(&self, session_id: &str) -> Option<()> {
+        let mut guard = self.workers.lock().await;
+        let worker = guard.get_mut(session_id)?;
+        worker.state = WorkerState::Dead;
+        Some(())
+    }
+
+    pub async fn remove(&self, session_id: &str) {
+        self.workers.lock().await.remove(session_id);
+    }
+
+    async fn send(&self, session_id: &str, control: WorkerControl) -> Result<(), String> {
+        let guard = self.workers.lock().await;
+        let worker = guard
+            .get(session_id)
+            .ok_or_else(|| format!("No worker registered for session {session_id}"))?;
+        worker
+            .control_tx
+            .send(control)
+            .map_err(|_| "Worker control channel closed".to_string())
+    }
+
+    /// Pause a worker: stop its underlying process with SIGSTOP so it can be resumed later.
+    pub async fn pause(
+        &self,
+        session_manager: &SessionManager,
+        session_id: &str,
+    ) -> Result<(), String> {
+        self.send(session_id, WorkerControl::Pause).await?;
+        send_pane_signal(session_manager, session_id, PauseSignal::Stop).await?;
+        if let Some(worker) = self.workers.lock().await.get_mut(session_id) {
+            worker.paused = true;
+            worker.state = WorkerState::Idle;
+        }
+        Ok(())
+    }
+
+    /// Resume a previously-paused worker with SIGCONT.
+    pub async fn resume(
+        &self,
+        session_manager: &SessionManager,
+        session_id: &str,
+    ) -> Result<(), String> {
+        self.send(session_id, WorkerControl::Resume).await?;
+        send_pane_signal(session_manager, session_id, PauseSignal::Continue).await?;
+        if let Some(worker) = self.workers.lock().await.get_mut(session_id) {
+            worker.paused = false;
+            worker.state = WorkerState::Active;
+        }
+        Ok(())
+    }
+
+    /// Ask a worker to cancel; the monitor observes this on its next tick and routes
+    /// the actual session teardown through `SessionManager::kill_session`.
+    pub async fn cancel(&self, session_id: &str) -> Result<(), String> {
+        self.send(session_id, WorkerControl::Cancel).await
+    }
+}
+
+enum PauseSignal {
+    Stop,
+    Continue,
+}
+
+async fn send_pane_signal(
+    session_manager: &SessionManager,
+    session_id: &str,
+    signal: PauseSignal,
+) -> Result<(), String> {
+    let backend_kind = session_manager
+        .backend_kind(session_id)
+        .await
+        .ok_or_else(|| format!("Unknown session {session_id}"))?;
+    let pid = session_manager
+        .backend(backend_kind)
+        .pane_pid(session_id)
+        .map_err(|e| e.0)?;
+
+    #[cfg(unix)]
+    {
+        let raw = match signal {
+            PauseSignal::Stop => libc::SIGSTOP,
+            PauseSignal::Continue => libc::SIGCONT,
+        };
+        if unsafe { libc::kill(pid, raw) } != 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (pid, signal);
+        Err("Pause/resume is only supported on Unix".to_string())
+    }
+}
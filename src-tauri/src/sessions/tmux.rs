@@ -33,6 +33,28 @@ pub fn create_session(session_id: &str, command: &str, cwd: Option<&str>) -> Res
     Ok(())
 }
 
+/// All tmux session names currently alive, for reconciling persisted sessions against
+/// reality on startup (see `SessionManager::recover_sessions`). Empty (not an error) when
+/// tmux is missing or has no sessions at all.
+pub fn list_sessions() -> Vec<String> {
+    if !is_available() {
+        return Vec::new();
+    }
+
+    Command::new("tmux")
+        .args(["list-sessions", "-F", "#{session_name}"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| line.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 pub fn session_exists(session_id: &str) -> bool {
     Command::new("tmux")
         .args(["has-session", "-t", session_id])
@@ -129,6 +151,39 @@ pub fn kill_session(session_id: &str) -> Result<(), TmuxError> {
     Ok(())
 }
 
-pub fn get_attach_command(session_id: &str) -> String {
-    format!("tmux attach -t {}", session_id)
+/// Shell command to attach to `session_id`. With `detach`, passes `-d` so attaching here
+/// kicks any other client already attached to the session off instead of sharing the view.
+pub fn get_attach_command(session_id: &str, detach: bool) -> String {
+    if detach {
+        format!("tmux attach -d -t {}", session_id)
+    } else {
+        format!("tmux attach -t {}", session_id)
+    }
+}
+
+/// PID of the process running in a session's (first) pane, for sending signals
+/// directly to the agent process (e.g. SIGSTOP/SIGCONT for pause/resume).
+pub fn pane_pid(session_id: &str) -> Result<i32, TmuxError> {
+    if !is_available() {
+        return Err(TmuxError(
+            "tmux is not installed or not on PATH".to_string(),
+        ));
+    }
+
+    let output = Command::new("tmux")
+        .args(["list-panes", "-t", session_id, "-F", "#{pane_pid}"])
+        .output()
+        .map_err(|e| TmuxError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(TmuxError(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .and_then(|line| line.trim().parse::<i32>().ok())
+        .ok_or_else(|| TmuxError("no panes found for session".to_string()))
 }
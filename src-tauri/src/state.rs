@@ -1,22 +1,65 @@
-use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, RwLock};
 
 use crate::commands::projects::Project;
+use crate::executors::remote_connection::RemoteConnectionManager;
+use crate::executors::{ExecutionRegistry, RemotePtyInputRegistry};
+use crate::session_log::SessionLogStore;
+use crate::sessions::agent_state::{StateReporter, StateTransition};
 
 #[derive(Clone)]
 pub struct AppState {
     pub projects: Arc<RwLock<HashMap<String, Project>>>,
     pub processes: Arc<Mutex<HashMap<String, Arc<RunningProcess>>>>,
+    /// Per-session VT100 screen state, fed by `pump_output`/`pump_error`/`pump_pty_output`
+    /// in `commands::execution`; see `terminal::TerminalEmulator`. Keyed the same as
+    /// `processes` and removed alongside it once a session's process exits.
+    pub terminals: Arc<Mutex<HashMap<String, Arc<Mutex<crate::terminal::TerminalEmulator>>>>>,
+    /// Tracks pids of in-flight headless executions, keyed by node id, so `executors::execute`
+    /// can apply a node's `OnBusyUpdate` policy before spawning a re-run.
+    pub execution_registry: ExecutionRegistry,
+    /// Shared registry of persistent SSH ControlMaster connections for the remote
+    /// executor backends; see `executors::remote_connection`. Threaded into each
+    /// `ExecuteRequest` as `remote_connections` so repeated runs against the same host
+    /// reuse one multiplexed connection.
+    pub remote_connections: RemoteConnectionManager,
+    /// Registry of live remote-PTY session input channels; see
+    /// `executors::RemotePtyInputRegistry`. Threaded into each `ExecuteRequest` as
+    /// `remote_pty_inputs` so `send_remote_pty_input` can forward keystrokes into a
+    /// session already running.
+    pub remote_pty_inputs: RemotePtyInputRegistry,
+    /// Append-only per-session log files backing `commands::execution::get_session_log`/
+    /// `list_session_logs`; see `session_log::SessionLogStore`.
+    pub session_logs: SessionLogStore,
+    /// Every node state change goes through this sender; see `sessions::agent_state`.
+    /// Cloning `AppState` clones the sender too, so any command/executor that holds an
+    /// `AppState` can report a transition without a separate handle threaded through.
+    pub state_tx: StateReporter,
+    /// Receiving half of `state_tx`, taken exactly once by `sessions::agent_state::start_state_sink`
+    /// from `lib.rs`'s `setup()` (the one place a real `tauri::WebviewWindow` exists
+    /// alongside this `AppState`). `AppState::new()` runs before that window exists, so
+    /// the channel is built here and the receiver handed off later.
+    state_rx: Arc<std::sync::Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<StateTransition>>>>,
     projects_file: Arc<PathBuf>,
     workspaces_dir: Arc<PathBuf>,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        let (projects_file, workspaces_dir) = app_storage_paths();
+        let (projects_file, workspaces_dir, logs_dir) = app_storage_paths();
+        let session_logs = SessionLogStore::new(logs_dir.clone()).unwrap_or_else(|e| {
+            tracing::warn!("Failed to create session logs dir {:?}: {e}", logs_dir);
+            SessionLogStore::new(std::env::temp_dir().join("orchestra-session-logs"))
+                .expect("failed to create fallback session logs dir")
+        });
         let projects = load_projects_from_disk(&projects_file)
             .unwrap_or_else(|e| {
                 tracing::warn!("Failed to load projects from disk: {e}");
@@ -26,14 +69,29 @@ impl AppState {
             .map(|p| (p.id.clone(), p))
             .collect::<HashMap<_, _>>();
 
+        let (state_tx, state_rx) = tokio::sync::mpsc::unbounded_channel();
+
         Self {
             projects: Arc::new(RwLock::new(projects)),
             processes: Arc::new(Mutex::new(HashMap::new())),
+            terminals: Arc::new(Mutex::new(HashMap::new())),
+            execution_registry: ExecutionRegistry::new(),
+            remote_connections: RemoteConnectionManager::new(),
+            remote_pty_inputs: RemotePtyInputRegistry::new(),
+            session_logs,
+            state_tx,
+            state_rx: Arc::new(std::sync::Mutex::new(Some(state_rx))),
             projects_file: Arc::new(projects_file),
             workspaces_dir: Arc::new(workspaces_dir),
         }
     }
 
+    /// Hand off the state-transition receiver to its one consumer. Returns `None` if
+    /// already taken -- `start_state_sink` is only ever called once, from `setup()`.
+    pub fn take_state_rx(&self) -> Option<tokio::sync::mpsc::UnboundedReceiver<StateTransition>> {
+        self.state_rx.lock().unwrap().take()
+    }
+
     pub fn projects_file(&self) -> &PathBuf {
         &self.projects_file
     }
@@ -47,39 +105,232 @@ impl AppState {
     }
 }
 
+enum RunningProcessInner {
+    Piped(Option<tokio::process::Child>),
+    /// A PTY-backed child (see `commands::execution::spawn_pty_agent`). `portable_pty`'s
+    /// `Child`/`MasterPty` aren't `tokio::process::Child`, so the child itself is owned by
+    /// the background task that waits on it (mirroring `executors::local`'s split between
+    /// `execute_local` and `execute_local_pty`); this variant keeps only what
+    /// `stop_execution`/`resize_pty` need to reach it from the outside: its pid (stopped
+    /// via `executors::stop_pid`, the same helper used for a tmux pane's process) and its
+    /// PTY master (for a window-size change).
+    Pty {
+        pid: i32,
+        master: Box<dyn portable_pty::MasterPty + Send>,
+        /// Taken from `master` at construction time so `send_input`/`close_input` don't
+        /// need to re-derive it; `None` once `close_input` has dropped it (closing the
+        /// PTY's write side, signalling EOF to the child).
+        writer: Option<Box<dyn std::io::Write + Send>>,
+    },
+    /// An agent run on a remote host over SSH (see `commands::execution::spawn_remote_agent`).
+    /// The "remote process handle" is nothing more than the local `ssh` child: its piped
+    /// stdin/stdout/stderr already ride the SSH channel to the remote command, so
+    /// `send_input`/`wait` work unmodified. Killing or signalling it closes the SSH
+    /// connection rather than reaching the remote pid directly, which the remote shell
+    /// sees as a hangup regardless of which signal was used locally -- good enough to stop
+    /// a run, but not a substitute for a real remote signal if that distinction ever
+    /// matters.
+    Remote {
+        child: Option<tokio::process::Child>,
+        /// Keeps this execution's hold on the shared ControlMaster connection (see
+        /// `executors::remote_connection`) for as long as the process is tracked here.
+        _connection_guard: crate::executors::remote_connection::ConnectionGuard,
+    },
+}
+
 pub struct RunningProcess {
-    child: Mutex<Option<tokio::process::Child>>,
+    inner: Mutex<RunningProcessInner>,
 }
 
 impl RunningProcess {
     pub fn new(child: tokio::process::Child) -> Arc<Self> {
         Arc::new(Self {
-            child: Mutex::new(Some(child)),
+            inner: Mutex::new(RunningProcessInner::Piped(Some(child))),
+        })
+    }
+
+    pub fn new_pty(
+        pid: i32,
+        master: Box<dyn portable_pty::MasterPty + Send>,
+    ) -> Result<Arc<Self>, String> {
+        let writer = master
+            .take_writer()
+            .map_err(|e| format!("failed to take PTY writer: {e}"))?;
+        Ok(Arc::new(Self {
+            inner: Mutex::new(RunningProcessInner::Pty {
+                pid,
+                master,
+                writer: Some(writer),
+            }),
+        }))
+    }
+
+    pub fn new_remote(
+        child: tokio::process::Child,
+        connection_guard: crate::executors::remote_connection::ConnectionGuard,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(RunningProcessInner::Remote {
+                child: Some(child),
+                _connection_guard: connection_guard,
+            }),
         })
     }
 
+    /// Send `signal` once, without waiting for it to take effect or escalating if it
+    /// doesn't. Used by `commands::execution::stop_execution`'s own multi-stage
+    /// escalation (SIGINT, then SIGTERM, only SIGKILL as a last resort), which delivers
+    /// each stage itself rather than delegating the whole sequence to `stop`/`kill`.
+    pub async fn signal(&self, signal: crate::commands::projects::StopSignal) -> Result<(), String> {
+        match &*self.inner.lock().await {
+            RunningProcessInner::Piped(child) | RunningProcessInner::Remote { child, .. } => {
+                let pid = child
+                    .as_ref()
+                    .and_then(|c| c.id())
+                    .ok_or_else(|| "process already exited".to_string())?;
+                crate::executors::send_signal_to_group(pid as i32, signal);
+                Ok(())
+            }
+            RunningProcessInner::Pty { pid, .. } => {
+                crate::executors::send_signal_to_group(*pid, signal);
+                Ok(())
+            }
+        }
+    }
+
     pub async fn kill(&self) -> Result<(), std::io::Error> {
-        let mut guard = self.child.lock().await;
-        if let Some(child) = guard.as_mut() {
-            child.kill().await?;
+        match &mut *self.inner.lock().await {
+            RunningProcessInner::Piped(child) | RunningProcessInner::Remote { child, .. } => {
+                if let Some(child) = child.as_mut() {
+                    child.kill().await?;
+                }
+            }
+            RunningProcessInner::Pty { pid, .. } => {
+                // No grace period: a zero-duration window still sends `Sigterm` first,
+                // but escalates to `SIGKILL` immediately if the process hasn't already
+                // exited by the time that's checked.
+                crate::executors::stop_pid(
+                    *pid,
+                    crate::commands::projects::StopSignal::Sigterm,
+                    std::time::Duration::from_secs(0),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gracefully stop the process: deliver `signal` and wait up to `grace` for it to
+    /// exit on its own before escalating to SIGKILL. See `executors::stop_child`/`stop_pid`.
+    pub async fn stop(
+        &self,
+        signal: crate::commands::projects::StopSignal,
+        grace: std::time::Duration,
+    ) -> Result<(), std::io::Error> {
+        match &mut *self.inner.lock().await {
+            RunningProcessInner::Piped(child) | RunningProcessInner::Remote { child, .. } => {
+                if let Some(child) = child.as_mut() {
+                    crate::executors::stop_child(child, signal, grace).await?;
+                }
+            }
+            RunningProcessInner::Pty { pid, .. } => {
+                crate::executors::stop_pid(*pid, signal, grace).await?;
+            }
         }
         Ok(())
     }
 
     pub async fn wait(&self) -> Result<std::process::ExitStatus, std::io::Error> {
-        let mut guard = self.child.lock().await;
-        if let Some(mut child) = guard.take() {
-            let status = child.wait().await?;
-            Ok(status)
-        } else {
-            Err(std::io::Error::new(
+        let mut guard = self.inner.lock().await;
+        match &mut *guard {
+            RunningProcessInner::Piped(child) | RunningProcessInner::Remote { child, .. } => {
+                if let Some(mut child) = child.take() {
+                    let status = child.wait().await?;
+                    Ok(status)
+                } else {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        "process already awaited",
+                    ))
+                }
+            }
+            RunningProcessInner::Pty { .. } => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
-                "process already awaited",
-            ))
+                "a PTY-backed session is awaited by its own spawning task, not RunningProcess::wait",
+            )),
+        }
+    }
+
+    /// Forward a window-size change to the PTY master. Errors if this session wasn't
+    /// started with `usePty`.
+    pub async fn resize_pty(&self, rows: u16, cols: u16) -> Result<(), String> {
+        match &*self.inner.lock().await {
+            RunningProcessInner::Pty { master, .. } => master
+                .resize(portable_pty::PtySize {
+                    rows,
+                    cols,
+                    pixel_width: 0,
+                    pixel_height: 0,
+                })
+                .map_err(|e| format!("failed to resize pty: {e}")),
+            RunningProcessInner::Piped(_) | RunningProcessInner::Remote { .. } => {
+                Err("session was not started with usePty".to_string())
+            }
+        }
+    }
+
+    /// Write `data` to the process's stdin (or PTY master), optionally followed by a
+    /// newline, so a CLI blocked on a "y/n"-style prompt can be answered.
+    pub async fn send_input(&self, data: &str, newline: bool) -> Result<(), String> {
+        match &mut *self.inner.lock().await {
+            RunningProcessInner::Piped(child) | RunningProcessInner::Remote { child, .. } => {
+                use tokio::io::AsyncWriteExt;
+                let stdin = child
+                    .as_mut()
+                    .and_then(|c| c.stdin.as_mut())
+                    .ok_or_else(|| "stdin is not available".to_string())?;
+                stdin
+                    .write_all(data.as_bytes())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if newline {
+                    stdin.write_all(b"\n").await.map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            }
+            RunningProcessInner::Pty { writer, .. } => {
+                let writer = writer
+                    .as_mut()
+                    .ok_or_else(|| "stdin already closed".to_string())?;
+                writer.write_all(data.as_bytes()).map_err(|e| e.to_string())?;
+                if newline {
+                    writer.write_all(b"\n").map_err(|e| e.to_string())?;
+                }
+                writer.flush().map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    /// Drop the stdin (or PTY writer) handle, signalling EOF to the child.
+    pub async fn close_input(&self) -> Result<(), String> {
+        match &mut *self.inner.lock().await {
+            RunningProcessInner::Piped(child) | RunningProcessInner::Remote { child, .. } => {
+                if let Some(child) = child.as_mut() {
+                    child.stdin = None;
+                }
+                Ok(())
+            }
+            RunningProcessInner::Pty { writer, .. } => {
+                *writer = None;
+                Ok(())
+            }
         }
     }
 }
 
+/// Current on-disk shape of `projects.json`. Bump `CURRENT_PROJECTS_VERSION` and append a
+/// step to `MIGRATIONS` whenever `Project`'s serialized shape changes, rather than
+/// changing this struct's meaning out from under old files.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ProjectsFileV1 {
@@ -87,7 +338,25 @@ struct ProjectsFileV1 {
     projects: Vec<Project>,
 }
 
-fn app_storage_paths() -> (PathBuf, PathBuf) {
+/// The version `ProjectsFileV1` (and `Project` itself) currently implements.
+const CURRENT_PROJECTS_VERSION: u32 = 1;
+
+/// Upgrades a parsed `projects` array by exactly one version. `MIGRATIONS[i]` takes
+/// version `i + 1` to `i + 2` (there's no step for version 0, since 1 is the first
+/// version ever written). Operates on raw JSON so a migration doesn't need last
+/// version's now-deleted Rust types in scope.
+type ProjectsMigration = fn(Vec<serde_json::Value>) -> Result<Vec<serde_json::Value>, String>;
+const MIGRATIONS: &[ProjectsMigration] = &[];
+
+/// Envelope used only to sniff `version` and defer parsing `projects` until we know
+/// whether it needs to be migrated first.
+#[derive(Debug, Deserialize)]
+struct ProjectsFileEnvelope {
+    version: u32,
+    projects: Vec<serde_json::Value>,
+}
+
+fn app_storage_paths() -> (PathBuf, PathBuf, PathBuf) {
     // Prefer OS-appropriate app data dir; fall back to a temp directory.
     let base_dir = ProjectDirs::from("ai", "Orchestra", "Orchestra")
         .map(|d| d.data_dir().to_path_buf())
@@ -106,7 +375,9 @@ fn app_storage_paths() -> (PathBuf, PathBuf) {
         );
     }
 
-    (base_dir.join("projects.json"), workspaces_dir)
+    let logs_dir = base_dir.join("logs");
+
+    (base_dir.join("projects.json"), workspaces_dir, logs_dir)
 }
 
 fn load_projects_from_disk(path: &PathBuf) -> Result<Vec<Project>, String> {
@@ -119,12 +390,88 @@ fn load_projects_from_disk(path: &PathBuf) -> Result<Vec<Project>, String> {
         return Ok(Vec::new());
     }
 
-    let parsed: ProjectsFileV1 =
+    let envelope: ProjectsFileEnvelope =
         serde_json::from_slice(&bytes).map_err(|e| format!("parse failed: {e}"))?;
-    if parsed.version != 1 {
-        return Err(format!("unsupported projects.json version: {}", parsed.version));
+
+    if envelope.version > CURRENT_PROJECTS_VERSION {
+        return Err(format!(
+            "projects.json is version {} but this build only understands up to {}; refusing to load to avoid data loss",
+            envelope.version, CURRENT_PROJECTS_VERSION
+        ));
+    }
+
+    let from_version = envelope.version;
+    let mut version = envelope.version;
+    let mut projects = envelope.projects;
+
+    while version < CURRENT_PROJECTS_VERSION {
+        let step = MIGRATIONS.get(version as usize).ok_or_else(|| {
+            format!("no migration registered to upgrade projects.json from version {version}")
+        })?;
+        projects = step(projects)
+            .map_err(|e| format!("migration from projects.json v{version} failed: {e}"))?;
+        version += 1;
+    }
+
+    let projects: Vec<Project> = projects
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("parse failed after migration: {e}"))?;
+
+    if from_version < CURRENT_PROJECTS_VERSION {
+        backup_pre_migration_file(path, &bytes, from_version)?;
+        write_projects_file_sync(path, &projects)?;
+        tracing::info!(
+            "Migrated projects.json from version {} to {}",
+            from_version,
+            CURRENT_PROJECTS_VERSION
+        );
+    }
+
+    Ok(projects)
+}
+
+/// Save the pre-migration bytes alongside `path` before overwriting it, so a migration
+/// that turns out to be buggy or partial never destroys the user's project graph.
+fn backup_pre_migration_file(path: &Path, bytes: &[u8], from_version: u32) -> Result<(), String> {
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("projects");
+    let backup_path = path.with_file_name(format!("{stem}.v{from_version}.{ts}.bak.json"));
+
+    fs::write(&backup_path, bytes).map_err(|e| format!("backup write failed: {e}"))?;
+    tracing::info!(
+        "Backed up pre-migration projects.json (v{}) to {:?}",
+        from_version,
+        backup_path
+    );
+    Ok(())
+}
+
+/// Synchronous counterpart to `persist_projects_to_disk`, used once at startup to write
+/// a just-migrated file back before `AppState` takes over with the async path. Atomic
+/// for the same reason: write to a tmp file, then rename over the real one.
+fn write_projects_file_sync(path: &Path, projects: &[Project]) -> Result<(), String> {
+    let payload = ProjectsFileV1 {
+        version: CURRENT_PROJECTS_VERSION,
+        projects: projects.to_vec(),
+    };
+    let bytes = serde_json::to_vec_pretty(&payload).map_err(|e| format!("serialize failed: {e}"))?;
+
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, bytes).map_err(|e| format!("write failed: {e}"))?;
+
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(path);
+        fs::rename(&tmp, path).map_err(|e2| format!("rename failed: {e} / retry: {e2}"))?;
     }
-    Ok(parsed.projects)
+    Ok(())
 }
 
 async fn persist_projects_to_disk(
@@ -136,7 +483,7 @@ async fn persist_projects_to_disk(
     list.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
 
     let payload = ProjectsFileV1 {
-        version: 1,
+        version: CURRENT_PROJECTS_VERSION,
         projects: list,
     };
     let bytes = serde_json::to_vec_pretty(&payload).map_err(|e| format!("serialize failed: {e}"))?;
@@ -0,0 +1,461 @@
+//! A minimal server-side VT100/ANSI terminal emulator. Parses raw bytes from a PTY or
+//! piped agent process into a grid of styled cells, so a client that (re)attaches to a
+//! session can render its current screen without replaying every byte it ever wrote.
+//! Handles the escape sequences agent CLIs actually emit for colored, full-screen
+//! output: SGR (`m`) for color/attributes, `H`/`f` for absolute cursor position, `J`/`K`
+//! for erase, and `A`-`D` for relative cursor movement. Any other CSI sequence, and any
+//! two-byte `ESC x` sequence, is consumed and discarded rather than leaking into the grid
+//! as garbage characters; longer non-CSI sequences such as OSC (`ESC ]`) are not
+//! recognised, so the text after their second byte is written to the grid.
+
+use std::collections::{BTreeSet, VecDeque};
+
+use serde::Serialize;
+
+/// How many scrolled-off rows are retained per session.
+const SCROLLBACK_CAP: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Option<u8>,
+    pub bg: Option<u8>,
+    pub bold: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            fg: None,
+            bg: None,
+            bold: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Attrs {
+    fg: Option<u8>,
+    bg: Option<u8>,
+    bold: bool,
+    underline: bool,
+    reverse: bool,
+}
+
+impl Attrs {
+    /// Apply a `CSI ... m` (SGR) parameter list, in order, to these attributes. Bare
+    /// `ESC[m` (no params) is treated as `ESC[0m` (reset), per the spec.
+    fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Attrs::default();
+            return;
+        }
+        for &param in params {
+            match param {
+                0 => *self = Attrs::default(),
+                1 => self.bold = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => self.bold = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                30..=37 => self.fg = Some((param - 30) as u8),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some((param - 40) as u8),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some((param - 90 + 8) as u8),
+                100..=107 => self.bg = Some((param - 100 + 8) as u8),
+                _ => {}
+            }
+        }
+    }
+
+    fn cell(&self, ch: char) -> Cell {
+        Cell {
+            ch,
+            fg: self.fg,
+            bg: self.bg,
+            bold: self.bold,
+            underline: self.underline,
+            reverse: self.reverse,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+enum ParseState {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+}
+
+/// Full current screen contents, as returned by `get_screen` for a client that just
+/// (re)attached and has no prior `execution://screen` deltas to replay.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenSnapshot {
+    pub rows: usize,
+    pub cols: usize,
+    pub cursor_row: usize,
+    pub cursor_col: usize,
+    pub grid: Vec<Vec<Cell>>,
+    /// Number of rows scrolled off the top and retained in scrollback.
+    pub scrollback_len: usize,
+}
+
+/// Per-session VT100 state: a fixed `rows` x `cols` grid, cursor position, current SGR
+/// attributes, a bounded scrollback ring of rows scrolled off the top, and the set of
+/// rows touched since the last `take_dirty_rows` call.
+pub struct TerminalEmulator {
+    rows: usize,
+    cols: usize,
+    grid: Vec<Vec<Cell>>,
+    cursor_row: usize,
+    cursor_col: usize,
+    attrs: Attrs,
+    state: ParseState,
+    params: Vec<u16>,
+    current_param: Option<u16>,
+    scrollback: VecDeque<Vec<Cell>>,
+    dirty: BTreeSet<usize>,
+}
+
+impl TerminalEmulator {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            grid: vec![vec![Cell::default(); cols]; rows],
+            cursor_row: 0,
+            cursor_col: 0,
+            attrs: Attrs::default(),
+            state: ParseState::default(),
+            params: Vec::new(),
+            current_param: None,
+            scrollback: VecDeque::new(),
+            dirty: BTreeSet::new(),
+        }
+    }
+
+    /// Resize the grid in place, clamping the cursor and marking every row dirty (a
+    /// client must fully redraw after a resize anyway).
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        self.grid.resize(rows, vec![Cell::default(); cols]);
+        for row in &mut self.grid {
+            row.resize(cols, Cell::default());
+        }
+        self.rows = rows;
+        self.cols = cols;
+        self.cursor_row = self.cursor_row.min(rows.saturating_sub(1));
+        self.cursor_col = self.cursor_col.min(cols.saturating_sub(1));
+        self.dirty.extend(0..rows);
+    }
+
+    /// Feed raw output bytes through the parser. Multi-byte UTF-8 sequences split across
+    /// two `feed` calls may decode as a stray replacement character; real agent CLIs
+    /// don't split output mid-codepoint often enough for this to matter in practice.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for ch in String::from_utf8_lossy(bytes).chars() {
+            self.feed_char(ch);
+        }
+    }
+
+    fn feed_char(&mut self, ch: char) {
+        match self.state {
+            ParseState::Ground => match ch {
+                '\u{1b}' => self.state = ParseState::Escape,
+                '\r' => self.cursor_col = 0,
+                '\n' => self.line_feed(),
+                '\u{8}' => self.cursor_col = self.cursor_col.saturating_sub(1),
+                _ => self.put_char(ch),
+            },
+            ParseState::Escape => match ch {
+                '[' => {
+                    self.state = ParseState::Csi;
+                    self.params.clear();
+                    self.current_param = None;
+                }
+                _ => self.state = ParseState::Ground,
+            },
+            ParseState::Csi => match ch {
+                '0'..='9' => {
+                    let digit = ch as u16 - '0' as u16;
+                    self.current_param = Some(self.current_param.unwrap_or(0) * 10 + digit);
+                }
+                ';' => self.params.push(self.current_param.take().unwrap_or(0)),
+                _ if ch.is_ascii_alphabetic() || ch == '@' || ch == '~' => {
+                    if let Some(p) = self.current_param.take() {
+                        self.params.push(p);
+                    }
+                    self.run_csi(ch);
+                    self.state = ParseState::Ground;
+                }
+                // Intermediate byte (e.g. `?` in `ESC[?25h`): keep collecting until the
+                // final byte; we don't special-case private-mode sequences, so they're
+                // parsed but fall through `run_csi`'s wildcard arm as a no-op.
+                _ => {}
+            },
+        }
+    }
+
+    fn run_csi(&mut self, final_byte: char) {
+        let params = std::mem::take(&mut self.params);
+        match final_byte {
+            'm' => self.attrs.apply_sgr(&params),
+            'H' | 'f' => {
+                let row = params.first().copied().unwrap_or(1).max(1) as usize - 1;
+                let col = params.get(1).copied().unwrap_or(1).max(1) as usize - 1;
+                self.cursor_row = row.min(self.rows.saturating_sub(1));
+                self.cursor_col = col.min(self.cols.saturating_sub(1));
+            }
+            'A' => {
+                let n = params.first().copied().unwrap_or(1).max(1) as usize;
+                self.cursor_row = self.cursor_row.saturating_sub(n);
+            }
+            'B' => {
+                let n = params.first().copied().unwrap_or(1).max(1) as usize;
+                self.cursor_row = (self.cursor_row + n).min(self.rows.saturating_sub(1));
+            }
+            'C' => {
+                let n = params.first().copied().unwrap_or(1).max(1) as usize;
+                self.cursor_col = (self.cursor_col + n).min(self.cols.saturating_sub(1));
+            }
+            'D' => {
+                let n = params.first().copied().unwrap_or(1).max(1) as usize;
+                self.cursor_col = self.cursor_col.saturating_sub(n);
+            }
+            'J' => self.erase_in_display(params.first().copied().unwrap_or(0)),
+            'K' => self.erase_in_line(params.first().copied().unwrap_or(0)),
+            _ => {}
+        }
+    }
+
+    fn erase_in_display(&mut self, mode: u16) {
+        match mode {
+            0 => {
+                self.clear_line_from(self.cursor_row, self.cursor_col);
+                for row in (self.cursor_row + 1)..self.rows {
+                    self.clear_row(row);
+                }
+            }
+            1 => {
+                for row in 0..self.cursor_row {
+                    self.clear_row(row);
+                }
+                self.clear_line_up_to(self.cursor_row, self.cursor_col);
+            }
+            _ => {
+                for row in 0..self.rows {
+                    self.clear_row(row);
+                }
+            }
+        }
+    }
+
+    fn erase_in_line(&mut self, mode: u16) {
+        match mode {
+            0 => self.clear_line_from(self.cursor_row, self.cursor_col),
+            1 => self.clear_line_up_to(self.cursor_row, self.cursor_col),
+            _ => self.clear_row(self.cursor_row),
+        }
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        if let Some(r) = self.grid.get_mut(row) {
+            r.iter_mut().for_each(|c| *c = Cell::default());
+            self.dirty.insert(row);
+        }
+    }
+
+    fn clear_line_from(&mut self, row: usize, col: usize) {
+        if let Some(r) = self.grid.get_mut(row) {
+            for c in r.iter_mut().skip(col) {
+                *c = Cell::default();
+            }
+            self.dirty.insert(row);
+        }
+    }
+
+    fn clear_line_up_to(&mut self, row: usize, col: usize) {
+        if let Some(r) = self.grid.get_mut(row) {
+            for c in r.iter_mut().take(col + 1) {
+                *c = Cell::default();
+            }
+            self.dirty.insert(row);
+        }
+    }
+
+    fn put_char(&mut self, ch: char) {
+        if self.cursor_col >= self.cols {
+            self.line_feed();
+            self.cursor_col = 0;
+        }
+        self.grid[self.cursor_row][self.cursor_col] = self.attrs.cell(ch);
+        self.dirty.insert(self.cursor_row);
+        self.cursor_col += 1;
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor_row + 1 < self.rows {
+            self.cursor_row += 1;
+        } else {
+            let top = self.grid.remove(0);
+            self.scrollback.push_back(top);
+            if self.scrollback.len() > SCROLLBACK_CAP {
+                self.scrollback.pop_front();
+            }
+            self.grid.push(vec![Cell::default(); self.cols]);
+            self.dirty = (0..self.rows).collect();
+        }
+    }
+
+    /// Rows touched since the last call, cleared after reading. Empty if nothing
+    /// changed, which lets a caller skip emitting `execution://screen` entirely.
+    pub fn take_dirty_rows(&mut self) -> Vec<(usize, Vec<Cell>)> {
+        std::mem::take(&mut self.dirty)
+            .into_iter()
+            .filter_map(|row| self.grid.get(row).map(|cells| (row, cells.clone())))
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> ScreenSnapshot {
+        ScreenSnapshot {
+            rows: self.rows,
+            cols: self.cols,
+            cursor_row: self.cursor_row,
+            cursor_col: self.cursor_col,
+            grid: self.grid.clone(),
+            scrollback_len: self.scrollback.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_text(term: &TerminalEmulator, row: usize) -> String {
+        term.grid[row].iter().map(|c| c.ch).collect::<String>()
+    }
+
+    #[test]
+    fn plain_text_advances_the_cursor() {
+        let mut term = TerminalEmulator::new(5, 10);
+        term.feed(b"hi");
+        assert_eq!(row_text(&term, 0).trim_end(), "hi");
+        assert_eq!((term.cursor_row, term.cursor_col), (0, 2));
+    }
+
+    #[test]
+    fn carriage_return_and_line_feed_are_independent() {
+        let mut term = TerminalEmulator::new(5, 10);
+        term.feed(b"ab\r\ncd");
+        assert_eq!(row_text(&term, 0).trim_end(), "ab");
+        assert_eq!(row_text(&term, 1).trim_end(), "cd");
+        assert_eq!((term.cursor_row, term.cursor_col), (1, 2));
+    }
+
+    #[test]
+    fn wrapping_at_the_last_column_wraps_to_the_next_row() {
+        let mut term = TerminalEmulator::new(5, 3);
+        term.feed(b"abcd");
+        assert_eq!(row_text(&term, 0), "abc");
+        assert_eq!(row_text(&term, 1).trim_end(), "d");
+    }
+
+    #[test]
+    fn cursor_position_csi_is_one_indexed_and_clamped() {
+        let mut term = TerminalEmulator::new(5, 5);
+        term.feed(b"\x1b[3;2Hx");
+        assert_eq!(row_text(&term, 2).chars().nth(1), Some('x'));
+
+        // Out-of-range coordinates clamp to the last row/col rather than panicking.
+        term.feed(b"\x1b[99;99Hy");
+        assert_eq!(row_text(&term, 4).chars().nth(4), Some('y'));
+    }
+
+    #[test]
+    fn relative_cursor_movement_is_clamped_to_the_grid() {
+        let mut term = TerminalEmulator::new(5, 5);
+        term.feed(b"\x1b[10A"); // already at row 0; shouldn't underflow
+        assert_eq!(term.cursor_row, 0);
+        term.feed(b"\x1b[2B\x1b[3C");
+        assert_eq!((term.cursor_row, term.cursor_col), (2, 3));
+        term.feed(b"\x1b[10D");
+        assert_eq!(term.cursor_col, 0);
+    }
+
+    #[test]
+    fn erase_in_line_from_cursor_clears_the_rest_of_the_row() {
+        let mut term = TerminalEmulator::new(5, 5);
+        term.feed(b"abcde\r");
+        term.feed(b"\x1b[2C\x1b[K");
+        assert_eq!(row_text(&term, 0), "ab   ");
+    }
+
+    #[test]
+    fn erase_in_display_mode_2_clears_the_whole_screen() {
+        let mut term = TerminalEmulator::new(3, 3);
+        term.feed(b"abc\r\ndef\r\nghi");
+        term.feed(b"\x1b[2J");
+        for row in 0..3 {
+            assert_eq!(row_text(&term, row), "   ");
+        }
+    }
+
+    #[test]
+    fn sgr_bold_and_color_are_applied_to_written_cells() {
+        let mut term = TerminalEmulator::new(1, 5);
+        term.feed(b"\x1b[1;31mx");
+        let cell = term.grid[0][0];
+        assert_eq!(cell.ch, 'x');
+        assert!(cell.bold);
+        assert_eq!(cell.fg, Some(1));
+    }
+
+    #[test]
+    fn bare_sgr_reset_clears_previous_attributes() {
+        let mut term = TerminalEmulator::new(1, 5);
+        term.feed(b"\x1b[1;31mx\x1b[my");
+        assert!(term.grid[0][0].bold);
+        assert!(!term.grid[0][1].bold);
+        assert_eq!(term.grid[0][1].fg, None);
+    }
+
+    #[test]
+    fn scrolling_past_the_last_row_pushes_it_into_scrollback() {
+        let mut term = TerminalEmulator::new(2, 3);
+        term.feed(b"a\r\nb\r\nc");
+        assert_eq!(term.scrollback.len(), 1);
+        assert_eq!(row_text(&term, 0).trim_end(), "b");
+        assert_eq!(row_text(&term, 1).trim_end(), "c");
+    }
+
+    #[test]
+    fn take_dirty_rows_drains_and_resets() {
+        let mut term = TerminalEmulator::new(3, 3);
+        term.feed(b"a\r\nb");
+        let dirty = term.take_dirty_rows();
+        let rows: Vec<usize> = dirty.iter().map(|(r, _)| *r).collect();
+        assert!(rows.contains(&0));
+        assert!(rows.contains(&1));
+        assert!(term.take_dirty_rows().is_empty());
+    }
+
+    #[test]
+    fn resize_clamps_cursor_and_marks_everything_dirty() {
+        let mut term = TerminalEmulator::new(5, 5);
+        term.feed(b"\x1b[5;5H");
+        term.take_dirty_rows();
+        term.resize(2, 2);
+        assert_eq!((term.cursor_row, term.cursor_col), (1, 1));
+        assert_eq!(term.take_dirty_rows().len(), 2);
+    }
+}